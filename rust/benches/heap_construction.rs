@@ -0,0 +1,33 @@
+use std::time::{Duration, Instant};
+
+use rust::calepinage::{Plank, PlankHeap};
+
+// Times the construction of 100k planks heaps, whole boards of 1000 different lengths.
+// Runs with `cargo bench --bench heap_construction`.
+
+const PLANKS: usize = 100_000;
+const RUNS: u32 = 10;
+
+fn time<T>(name: &str, build: impl Fn() -> T) {
+    let mut total = Duration::default();
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        std::hint::black_box(build());
+        total += start.elapsed();
+    }
+    println!("{:<12} {:>10.3} ms", name, total.as_secs_f64() * 1000.0 / RUNS as f64);
+}
+
+fn main() {
+    let planks: Vec<Plank> = (0..PLANKS).map(|index| Plank::new(1000 + index % 1000).unwrap()).collect();
+
+    time("add", || (0..PLANKS).fold(PlankHeap::new(), |heap, index| heap.add(1, 1000 + index % 1000)));
+    time("push", || {
+        let mut heap = PlankHeap::new();
+        for plank in planks.iter() {
+            heap.push(plank.clone()).unwrap();
+        }
+        heap
+    });
+    time("from_planks", || PlankHeap::from_planks(planks.clone()));
+}
@@ -0,0 +1,237 @@
+use std::io::Write;
+use std::process::exit;
+
+use rust::bom::bom;
+use rust::calepinage::Calepinage;
+use rust::cut_list::{cut_schedule, miter_schedule, notch_schedule, placements_csv};
+use rust::patterns::{PatternError, Preset};
+use rust::project::Project;
+use rust::render;
+
+// The calepinage command: lays the zones of a project file, see `rust::config`, and writes
+// the plan, its drawings, the checks of a saved plan or the bill of materials.
+
+const USAGE: &str = "usage:
+    calepinage plan <project> [--format text|csv|cuts|json] [--pattern <pattern>]
+    calepinage render <project> [--format ascii|svg|dxf|png|pdf] [--zone <zone>] [--output <file>] [--pattern <pattern>]
+    calepinage validate <project> <plan.json>
+    calepinage bom <project> [--pattern <pattern>]
+
+patterns: random, half_bond, third_bond, quarter_stagger, symmetric";
+
+/// Width of a row for decks whose boards width isn't known, like the render options default.
+const ROW_WIDTH: usize = 100;
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Plan { project: String, format: String, pattern: Option<Preset> },
+    Render { project: String, format: String, zone: usize, output: Option<String>, pattern: Option<Preset> },
+    Validate { project: String, plan: String },
+    Bom { project: String, pattern: Option<Preset> },
+}
+
+fn parse(args: &[String]) -> Result<Command, String> {
+    let mut positional = vec![];
+    let mut format = None;
+    let mut zone = 0;
+    let mut output = None;
+    let mut pattern = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--format" => format = Some(value()?),
+            "--zone" => zone = value()?.parse().map_err(|_| "the zone is the index of a deck".to_string())?,
+            "--output" => output = Some(value()?),
+            "--pattern" => pattern = Some(value()?.parse().map_err(|error: PatternError| error.to_string())?),
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            _ => positional.push(arg.clone()),
+        }
+    }
+    let mut positional = positional.into_iter();
+    let subcommand = positional.next().ok_or_else(|| "missing command".to_string())?;
+    let project = positional.next().ok_or_else(|| "missing project file".to_string())?;
+    let command = match subcommand.as_str() {
+        "plan" => Command::Plan {
+            project,
+            format: format.unwrap_or_else(|| "text".to_string()),
+            pattern,
+        },
+        "render" => Command::Render {
+            project,
+            format: format.unwrap_or_else(|| "ascii".to_string()),
+            zone,
+            output,
+            pattern,
+        },
+        "validate" => Command::Validate {
+            project,
+            plan: positional.next().ok_or_else(|| "missing plan file".to_string())?,
+        },
+        "bom" => Command::Bom { project, pattern },
+        other => return Err(format!("unknown command {}", other)),
+    };
+    match positional.next() {
+        Some(extra) => Err(format!("unexpected argument {}", extra)),
+        None => Ok(command),
+    }
+}
+
+/// Loads the project, its decks laid with the preset pattern along its longest planks when
+/// one is given.
+fn load(project: &str, pattern: Option<Preset>) -> Result<Project, String> {
+    let mut project = Project::load(project).map_err(|error| error.to_string())?;
+    if let Some(preset) = pattern {
+        let plank_length = project.plank_heap.planks().map(|plank| plank.length).max().unwrap_or(0);
+        project.options = project.options.with_pattern(preset.pattern(plank_length, 0));
+    }
+    Ok(project)
+}
+
+fn calepine(project: &Project) -> Result<Vec<Calepinage>, String> {
+    project
+        .calepine()
+        .map_err(|failure| failure.to_string())
+}
+
+/// Runs the command, returning what to write on the standard output and whether the
+/// command succeeded.
+fn run(command: Command) -> Result<(Vec<u8>, bool), String> {
+    match command {
+        Command::Plan { project, format, pattern } => {
+            let project = load(&project, pattern)?;
+            let calepinages = calepine(&project)?;
+            let out = match format.as_str() {
+                "text" => calepinages.iter().map(|calepinage| format!("{}\n", calepinage)).collect(),
+                "csv" => calepinages.iter().map(placements_csv).collect(),
+                "cuts" => calepinages
+                    .iter()
+                    .zip(project.decks.iter())
+                    .flat_map(|(calepinage, deck)| {
+                        let cuts = cut_schedule(calepinage).into_iter().map(|group| format!("{}\n", group));
+                        let miters = miter_schedule(calepinage).into_iter().map(|group| format!("{}\n", group));
+                        let notches = notch_schedule(calepinage, deck, &project.options)
+                            .into_iter()
+                            .map(|notch| format!("{}\n", notch));
+                        cuts.chain(miters).chain(notches).collect::<Vec<String>>()
+                    })
+                    .collect(),
+                "json" => serde_json::to_string_pretty(&calepinages).map_err(|error| error.to_string())? + "\n",
+                other => return Err(format!("unknown plan format {}", other)),
+            };
+            Ok((out.into_bytes(), true))
+        }
+        Command::Render {
+            project,
+            format,
+            zone,
+            output,
+            pattern,
+        } => {
+            let project = load(&project, pattern)?;
+            let calepinages = calepine(&project)?;
+            let (calepinage, deck) = calepinages
+                .get(zone)
+                .zip(project.decks.get(zone))
+                .ok_or_else(|| format!("the project has no zone {}", zone))?;
+            let drawing = match format.as_str() {
+                "ascii" => render::ascii(calepinage, deck).into_bytes(),
+                "svg" => render::svg(calepinage, deck, &render::SvgOptions::default()).into_bytes(),
+                "dxf" => render::dxf(calepinage, deck, ROW_WIDTH).into_bytes(),
+                #[cfg(feature = "image")]
+                "png" => {
+                    render::png(calepinage, deck, &render::PngOptions::default()).map_err(|error| error.to_string())?
+                }
+                #[cfg(feature = "pdf")]
+                "pdf" => render::pdf(calepinage, deck, ROW_WIDTH),
+                #[cfg(not(feature = "image"))]
+                "png" => return Err("png drawings need the image feature".to_string()),
+                #[cfg(not(feature = "pdf"))]
+                "pdf" => return Err("pdf plan sheets need the pdf feature".to_string()),
+                other => return Err(format!("unknown render format {}", other)),
+            };
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, drawing).map_err(|error| format!("{}: {}", path, error))?;
+                    Ok((vec![], true))
+                }
+                None => Ok((drawing, true)),
+            }
+        }
+        Command::Validate { project, plan } => {
+            let project = Project::load(project).map_err(|error| error.to_string())?;
+            let text = std::fs::read_to_string(&plan).map_err(|error| format!("{}: {}", plan, error))?;
+            let calepinages: Vec<Calepinage> =
+                serde_json::from_str(&text).map_err(|error| format!("{}: {}", plan, error))?;
+            if calepinages.len() != project.decks.len() {
+                return Err(format!("{}: {} zones planned for {} decks", plan, calepinages.len(), project.decks.len()));
+            }
+            let mut out = String::new();
+            for (zone, (calepinage, deck)) in calepinages.iter().zip(project.decks.iter()).enumerate() {
+                for violation in calepinage.validate(deck, &project.options) {
+                    out.push_str(&format!("zone {}: {}\n", zone, violation));
+                }
+            }
+            let valid = out.is_empty();
+            Ok((out.into_bytes(), valid))
+        }
+        Command::Bom { project, pattern } => {
+            let project = load(&project, pattern)?;
+            let calepinages = calepine(&project)?;
+            let substructure = project.options.substructure.as_ref();
+            let out: String = calepinages
+                .iter()
+                .zip(project.decks.iter())
+                .map(|(calepinage, deck)| bom(calepinage, deck, substructure).to_string())
+                .collect();
+            Ok((out.into_bytes(), true))
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = parse(&args).unwrap_or_else(|error| {
+        eprintln!("{}\n{}", error, USAGE);
+        exit(2)
+    });
+    match run(command) {
+        Ok((out, success)) => {
+            std::io::stdout().write_all(&out).expect("the standard output is closed");
+            exit(if success { 0 } else { 1 })
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(1)
+        }
+    }
+}
+
+#[test]
+fn parse_should_read_options_anywhere() {
+    let args: Vec<String> = ["render", "--zone", "1", "project.toml", "--format", "svg", "--pattern", "third-bond"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+    assert_eq!(
+        Ok(Command::Render {
+            project: "project.toml".to_string(),
+            format: "svg".to_string(),
+            zone: 1,
+            output: None,
+            pattern: Some(Preset::ThirdBond)
+        }),
+        parse(&args)
+    );
+}
+
+#[test]
+fn parse_should_reject_missing_arguments() {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>();
+
+    assert_eq!(Err("missing plan file".to_string()), parse(&args(&["validate", "project.toml"])));
+    assert_eq!(Err("--format needs a value".to_string()), parse(&args(&["plan", "project.toml", "--format"])));
+    assert_eq!(Err("unknown command lay".to_string()), parse(&args(&["lay", "project.toml"])));
+    assert!(parse(&args(&["plan", "project.toml", "--pattern", "zigzag"])).is_err());
+}
@@ -0,0 +1,168 @@
+use crate::calepinage::{Calepinage, Deck, Plank};
+#[cfg(test)]
+use crate::calepinage::{Cut, Line};
+use crate::hardware::hardware;
+#[cfg(test)]
+use crate::plank_line;
+use crate::substructure::Substructure;
+
+// Bill of materials of a calepinage: the boards to buy, offcuts laid or cut again coming
+// from boards already bought, and with a substructure the fasteners and joists.
+
+/// `count` boards of the same size.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Boards {
+    pub count: usize,
+    pub length: usize,
+    pub width: usize,
+}
+
+impl Boards {
+    pub fn total_length(&self) -> usize {
+        self.count * self.length
+    }
+}
+
+/// Joists under the whole deck width, as long as the deck is wide when its physical width
+/// is known.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Joists {
+    pub count: usize,
+    pub length: Option<usize>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Bom {
+    /// Longest boards first.
+    pub boards: Vec<Boards>,
+    pub fasteners: Option<usize>,
+    pub joists: Option<Joists>,
+}
+
+impl Bom {
+    pub fn board_count(&self) -> usize {
+        self.boards.iter().map(|boards| boards.count).sum()
+    }
+
+    pub fn total_board_length(&self) -> usize {
+        self.boards.iter().map(|boards| boards.total_length()).sum()
+    }
+}
+
+impl std::fmt::Display for Bom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for boards in self.boards.iter() {
+            write!(f, "{} x {} boards", boards.count, boards.length)?;
+            if boards.width > 1 {
+                write!(f, " of width {}", boards.width)?;
+            }
+            writeln!(f)?;
+        }
+        if let Some(fasteners) = self.fasteners {
+            writeln!(f, "{} fasteners", fasteners)?;
+        }
+        if let Some(joists) = &self.joists {
+            match joists.length {
+                Some(length) => writeln!(f, "{} x {} joists", joists.count, length)?,
+                None => writeln!(f, "{} joists", joists.count)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn bom(calepinage: &Calepinage, deck: &Deck, substructure: Option<&Substructure>) -> Bom {
+    let mut pieces: Vec<Plank> = calepinage.cuts.iter().map(|cut| cut.piece()).collect();
+    let mut offcuts: Vec<Plank> = calepinage.cuts.iter().map(|cut| cut.offcut_plank(calepinage.kerf)).collect();
+    let laid = calepinage
+        .lines
+        .iter()
+        .chain(calepinage.border.iter())
+        .chain(calepinage.breakers.iter().map(|breaker| &breaker.line))
+        .flat_map(|line| line.planks.iter())
+        .filter(|plank| !take(&mut pieces, plank));
+    let cut = calepinage.cuts.iter().map(|cut| &cut.plank);
+    let bought = laid.chain(cut).filter(|plank| !take(&mut offcuts, plank));
+
+    let mut boards: Vec<Boards> = vec![];
+    for plank in bought {
+        match boards.iter_mut().find(|boards| boards.length == plank.length && boards.width == plank.width) {
+            Some(boards) => boards.count += 1,
+            None => boards.push(Boards {
+                count: 1,
+                length: plank.length,
+                width: plank.width,
+            }),
+        }
+    }
+    boards.sort_by(|a, b| b.length.cmp(&a.length).then(b.width.cmp(&a.width)));
+
+    Bom {
+        boards,
+        fasteners: substructure.map(|substructure| hardware(calepinage, substructure).fasteners),
+        joists: substructure.map(|substructure| Joists {
+            count: joist_count(deck.length, substructure),
+            length: deck.board_width.map(|_| deck.width),
+        }),
+    }
+}
+
+/// Removes a plank from the given ones, if it is one of them.
+fn take(planks: &mut Vec<Plank>, plank: &Plank) -> bool {
+    match planks.iter().position(|candidate| candidate == plank) {
+        Some(index) => {
+            planks.remove(index);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Joists from the first one to the deck end.
+fn joist_count(deck_length: usize, substructure: &Substructure) -> usize {
+    match deck_length.checked_sub(substructure.first_joist_offset) {
+        Some(length) => length / substructure.joist_spacing + 1,
+        None => 0,
+    }
+}
+
+#[test]
+fn bom_should_not_buy_the_offcuts_laid_again() {
+    let deck = Deck::physical(1200, 300, 150).unwrap();
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![Plank::new(800).unwrap(), Plank::new(400).unwrap()])
+        .with_line(plank_line![Plank::new(1200).unwrap()])
+        .with_cut(Cut {
+            plank: Plank::new(1200).unwrap(),
+            at: 800,
+        });
+    let substructure = Substructure::new(400, 0).unwrap();
+
+    let bom = bom(&calepinage, &deck, Some(&substructure));
+
+    assert_eq!(
+        vec![Boards {
+            count: 2,
+            length: 1200,
+            width: 1
+        }],
+        bom.boards
+    );
+    assert_eq!(Some(Joists { count: 4, length: Some(300) }), bom.joists);
+    assert_eq!("2 x 1200 boards\n20 fasteners\n4 x 300 joists\n", bom.to_string());
+}
+
+#[test]
+fn bom_should_group_boards_by_size() {
+    let calepinage = Calepinage::default()
+        .with_line(Line::new(vec![Plank::new(5).unwrap(), Plank::new(5).unwrap()]))
+        .with_line(plank_line![Plank::new(3).unwrap(), Plank::new(5).unwrap(), Plank::new(2).unwrap()]);
+
+    let bom = bom(&calepinage, &Deck::new(10, 2).unwrap(), None);
+
+    let sizes: Vec<(usize, usize)> = bom.boards.iter().map(|boards| (boards.count, boards.length)).collect();
+    assert_eq!(vec![(3, 5), (1, 3), (1, 2)], sizes);
+    assert_eq!(5, bom.board_count());
+    assert_eq!(20, bom.total_board_length());
+    assert_eq!(None, bom.fasteners);
+}
@@ -1,5 +1,13 @@
-use std::collections::HashSet;
+use std::cell::{Cell, RefCell};
 use std::fmt::Formatter;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::gaps::Gaps;
+use crate::messages::{Language, Localize};
+use crate::substructure::Substructure;
+#[cfg(test)]
 use spectral::assert_that;
 
 // This is a deck with length = 6 and width = 4
@@ -15,351 +23,4729 @@ use spectral::assert_that;
 // |  |--|p6|  |
 // |  |p4|  |  |
 // \===========/
+//
+// `render::ascii` draws calepinages the same way, rows being text lines.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Deck {
     pub length: usize,
+    /// Number of rows, or the physical width when the width of the boards is known.
+    pub width: usize,
+    /// Width of a row, a plank covering `plank.width` rows.
+    pub board_width: Option<usize>,
+    pub cutouts: Vec<Cutout>,
+    /// Shape of non rectangular decks, the deck being the bounding box of the outline.
+    pub outline: Option<Outline>,
+    /// Railing posts along the deck edges, notched into the boards they stand on.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub posts: Vec<Post>,
+    /// Direction the deck slopes down to drain, along its length or its width.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub slope: Option<Orientation>,
+}
+
+impl std::fmt::Display for Deck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.board_width {
+            Some(board_width) => write!(f, "deck {} x {}, boards {} wide", self.length, self.width, board_width)?,
+            None => write!(f, "deck {} x {} rows", self.length, self.width)?,
+        }
+        if let Some(outline) = &self.outline {
+            write!(f, ", {}", outline)?;
+        }
+        for cutout in self.cutouts.iter() {
+            write!(f, ", {}", cutout)?;
+        }
+        for post in self.posts.iter() {
+            write!(f, ", {}", post)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rectangular part of the deck left uncovered (post, tree, hatch), from `start` to
+/// `start + length` along the deck length, on `rows` rows from `first_row`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cutout {
+    pub start: usize,
+    pub length: usize,
+    pub first_row: usize,
+    pub rows: usize,
+}
+
+impl Cutout {
+    fn covers_row(&self, row: usize) -> bool {
+        self.first_row <= row && row < self.first_row + self.rows
+    }
+}
+
+impl std::fmt::Display for Cutout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cutout from {} to {} on rows {} to {}",
+            self.start,
+            self.start + self.length,
+            self.first_row,
+            (self.first_row + self.rows).saturating_sub(1)
+        )
+    }
+}
+
+/// Direction boards run on a deck, or the deck slopes down.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Orientation {
+    AlongLength,
+    AlongWidth,
+}
+
+/// Side of a rectangular deck: along the first or the last row, or at the start or the end
+/// of the rows.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PostEdge {
+    First,
+    Last,
+    Start,
+    End,
+}
+
+/// Railing post standing on the edge, centered on `at` along the edge, `width` wide along
+/// the edge and `depth` deep into the deck. Positions along the start and end edges are
+/// measured like the deck width.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Post {
+    pub edge: PostEdge,
+    pub at: usize,
+    pub width: usize,
+    pub depth: usize,
+}
+
+impl Post {
+    /// Where the post starts and ends along the edge.
+    pub fn span(&self) -> (usize, usize) {
+        let start = self.at.saturating_sub(self.width / 2);
+        (start, start + self.width)
+    }
+}
+
+impl std::fmt::Display for Post {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "post {} x {} at {} on the {:?} edge", self.width, self.depth, self.at, self.edge)
+    }
+}
+
+/// Rectilinear polygon given by its vertices in order, x along the deck length and y
+/// counted in rows.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Vec<(usize, usize)>", into = "Vec<(usize, usize)>"))]
+pub struct Outline {
+    vertices: Vec<(usize, usize)>,
+}
+
+impl Outline {
+    pub fn new(vertices: Vec<(usize, usize)>) -> Result<Self, OutlineError> {
+        if vertices.len() < 4 || vertices.len() % 2 == 1 {
+            return Err(OutlineError::VertexCount(vertices.len()));
+        }
+        let edges = vertices.iter().zip(vertices.iter().cycle().skip(1));
+        for (&(x1, y1), &(x2, y2)) in edges.clone() {
+            if (x1 == x2) == (y1 == y2) {
+                return Err(OutlineError::SlantedEdge {
+                    from: (x1, y1),
+                    to: (x2, y2),
+                });
+            }
+        }
+        let horizontal_edges = edges.filter(|((_, y1), (_, y2))| y1 == y2).count();
+        if horizontal_edges * 2 != vertices.len() {
+            return Err(OutlineError::NonAlternatingEdges);
+        }
+        Ok(Outline { vertices })
+    }
+
+    /// Corners of the outline, x along the deck length and y counted in rows.
+    pub fn vertices(&self) -> &[(usize, usize)] {
+        &self.vertices
+    }
+
+    /// Surface inside the outline, x units times rows.
+    pub fn area(&self) -> usize {
+        let doubled: isize = self
+            .edges()
+            .map(|(&(x1, y1), &(x2, y2))| x1 as isize * y2 as isize - x2 as isize * y1 as isize)
+            .sum();
+        doubled.unsigned_abs() / 2
+    }
+
+    pub fn perimeter(&self) -> usize {
+        self.edges().map(|(&(x1, y1), &(x2, y2))| x1.abs_diff(x2) + y1.abs_diff(y2)).sum()
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (&(usize, usize), &(usize, usize))> + '_ {
+        self.vertices.iter().zip(self.vertices.iter().cycle().skip(1))
+    }
+
+    fn bounds(&self) -> (usize, usize) {
+        let length = self.vertices.iter().map(|(x, _)| *x).max().unwrap_or(0);
+        let width = self.vertices.iter().map(|(_, y)| *y).max().unwrap_or(0);
+        (length, width)
+    }
+
+    /// Parts of the given row inside the outline, as (start, end) pairs, sampled in the
+    /// middle of the row.
+    fn row_intervals(&self, row: usize) -> Vec<(usize, usize)> {
+        let middle = 2 * row + 1;
+        let mut crossings: Vec<usize> = self
+            .edges()
+            .filter(|((x1, y1), (x2, y2))| x1 == x2 && (2 * y1).min(2 * y2) < middle && middle < (2 * y1).max(2 * y2))
+            .map(|((x, _), _)| *x)
+            .collect();
+        crossings.sort_unstable();
+        crossings.chunks(2).filter(|pair| pair.len() == 2).map(|pair| (pair[0], pair[1])).collect()
+    }
+}
+
+impl std::fmt::Display for Outline {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let vertices: Vec<String> = self.vertices.iter().map(|(x, y)| format!("({}, {})", x, y)).collect();
+        write!(f, "outline {}", vertices.join(", "))
+    }
+}
+
+impl std::convert::TryFrom<Vec<(usize, usize)>> for Outline {
+    type Error = OutlineError;
+
+    fn try_from(vertices: Vec<(usize, usize)>) -> Result<Self, Self::Error> {
+        Outline::new(vertices)
+    }
+}
+
+impl From<Outline> for Vec<(usize, usize)> {
+    fn from(outline: Outline) -> Self {
+        outline.vertices
+    }
+}
+
+/// A dimension of a deck or of a plank.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Dimension {
+    Length,
+    Width,
+    BoardWidth,
+    Thickness,
+}
+
+impl std::fmt::Display for Dimension {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutlineError {
+    /// An outline needs an even number of vertices, at least 4.
+    VertexCount(usize),
+    /// The edge between the two vertices is neither horizontal nor vertical.
+    SlantedEdge { from: (usize, usize), to: (usize, usize) },
+    NonAlternatingEdges,
+}
+
+impl std::fmt::Display for OutlineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for OutlineError {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeckError {
+    ZeroDimension(Dimension),
+    /// The dimension was never given to the `DeckBuilder`.
+    MissingDimension(Dimension),
+    /// The gaps at both ends of the dimension leave nothing to lay.
+    GapTooWide { gap: usize, dimension: Dimension },
+    TooLong { length: usize, max_length: usize },
+    Outline(OutlineError),
+}
+
+impl std::fmt::Display for DeckError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for DeckError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DeckError::Outline(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<OutlineError> for DeckError {
+    fn from(error: OutlineError) -> Self {
+        DeckError::Outline(error)
+    }
+}
+
+impl Deck {
+    pub const MAX_LENGTH: usize = 1_000_000;
+
+    pub fn new(length: usize, width: usize) -> Result<Self, DeckError> {
+        if length == 0 {
+            Err(DeckError::ZeroDimension(Dimension::Length))
+        } else if width == 0 {
+            Err(DeckError::ZeroDimension(Dimension::Width))
+        } else if length > Self::MAX_LENGTH {
+            Err(DeckError::TooLong {
+                length,
+                max_length: Self::MAX_LENGTH,
+            })
+        } else {
+            Ok(Deck {
+                length,
+                width,
+                board_width: None,
+                cutouts: vec![],
+                outline: None,
+                posts: vec![],
+                slope: None,
+            })
+        }
+    }
+
+    /// A deck whose width is measured like its length, the number of rows depending on
+    /// the width of the boards.
+    pub fn physical(length: usize, width: usize, board_width: usize) -> Result<Self, DeckError> {
+        if board_width == 0 {
+            return Err(DeckError::ZeroDimension(Dimension::BoardWidth));
+        }
+        Deck::new(length, width).map(|deck| Deck {
+            board_width: Some(board_width),
+            ..deck
+        })
+    }
+
+    /// A deck of the outline bounding box, rows only covering the inside of the outline.
+    pub fn from_outline(outline: Outline) -> Result<Self, DeckError> {
+        let (length, width) = outline.bounds();
+        Deck::new(length, width).map(|deck| Deck {
+            outline: Some(outline),
+            ..deck
+        })
+    }
+
+    pub fn with_cutout(mut self, cutout: Cutout) -> Self {
+        self.cutouts.push(cutout);
+        self
+    }
+
+    pub fn with_post(mut self, post: Post) -> Self {
+        self.posts.push(post);
+        self
+    }
+
+    pub fn with_slope(mut self, slope: Orientation) -> Self {
+        self.slope = Some(slope);
+        self
+    }
+
+    /// Parts of the given row from `start` to `start + length` inside the outline and left
+    /// by the cutouts.
+    pub(crate) fn row_segments(&self, row: usize, start: usize, length: usize) -> Vec<RowGoal> {
+        let end = start + length;
+        let intervals = match &self.outline {
+            None => vec![(start, end)],
+            Some(outline) => outline
+                .row_intervals(row)
+                .into_iter()
+                .map(|(interval_start, interval_end)| (interval_start.max(start), interval_end.min(end)))
+                .filter(|(interval_start, interval_end)| interval_start < interval_end)
+                .collect(),
+        };
+        let mut cutouts: Vec<&Cutout> = self.cutouts.iter().filter(|cutout| cutout.covers_row(row)).collect();
+        cutouts.sort_by_key(|cutout| cutout.start);
+
+        let mut segments = vec![];
+        for (start, end) in intervals {
+            let mut segment_start = start;
+            for cutout in cutouts.iter() {
+                let cutout_start = cutout.start.clamp(start, end);
+                if cutout_start > segment_start {
+                    segments.push(RowGoal {
+                        row,
+                        start: segment_start,
+                        length: cutout_start - segment_start,
+                    });
+                }
+                segment_start = segment_start.max((cutout.start + cutout.length).min(end));
+            }
+            if segment_start < end {
+                segments.push(RowGoal {
+                    row,
+                    start: segment_start,
+                    length: end - segment_start,
+                });
+            }
+        }
+        segments
+    }
+
+    /// Surface of the deck, length times width or inside its outline, cutouts included.
+    pub fn area(&self) -> usize {
+        match &self.outline {
+            Some(outline) => outline.area(),
+            None => self.length * self.width,
+        }
+    }
+
+    pub fn perimeter(&self) -> usize {
+        match &self.outline {
+            Some(outline) => outline.perimeter(),
+            None => 2 * (self.length + self.width),
+        }
+    }
+
+    /// Rows of planks `plank_width` wide and `gap` apart covering the deck width, the last
+    /// one ripped when the width isn't a multiple, like `LastRow::Rip`. A remaining width
+    /// not wider than the gap is left as a gap. None for planks of no width.
+    pub fn rows_needed(&self, plank_width: usize, gap: usize) -> Option<usize> {
+        if plank_width == 0 {
+            return None;
+        }
+        let (full_rows, remaining_width) = fit_rows(self.width, plank_width, gap);
+        if remaining_width <= gap {
+            Some(full_rows)
+        } else {
+            Some(full_rows + 1)
+        }
+    }
+
+    /// How the boards of the last row of a physical deck are ripped when the width isn't a
+    /// multiple of the boards width and the side gap, none when it is.
+    pub fn rip(&self, side_gap: usize) -> Option<Rip> {
+        let board_width = self.board_width?;
+        let (full_rows, remaining_width) = fit_rows(self.width, board_width, side_gap);
+        if remaining_width <= side_gap {
+            return None;
+        }
+        let width = remaining_width - side_gap;
+        // giving up a full row, the first and last rows share its width and the rip
+        let balanced = (full_rows > 0).then(|| {
+            let last_width = (width + board_width) / 2;
+            let first_width = width + board_width - last_width;
+            BalancedRip {
+                shift: board_width - first_width,
+                first_width,
+                last_width,
+            }
+        });
+        Some(Rip { width, balanced })
+    }
+
+    /// Distance from the deck side to the given row, physical decks only.
+    pub fn row_offset(&self, row: usize, side_gap: usize) -> Option<usize> {
+        self.board_width.map(|board_width| row * (board_width + side_gap))
+    }
+}
+
+#[test]
+fn row_segments_should_skip_cutouts_of_the_row() {
+    let cutout = |start, length, first_row| Cutout {
+        start,
+        length,
+        first_row,
+        rows: 1,
+    };
+    let deck = Deck::new(20, 2)
+        .unwrap()
+        .with_cutout(cutout(12, 3, 0))
+        .with_cutout(cutout(0, 2, 0))
+        .with_cutout(cutout(10, 4, 0))
+        .with_cutout(cutout(5, 5, 1));
+
+    let segments: Vec<(usize, usize)> =
+        deck.row_segments(0, 0, 20).iter().map(|goal| (goal.start, goal.length)).collect();
+
+    assert_eq!(vec![(2, 8), (15, 5)], segments);
+    assert_eq!(2, deck.row_segments(1, 0, 20).len());
+}
+
+#[test]
+fn outline_should_give_l_shaped_rows() {
+    let outline = Outline::new(vec![(0, 0), (10, 0), (10, 2), (4, 2), (4, 3), (0, 3)]).unwrap();
+    let deck = Deck::from_outline(outline).unwrap();
+
+    let segments: Vec<(usize, usize, usize)> = (0..deck.width)
+        .flat_map(|row| deck.row_segments(row, 0, deck.length))
+        .map(|goal| (goal.row, goal.start, goal.length))
+        .collect();
+
+    assert_eq!((10, 3), (deck.length, deck.width));
+    assert_eq!(vec![(0, 0, 10), (1, 0, 10), (2, 0, 4)], segments);
+}
+
+#[test]
+fn outline_should_split_u_shaped_rows() {
+    let outline = Outline::new(vec![(0, 0), (9, 0), (9, 2), (6, 2), (6, 1), (3, 1), (3, 2), (0, 2)]).unwrap();
+
+    assert_eq!(vec![(0, 9)], outline.row_intervals(0));
+    assert_eq!(vec![(0, 3), (6, 9)], outline.row_intervals(1));
+}
+
+#[test]
+fn deck_should_tell_which_dimension_is_invalid() {
+    assert_eq!(Err(DeckError::ZeroDimension(Dimension::Width)), Deck::new(10, 0).map(|_| ()));
+    assert_eq!(Err(DeckError::ZeroDimension(Dimension::BoardWidth)), Deck::physical(10, 10, 0).map(|_| ()));
+    let error = Deck::new(Deck::MAX_LENGTH + 1, 1).unwrap_err();
+    assert_eq!("a deck of length 1000001 is longer than the max length 1000000", error.to_string());
+    let error: Box<dyn std::error::Error> = Box::new(DeckError::Outline(OutlineError::NonAlternatingEdges));
+    assert!(error.source().is_some());
+}
+
+#[test]
+fn outline_should_be_rectilinear() {
+    assert_eq!(Err(OutlineError::VertexCount(3)), Outline::new(vec![(0, 0), (10, 0), (10, 2)]));
+    assert_eq!(
+        Err(OutlineError::SlantedEdge { from: (10, 0), to: (8, 2) }),
+        Outline::new(vec![(0, 0), (10, 0), (8, 2), (0, 2)])
+    );
+}
+
+#[test]
+fn deck_geometry_should_follow_the_outline() {
+    let l_shaped = Outline::new(vec![(0, 0), (10, 0), (10, 2), (4, 2), (4, 5), (0, 5)]).unwrap();
+    let deck = Deck::from_outline(l_shaped).unwrap();
+
+    assert_eq!(32, deck.area());
+    assert_eq!(30, deck.perimeter());
+    assert_eq!(50, Deck::new(10, 5).unwrap().area());
+    assert_eq!(30, Deck::new(10, 5).unwrap().perimeter());
+}
+
+#[test]
+fn rows_needed_should_rip_the_last_row() {
+    let deck = Deck::physical(4000, 1200, 140).unwrap();
+
+    assert_eq!(Some(8), deck.rows_needed(150, 0));
+    assert_eq!(Some(8), deck.rows_needed(145, 5));
+    assert_eq!(Some(9), deck.rows_needed(140, 5));
+}
+
+#[test]
+fn rows_needed_should_need_planks_with_a_width() {
+    let deck = Deck::new(10, 5).unwrap();
+
+    assert_eq!(None, deck.rows_needed(0, 0));
+    assert_eq!(None, deck.rows_needed(0, 5));
+}
+
+#[test]
+fn rip_should_balance_both_sides() {
+    let deck = Deck::physical(4000, 1200, 140).unwrap();
+
+    let rip = deck.rip(5).unwrap();
+
+    assert_eq!(40, rip.width);
+    assert_eq!(
+        Some(BalancedRip {
+            shift: 50,
+            first_width: 90,
+            last_width: 90
+        }),
+        rip.balanced
+    );
+    assert_eq!(None, Deck::physical(10, 280, 140).unwrap().rip(0));
+    assert_eq!(None, Deck::physical(10, 100, 140).unwrap().rip(0).unwrap().balanced);
+}
+
+#[test]
+fn row_offset_should_include_side_gaps() {
+    let deck = Deck::physical(10, 330, 140).unwrap();
+
+    assert_eq!(Some(300), deck.row_offset(2, 10));
+    assert_eq!(None, Deck::new(10, 3).unwrap().row_offset(2, 10));
+}
+
+/// Planks compare by length first, then by width and their other attributes in order.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plank {
+    pub length: usize,
+    /// Number of deck rows the plank covers, 1 by default.
     pub width: usize,
+    /// Planks of a deck must all be as thick, when known.
+    pub thickness: Option<usize>,
+    /// Production lot, planks of different lots differing in color.
+    pub lot: Option<LotId>,
+    pub grade: Option<Grade>,
+    /// Mark written on the board, telling which board of the heap goes where.
+    pub label: Option<String>,
+    /// Positions of the knots and other defects, from the plank start, in increasing order.
+    pub defects: Vec<usize>,
+    /// Price of the board, in the smallest currency unit. Pieces and offcuts of a cut
+    /// board are free, the board being paid once.
+    pub price: Option<usize>,
+    /// Length the plank may be trimmed down to when squaring its end, a line a bit shorter
+    /// than the plank taking it trimmed even without cutting.
+    pub min_length: Option<usize>,
+}
+
+/// A plank one row wide, of no length and without any other attribute.
+impl Default for Plank {
+    fn default() -> Self {
+        Plank {
+            length: 0,
+            width: 1,
+            thickness: None,
+            lot: None,
+            grade: None,
+            label: None,
+            defects: vec![],
+            price: None,
+            min_length: None,
+        }
+    }
+}
+
+/// The label and length of the plank, like in lines, followed by what isn't the default.
+impl std::fmt::Display for Plank {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(label) = &self.label {
+            write!(f, "{} ", label)?;
+        }
+        write!(f, "{}", self.length)?;
+        let mut details = vec![];
+        if self.width > 1 {
+            details.push(format!("width {}", self.width));
+        }
+        if let Some(thickness) = self.thickness {
+            details.push(format!("thickness {}", thickness));
+        }
+        if let Some(lot) = self.lot {
+            details.push(lot.to_string());
+        }
+        if let Some(grade) = self.grade {
+            details.push(format!("grade {}", grade));
+        }
+        if !self.defects.is_empty() {
+            let defects: Vec<String> = self.defects.iter().map(usize::to_string).collect();
+            details.push(format!("defects at {}", defects.join(", ")));
+        }
+        if let Some(price) = self.price {
+            details.push(format!("price {}", price));
+        }
+        if let Some(min_length) = self.min_length {
+            details.push(format!("trimmable to {}", min_length));
+        }
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Aesthetic quality of a plank, A being the finest.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Grade {
+    A,
+    B,
+    C,
 }
 
-impl Deck {
-    pub const MAX_LENGTH: usize = 1_000_000;
+impl std::fmt::Display for Grade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LotId(pub u32);
+
+impl std::fmt::Display for LotId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "lot {}", self.0)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PlankError {
+    ZeroDimension(Dimension),
+    TooLong { length: usize, max_length: usize },
+    /// The defect lies beyond the end of the plank.
+    DefectOffPlank { defect: usize, length: usize },
+    /// The plank can't be trimmed to a length longer than itself.
+    MinLengthOverLength { min_length: usize, length: usize },
+    /// The planks are too many for the heap to count them or their total length.
+    TooManyPlanks { count: usize, length: usize },
+}
+
+impl std::fmt::Display for PlankError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for PlankError {}
+
+impl Plank {
+    pub const MAX_LENGTH: usize = 10000;
+
+    pub fn new(length: usize) -> Result<Self, PlankError> {
+        if length > Self::MAX_LENGTH {
+            Err(PlankError::TooLong {
+                length,
+                max_length: Self::MAX_LENGTH,
+            })
+        } else {
+            Ok(Plank { length, ..Default::default() })
+        }
+    }
+
+    pub fn with_width(self, width: usize) -> Result<Self, PlankError> {
+        if width == 0 {
+            Err(PlankError::ZeroDimension(Dimension::Width))
+        } else {
+            Ok(Plank { width, ..self })
+        }
+    }
+
+    pub fn with_lot(self, lot: LotId) -> Self {
+        Plank { lot: Some(lot), ..self }
+    }
+
+    pub fn with_grade(self, grade: Grade) -> Self {
+        Plank {
+            grade: Some(grade),
+            ..self
+        }
+    }
+
+    pub fn with_label(self, label: &str) -> Self {
+        Plank {
+            label: Some(label.to_string()),
+            ..self
+        }
+    }
+
+    pub fn with_price(self, price: usize) -> Self {
+        Plank {
+            price: Some(price),
+            ..self
+        }
+    }
+
+    pub fn with_min_length(self, min_length: usize) -> Result<Self, PlankError> {
+        if min_length > self.length {
+            Err(PlankError::MinLengthOverLength {
+                min_length,
+                length: self.length,
+            })
+        } else {
+            Ok(Plank {
+                min_length: Some(min_length),
+                ..self
+            })
+        }
+    }
+
+    /// Whether the plank may be trimmed to `length` instead of being cut.
+    fn can_be_trimmed_to(&self, length: usize) -> bool {
+        length < self.length && self.min_length.is_some_and(|min_length| min_length <= length)
+    }
+
+    /// Whether the planks only differ by their label.
+    fn is_like(&self, other: &Plank) -> bool {
+        self.length == other.length
+            && self.width == other.width
+            && self.thickness == other.thickness
+            && self.lot == other.lot
+            && self.grade == other.grade
+            && self.defects == other.defects
+            && self.price == other.price
+            && self.min_length == other.min_length
+    }
+
+    pub fn with_defects(self, defects: Vec<usize>) -> Result<Self, PlankError> {
+        if let Some(&defect) = defects.iter().find(|&&defect| defect > self.length) {
+            return Err(PlankError::DefectOffPlank {
+                defect,
+                length: self.length,
+            });
+        }
+        let mut defects = defects;
+        defects.sort_unstable();
+        Ok(Plank { defects, ..self })
+    }
+
+    /// The plank turned end to end, its defects measured from the other end.
+    pub fn turned(&self) -> Self {
+        let mut defects: Vec<usize> = self.defects.iter().map(|defect| self.length - defect).collect();
+        defects.reverse();
+        Plank {
+            defects,
+            ..self.clone()
+        }
+    }
+
+    /// The part of the plank from `start` to `end`, with the defects it contains.
+    pub(crate) fn part(&self, start: usize, end: usize) -> Self {
+        Plank {
+            length: end - start,
+            defects: self
+                .defects
+                .iter()
+                .filter(|&&defect| start <= defect && defect <= end)
+                .map(|defect| defect - start)
+                .collect(),
+            price: None,
+            min_length: None,
+            ..self.clone()
+        }
+    }
+
+    /// Whether a defect lies closer than `distance` to the start or to the end of the plank.
+    fn has_defect_near(&self, start: bool, distance: usize) -> bool {
+        self.defects.iter().any(|&defect| {
+            let from_end = if start { defect } else { self.length - defect };
+            from_end < distance
+        })
+    }
+
+    /// Checks the dimensions and the defects of a plank built field by field.
+    fn check(&self) -> Result<(), PlankError> {
+        let checked = Plank::new(self.length)?.with_width(self.width)?.with_defects(self.defects.clone())?;
+        match self.thickness {
+            Some(thickness) => checked.with_thickness(thickness).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    pub fn with_thickness(self, thickness: usize) -> Result<Self, PlankError> {
+        if thickness == 0 {
+            Err(PlankError::ZeroDimension(Dimension::Thickness))
+        } else {
+            Ok(Plank {
+                thickness: Some(thickness),
+                ..self
+            })
+        }
+    }
+}
+
+#[test]
+fn plank_should_not_have_zero_width() {
+    assert_eq!(Err(PlankError::ZeroDimension(Dimension::Width)), Plank::new(10).unwrap().with_width(0));
+    assert_eq!(2, Plank::new(10).unwrap().with_width(2).unwrap().width);
+}
+
+/// A term of a heap written like "6x2400 + 900" that can't be read, with the term.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseHeapError {
+    BadCount(String),
+    BadLength(String),
+    /// The term counts more planks than a heap can.
+    Overflow(String),
+    InvalidPlank(PlankError),
+}
+
+impl std::fmt::Display for ParseHeapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for ParseHeapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseHeapError::InvalidPlank(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Planks in order, identical planks following each other being counted instead of
+/// repeated, so that heaps of thousands of identical boards stay small.
+#[derive(Default, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Vec<Plank>", into = "Vec<Plank>"))]
+pub struct PlankHeap {
+    /// Never empty counts, a plank never being counted twice in a row.
+    counts: Vec<(Plank, usize)>,
+    total_length: usize,
+}
+
+impl PlankHeap {
+    /// Shorthand for tests, panicking on lengths `Plank::new` rejects and on more planks than
+    /// the heap can count, see `try_add`.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn add(self, count: usize, length: usize) -> Self {
+        self.try_add(count, length).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Shorthand for tests, panicking on dimensions `Plank::new` and `Plank::with_width`
+    /// reject, see `try_add_with_width`.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn add_with_width(self, count: usize, length: usize, width: usize) -> Self {
+        self.try_add_with_width(count, length, width).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn try_add(self, count: usize, length: usize) -> Result<Self, PlankError> {
+        self.try_add_planks(count, Plank::new(length)?)
+    }
+
+    pub fn try_add_with_width(self, count: usize, length: usize, width: usize) -> Result<Self, PlankError> {
+        self.try_add_planks(count, Plank::new(length)?.with_width(width)?)
+    }
+
+    /// Adds planks like the given one, checked like the `Plank` constructors do.
+    pub fn try_add_planks(mut self, count: usize, plank: Plank) -> Result<Self, PlankError> {
+        plank.check()?;
+        self.try_push_planks(count, plank)?;
+        Ok(self)
+    }
+
+    /// Adds the plank to the heap in place, checked like the `Plank` constructors do.
+    pub fn push(&mut self, plank: Plank) -> Result<(), PlankError> {
+        plank.check()?;
+        self.try_push_planks(1, plank)
+    }
+
+    /// Adds planks the heap can count, like the planks of another heap.
+    fn push_planks(&mut self, count: usize, plank: Plank) {
+        self.try_push_planks(count, plank).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_push_planks(&mut self, count: usize, plank: Plank) -> Result<(), PlankError> {
+        if count == 0 {
+            return Ok(());
+        }
+        let too_many = || PlankError::TooManyPlanks {
+            count,
+            length: plank.length,
+        };
+        self.total_length = count
+            .checked_mul(plank.length)
+            .and_then(|length| self.total_length.checked_add(length))
+            .ok_or_else(too_many)?;
+        match self.counts.last_mut() {
+            Some((last, last_count)) if *last == plank => {
+                *last_count = last_count.checked_add(count).ok_or_else(too_many)?
+            }
+            _ => self.counts.push((plank, count)),
+        }
+        Ok(())
+    }
+
+    pub fn new() -> Self {
+        PlankHeap {
+            counts: vec![],
+            total_length: 0,
+        }
+    }
+
+    /// Parses a heap written like "6x2400 + 4x1800 + 900", counts of planks of a length
+    /// added together, a length alone being a single plank.
+    pub fn parse(spec: &str) -> Result<Self, ParseHeapError> {
+        let mut plank_heap = PlankHeap::new();
+        for term in spec.split('+').map(str::trim).filter(|term| !term.is_empty()) {
+            let (count, length) = match term.split_once(['x', 'X']) {
+                Some((count, length)) => {
+                    let count = count.trim().parse().map_err(|_| ParseHeapError::BadCount(term.to_string()))?;
+                    (count, length.trim())
+                }
+                None => (1, term),
+            };
+            let length = length.parse().map_err(|_| ParseHeapError::BadLength(term.to_string()))?;
+            plank_heap = plank_heap.try_add(count, length).map_err(|error| match error {
+                PlankError::TooManyPlanks { .. } => ParseHeapError::Overflow(term.to_string()),
+                error => ParseHeapError::InvalidPlank(error),
+            })?;
+        }
+        Ok(plank_heap)
+    }
+
+    /// The heap of the planks, checked like the `Plank` constructors do, planks being built
+    /// field by field.
+    pub fn from_planks(planks: Vec<Plank>) -> Result<Self, PlankError> {
+        planks.iter().try_for_each(Plank::check)?;
+        Ok(PlankHeap::of_planks(planks))
+    }
+
+    /// The heap of the counted planks, in order, checked like `from_planks` does.
+    pub fn from_counts(counts: Vec<(Plank, usize)>) -> Result<Self, PlankError> {
+        let mut plank_heap = PlankHeap::new();
+        for (plank, count) in counts {
+            plank.check()?;
+            plank_heap.try_push_planks(count, plank)?;
+        }
+        Ok(plank_heap)
+    }
+
+    /// The heap of planks taken from heaps, hence already checked and counted.
+    fn of_planks(planks: Vec<Plank>) -> Self {
+        PlankHeap::of_counts(planks.into_iter().map(|plank| (plank, 1)).collect())
+    }
+
+    fn of_counts(counts: Vec<(Plank, usize)>) -> Self {
+        let mut plank_heap = PlankHeap::new();
+        for (plank, count) in counts {
+            plank_heap.push_planks(count, plank);
+        }
+        plank_heap
+    }
+
+    /// Every plank of the heap, in order, identical planks being repeated.
+    pub fn planks(&self) -> impl Iterator<Item = &Plank> + '_ {
+        self.counts.iter().flat_map(|(plank, count)| std::iter::repeat_n(plank, *count))
+    }
+
+    /// Every plank of the heap, in order, see `planks`.
+    pub fn into_planks(self) -> Vec<Plank> {
+        self.counts
+            .into_iter()
+            .flat_map(|(plank, count)| std::iter::repeat_n(plank, count))
+            .collect()
+    }
+
+    /// Identical planks following each other with their count, in order.
+    pub fn counts(&self) -> &[(Plank, usize)] {
+        &self.counts
+    }
+
+    /// Number of planks of the heap.
+    pub fn len(&self) -> usize {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Distinct widths of the planks, widest first.
+    fn widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = self.counts.iter().map(|(plank, _)| plank.width).collect();
+        widths.sort_by(|a, b| b.cmp(a));
+        widths.dedup();
+        widths
+    }
+
+    /// Surface covered by the planks of the width, a plank counting once for every row.
+    fn width_area(&self, width: usize) -> usize {
+        self.counts
+            .iter()
+            .filter(|(plank, _)| plank.width == width)
+            .map(|(plank, count)| plank.length * plank.width * count)
+            .sum()
+    }
+
+    /// Splits the heap between the planks matching the predicate and the others.
+    fn partition(&self, predicate: impl Fn(&Plank) -> bool) -> (PlankHeap, PlankHeap) {
+        let mut matching = PlankHeap::new();
+        let mut others = PlankHeap::new();
+        for (plank, count) in self.counts.iter() {
+            let heap = if predicate(plank) { &mut matching } else { &mut others };
+            heap.push_planks(*count, plank.clone());
+        }
+        (matching, others)
+    }
+
+    /// Splits the heap between the planks that may be laid on a line from the first row,
+    /// the deck ending with the last row, and the planks of grades kept near the wall.
+    fn split_by_rows(&self, first_row: usize, last_row: usize, options: &CalepinageOptions) -> (PlankHeap, PlankHeap) {
+        let is_far_from_wall = |plank: &Plank| {
+            options.grade_rules.iter().any(|rule| match rule {
+                GradeRule::LastRowsOnly { grade, rows } => {
+                    plank.grade == Some(*grade) && first_row + rows <= last_row
+                }
+                GradeRule::NotAdjacent(_) => false,
+            })
+        };
+        let (far, near) = self.partition(is_far_from_wall);
+        (near, far)
+    }
+
+    /// Distinct lots of the planks, in increasing order.
+    fn lots(&self) -> Vec<LotId> {
+        let mut lots: Vec<LotId> = self.counts.iter().filter_map(|(plank, _)| plank.lot).collect();
+        lots.sort_unstable();
+        lots.dedup();
+        lots
+    }
+
+    /// Splits the heap between the planks of the given lot, or of no known lot, and the
+    /// others.
+    fn split_by_lot(&self, lot: LotId) -> (PlankHeap, PlankHeap) {
+        self.partition(|plank| plank.lot.is_none_or(|plank_lot| plank_lot == lot))
+    }
+
+    /// Splits the heap between the planks of the given width and the others.
+    fn split_by_width(&self, width: usize) -> (PlankHeap, PlankHeap) {
+        self.partition(|plank| plank.width == width)
+    }
+
+    /// The plank at the index, counting identical planks as many times as they are counted.
+    fn nth(&self, mut index: usize) -> &Plank {
+        for (plank, count) in self.counts.iter() {
+            if index < *count {
+                return plank;
+            }
+            index -= count;
+        }
+        panic!("the heap has no plank {}", index)
+    }
+
+    /// The heap without its first planks.
+    fn skipping(&self, mut skipped: usize) -> PlankHeap {
+        let mut plank_heap = PlankHeap::new();
+        for (plank, count) in self.counts.iter() {
+            let kept = count.saturating_sub(skipped);
+            skipped -= count - kept;
+            plank_heap.push_planks(kept, plank.clone());
+        }
+        plank_heap
+    }
+
+    /// The heaps joined, the planks of `self` first.
+    fn chain(mut self, other: PlankHeap) -> PlankHeap {
+        for (plank, count) in other.counts {
+            self.push_planks(count, plank);
+        }
+        self
+    }
+}
+
+impl std::convert::TryFrom<Vec<Plank>> for PlankHeap {
+    type Error = PlankError;
+
+    fn try_from(planks: Vec<Plank>) -> Result<Self, Self::Error> {
+        PlankHeap::from_planks(planks)
+    }
+}
+
+impl From<PlankHeap> for Vec<Plank> {
+    fn from(plank_heap: PlankHeap) -> Self {
+        plank_heap.into_planks()
+    }
+}
+
+impl std::str::FromStr for PlankHeap {
+    type Err = ParseHeapError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        PlankHeap::parse(spec)
+    }
+}
+
+#[test]
+fn plank_heap_should_report_invalid_planks_instead_of_panicking() {
+    let too_long = PlankError::TooLong {
+        length: Plank::MAX_LENGTH + 1,
+        max_length: Plank::MAX_LENGTH,
+    };
+    assert_eq!(Err(too_long), PlankHeap::new().try_add(2, Plank::MAX_LENGTH + 1));
+    assert_eq!(
+        Err(PlankError::ZeroDimension(Dimension::Width)),
+        PlankHeap::new().try_add_with_width(2, 10, 0)
+    );
+    let zero_width = Plank { length: 10, width: 0, ..Default::default() };
+    assert_eq!(Err(PlankError::ZeroDimension(Dimension::Width)), PlankHeap::from_planks(vec![zero_width.clone()]));
+    assert_eq!(Err(PlankError::ZeroDimension(Dimension::Width)), PlankHeap::from_counts(vec![(zero_width.clone(), 2)]));
+    assert_eq!(Err(PlankError::ZeroDimension(Dimension::Width)), PlankHeap::new().push(zero_width));
+    assert_eq!(Ok(PlankHeap::new().add(2, 10)), PlankHeap::from_planks(vec![Plank::new(10).unwrap(); 2]));
+    assert_eq!(
+        Err(PlankError::TooManyPlanks {
+            count: usize::MAX / 2,
+            length: 100
+        }),
+        PlankHeap::new().try_add(usize::MAX / 2, 100)
+    );
+    assert!(PlankHeap::new().add(1, 0).try_add(usize::MAX, 0).is_err());
+}
+
+#[test]
+fn plank_heap_should_parse_counts_of_lengths() {
+    assert_eq!(Ok(PlankHeap::new().add(6, 2400).add(4, 1800).add(1, 900)), PlankHeap::parse("6x2400 + 4X1800+900"));
+    assert_eq!(Ok(PlankHeap::new()), "".parse::<PlankHeap>());
+    assert_eq!(Err(ParseHeapError::BadLength("6x2.4m".to_string())), PlankHeap::parse("6x2.4m"));
+    assert_eq!(Err(ParseHeapError::BadCount("x2400".to_string())), PlankHeap::parse("x2400"));
+    assert_eq!(
+        Err(ParseHeapError::Overflow("99999999999999999x10000".to_string())),
+        PlankHeap::parse("99999999999999999x10000")
+    );
+    assert!(matches!(PlankHeap::parse("1x200000"), Err(ParseHeapError::InvalidPlank(PlankError::TooLong { .. }))));
+    assert_eq!(
+        "6x2.4m has no length of planks, like 6x2400",
+        PlankHeap::parse("6x2.4m").unwrap_err().to_string()
+    );
+}
+
+impl std::fmt::Display for PlankHeap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.planks().map(|p| p.length.to_string()).collect::<Vec<String>>().join(", "))
+    }
+}
+
+/// What `plank_line!` lays: planks, or plank lengths.
+pub trait IntoPlank {
+    fn into_plank(self) -> Plank;
+}
+
+impl IntoPlank for Plank {
+    fn into_plank(self) -> Plank {
+        self
+    }
+}
+
+/// Shorthand for tests, see `plank_line!`.
+#[cfg(any(test, feature = "test-utils"))]
+impl IntoPlank for usize {
+    /// Panics on lengths `Plank::new` rejects, telling the length.
+    fn into_plank(self) -> Plank {
+        Plank::new(self).unwrap_or_else(|error| panic!("invalid plank length {}: {}", self, error))
+    }
+}
+
+/// A line of the given planks, or of plank lengths like `plank_line![2, 1, 4]` with the
+/// `test-utils` feature, panicking on lengths `Plank::new` rejects.
+#[macro_export]
+macro_rules! plank_line {
+    ( $($head: expr), *) => {{  // {{ pcq Bloc d'instructions
+        let line = Line::default();
+        $(
+          let line = line.with_plank($crate::calepinage::IntoPlank::into_plank($head));
+        )*
+        line
+      }};
+}
+
+/// A deck of the given length and width, like `deck![10, 2]`, or a physical deck given
+/// the boards width too, like `deck![4000, 3000, 145]`. Shorthand for tests, panicking on
+/// invalid dimensions.
+#[cfg(any(test, feature = "test-utils"))]
+#[macro_export]
+macro_rules! deck {
+    ($length: expr, $width: expr) => {
+        $crate::calepinage::Deck::new($length, $width).unwrap_or_else(|error| panic!("invalid deck: {}", error))
+    };
+    ($length: expr, $width: expr, $board_width: expr) => {
+        $crate::calepinage::Deck::physical($length, $width, $board_width)
+            .unwrap_or_else(|error| panic!("invalid deck: {}", error))
+    };
+}
+
+/// A heap of the given planks or plank lengths, like `heap![2, 1, 4]`, or of counts of them,
+/// like `heap![2400; 6, 1800; 4]`. Shorthand for tests, panicking on invalid planks.
+#[cfg(any(test, feature = "test-utils"))]
+#[macro_export]
+macro_rules! heap {
+    () => {
+        $crate::calepinage::PlankHeap::new()
+    };
+    ( $($plank: expr; $count: expr), + $(,)?) => {{
+        let mut planks = vec![];
+        $(
+          let plank = $crate::calepinage::IntoPlank::into_plank($plank);
+          planks.extend(std::iter::repeat(plank).take($count));
+        )+
+        $crate::calepinage::PlankHeap::from_planks(planks).unwrap_or_else(|error| panic!("invalid heap: {}", error))
+    }};
+    ( $($plank: expr), + $(,)?) => {
+        $crate::calepinage::PlankHeap::from_planks(vec![$($crate::calepinage::IntoPlank::into_plank($plank)), +])
+            .unwrap_or_else(|error| panic!("invalid heap: {}", error))
+    };
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Line {
+    pub planks: Vec<Plank>,
+    /// Coordinate of the beginning of the line, 0 unless the deck isn't rectangular.
+    pub start: usize,
+    /// Expansion gap left between the ends of consecutive planks.
+    pub gap: usize,
+}
+
+impl Line {
+    pub fn new(planks: Vec<Plank>) -> Self {
+        Line {
+            planks,
+            start: 0,
+            gap: 0,
+        }
+    }
+
+    pub fn with_plank(mut self, new_plank_to_add: Plank) -> Self {
+        self.planks.push(new_plank_to_add);
+        self
+    }
+
+    pub fn starting_at(self, start: usize) -> Self {
+        Line { start, ..self }
+    }
+
+    pub fn with_gap(self, gap: usize) -> Self {
+        Line { gap, ..self }
+    }
+
+    pub fn planks(&self) -> std::slice::Iter<'_, Plank> {
+        self.planks.iter()
+    }
+
+    pub fn length(&self) -> usize {
+        covered_length(self.planks.iter().map(|plank| plank.length).sum(), self.planks.len(), self.gap)
+    }
+
+    /// Where every plank lies, from start to end along the deck length.
+    fn plank_spans(&self) -> Vec<(usize, usize)> {
+        let mut start = self.start;
+        self.planks
+            .iter()
+            .map(|plank| {
+                let span = (start, start + plank.length);
+                start += plank.length + self.gap;
+                span
+            })
+            .collect()
+    }
+
+    /// Lot of the planks of the line whose lot is known, the first one if mixed.
+    pub fn lot(&self) -> Option<LotId> {
+        self.planks.iter().find_map(|plank| plank.lot)
+    }
+
+    /// Number of deck rows covered by the line, as wide as its widest plank.
+    pub fn width(&self) -> usize {
+        self.planks.iter().map(|plank| plank.width).max().unwrap_or(0)
+    }
+
+    /// Junctions coordinates are relative to the deck, so that junctions of lines starting
+    /// at different coordinates can be compared. A junction is at the end of a plank, before
+    /// the gap.
+    pub fn compute_junction(&self) -> Vec<Junction> {
+        if self.planks.len() > 1 {
+            self.planks
+                .iter()
+                .scan(self.start, |acc, plank| {
+                    let junction = *acc + plank.length;
+                    *acc = junction + self.gap;
+                    Some(junction)
+                })
+                .map(Junction)
+                .take(self.planks.len() - 1)
+                .collect()
+        } else {
+            Vec::<Junction>::new()
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Line {
+    type Item = &'a Plank;
+    type IntoIter = std::slice::Iter<'a, Plank>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.planks()
+    }
+}
+
+/// Length covered by planks laid end to end with a gap between each of them.
+fn covered_length(planks_length: usize, planks_count: usize, gap: usize) -> usize {
+    planks_length + gap * planks_count.saturating_sub(1)
+}
+
+impl std::fmt::Display for Line {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let planks: Vec<String> = self
+            .planks
+            .iter()
+            .map(|p| match &p.label {
+                Some(label) => format!("{} {}", label, p.length),
+                None => p.length.to_string(),
+            })
+            .collect();
+        write!(f, "[{}]", planks.join(", "))?;
+        if self.start > 0 {
+            write!(f, " from {}", self.start)?;
+        }
+        Ok(())
+    }
+}
+
+/// A Junction is a coordinate in a 1 dimension plan corresponding to two plank edges
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Junction(usize);
+
+impl std::fmt::Display for Junction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "junction at {}", self.0)
+    }
+}
+
+impl Junction {
+    pub fn distance(&self, other: &Junction) -> usize {
+        self.0.abs_diff(other.0)
+    }
+
+    pub fn coordinate(&self) -> usize {
+        self.0
+    }
+}
+
+#[test]
+fn junction_distance_should_be_symmetric() {
+    assert_eq!(3, Junction(2).distance(&Junction(5)));
+    assert_eq!(3, Junction(5).distance(&Junction(2)));
+    assert_eq!(0, Junction(5).distance(&Junction(5)));
+}
+
+#[test]
+fn empty_line_should_have_no_junction() {
+    assert_eq!(Vec::<Junction>::new(), plank_line!().compute_junction());
+}
+
+#[test]
+fn single_plank_line_should_have_no_junction() {
+    assert_eq!(
+        Vec::<Junction>::new(),
+        plank_line!(Plank::new(1).unwrap()).compute_junction()
+    );
+}
+
+#[test]
+fn two_planks_line_should_have_one_junction() {
+    assert_eq!(
+        vec![Junction(3)],
+        plank_line!(Plank::new(3).unwrap(), Plank::new(1).unwrap()).compute_junction()
+    );
+}
+
+#[test]
+fn junctions_should_be_shifted_by_line_start() {
+    assert_eq!(
+        vec![Junction(8)],
+        plank_line!(Plank::new(3).unwrap(), Plank::new(1).unwrap()).starting_at(5).compute_junction()
+    );
+}
+
+#[test]
+fn junctions_should_be_at_plank_ends_before_the_gap() {
+    let line = plank_line![Plank::new(3).unwrap(), Plank::new(1).unwrap(), Plank::new(2).unwrap()].with_gap(2);
+
+    assert_eq!(vec![Junction(3), Junction(6)], line.compute_junction());
+    assert_eq!(10, line.length());
+}
+
+#[test]
+fn should_build_line() {
+    let actual = plank_line![]
+        .with_plank(Plank::new(2).unwrap())
+        .with_plank(Plank::new(1).unwrap());
+
+    let expected = Line::new(vec![Plank::new(2).unwrap(), Plank::new(1).unwrap()]);
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_use_macro() {
+    let actual = plank_line![Plank::new(2).unwrap()];
+
+    let expected = Line::new(vec![Plank::new(2).unwrap()]);
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_use_macro_with_lengths() {
+    let actual = plank_line![2, 1, Plank::new(4).unwrap()];
+
+    let expected = Line::new(vec![Plank::new(2).unwrap(), Plank::new(1).unwrap(), Plank::new(4).unwrap()]);
+    assert_eq!(expected, actual);
+}
+
+#[test]
+#[should_panic(expected = "invalid plank length 10001: a plank of length 10001 is longer than the max length 10000")]
+fn macro_should_reject_invalid_lengths() {
+    plank_line![2, Plank::MAX_LENGTH + 1];
+}
+
+#[test]
+fn should_use_deck_and_heap_macros() {
+    assert_eq!((10, 2, None), { let deck = deck![10, 2]; (deck.length, deck.width, deck.board_width) });
+    assert_eq!(Some(145), deck![4000, 3000, 145].board_width);
+    assert_eq!(PlankHeap::new().add(2, 6).add(1, 5), heap![6; 2, 5; 1]);
+    assert_eq!(PlankHeap::new().add(1, 2).add(1, 1), heap![2, Plank::new(1).unwrap()]);
+    assert_eq!(PlankHeap::new(), heap![]);
+}
+
+#[test]
+#[should_panic(expected = "invalid deck: a deck can't have a zero width")]
+fn deck_macro_should_reject_invalid_dimensions() {
+    deck![10, 0];
+}
+
+#[test]
+fn should_use_macro_with_2_planks() {
+    let actual = plank_line![Plank::new(2).unwrap(), Plank::new(1).unwrap()];
+
+    let expected = Line::new(vec![Plank::new(2).unwrap(), Plank::new(1).unwrap()]);
+    assert_eq!(expected, actual);
+}
+
+/// A Cut splits a plank in two: the piece of length `at` laid on the deck and an offcut.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cut {
+    pub plank: Plank,
+    pub at: usize,
+}
+
+impl Cut {
+    /// Cuts the plank at `at`, turning it end to end when the piece keeps fewer defects
+    /// that way, so that as many defects as possible fall in the offcut.
+    fn keeping_defects_in_offcut(plank: &Plank, at: usize) -> Self {
+        let defects_in_piece = |plank: &Plank| plank.defects.iter().filter(|&&defect| defect <= at).count();
+        let turned = plank.turned();
+        let plank = if defects_in_piece(&turned) < defects_in_piece(plank) {
+            turned
+        } else {
+            plank.clone()
+        };
+        Cut { plank, at }
+    }
+
+    pub fn piece(&self) -> Plank {
+        self.plank.part(0, self.at)
+    }
+
+    /// The plank left once the saw blade consumed `kerf` of the plank, as long as the offcut.
+    pub(crate) fn offcut_plank(&self, kerf: usize) -> Plank {
+        let offcut = self.offcut_with_kerf(kerf);
+        self.plank.part(self.plank.length - offcut.length, self.plank.length)
+    }
+
+    pub fn offcut(&self) -> Offcut {
+        self.offcut_with_kerf(0)
+    }
+
+    /// The offcut left once the saw blade consumed `kerf` of the plank.
+    pub fn offcut_with_kerf(&self, kerf: usize) -> Offcut {
+        Offcut {
+            length: (self.plank.length - self.at).saturating_sub(kerf),
+        }
+    }
+}
+
+impl std::fmt::Display for Cut {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} cut at {}", self.plank.length, self.at)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Offcut {
+    pub length: usize,
+}
+
+impl std::fmt::Display for Offcut {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "offcut {}", self.length)
+    }
+}
+
+impl Offcut {
+    pub fn is_reusable(&self, min_reusable_length: usize) -> bool {
+        self.length >= min_reusable_length
+    }
+}
+
+#[test]
+fn cut_should_split_plank_in_piece_and_offcut() {
+    let cut = Cut {
+        plank: Plank::new(10).unwrap(),
+        at: 4,
+    };
+
+    assert_eq!(Plank::new(4).unwrap(), cut.piece());
+    assert_eq!(Offcut { length: 6 }, cut.offcut());
+    assert_eq!(Offcut { length: 3 }, cut.offcut_with_kerf(3));
+}
+
+/// Calepinages compare line by line, then by their cuts.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Calepinage {
+    pub lines: Vec<Line>,
+    pub cuts: Vec<Cut>,
+    /// Width the boards of the last row are ripped to, when the deck width isn't a
+    /// multiple of the boards width.
+    pub rip: Option<usize>,
+    /// Picture frame lines, bottom, right, top then left, the deck lines filling the inside.
+    pub border: Vec<Line>,
+    pub breakers: Vec<Breaker>,
+    /// Width of the saw blade the planks were cut with.
+    pub kerf: usize,
+}
+
+/// Where a board of the calepinage lies: on the row, from `x_start` to `x_end` along the
+/// deck length.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Placement<'a> {
+    pub row: usize,
+    pub x_start: usize,
+    pub x_end: usize,
+    pub plank: &'a Plank,
+}
+
+impl std::fmt::Display for Placement<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "row {}: {} from {} to {}", self.row, self.plank, self.x_start, self.x_end)
+    }
+}
+
+/// Line laid across the rows, from `at` to `at + board width` along the deck length.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Breaker {
+    pub at: usize,
+    pub line: Line,
+}
+
+impl std::fmt::Display for Breaker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}", self.line, self.at)
+    }
+}
+
+impl Calepinage {
+    pub fn with_line(mut self, new_line_to_add: Line) -> Self {
+        self.lines.push(new_line_to_add);
+        self
+    }
+
+    pub fn with_cut(mut self, new_cut_to_add: Cut) -> Self {
+        self.cuts.push(new_cut_to_add);
+        self
+    }
+
+    pub fn with_border(mut self, border: Vec<Line>) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Lines of the field, every line covering as many rows as it is wide.
+    pub fn rows(&self) -> std::slice::Iter<'_, Line> {
+        self.lines.iter()
+    }
+
+    /// Placements of the boards of every line, on the rows given by `first_rows`. Gaps are left between the boards of a line.
+    pub fn placements(&self) -> Vec<Placement<'_>> {
+        let mut placements = vec![];
+        for (row, line) in self.first_rows() {
+            for (plank, (x_start, x_end)) in line.planks.iter().zip(line.plank_spans()) {
+                placements.push(Placement {
+                    row,
+                    x_start,
+                    x_end,
+                    plank,
+                });
+            }
+        }
+        placements
+    }
+
+    /// Every line with the first row it covers. A line starting after the end of the line
+    /// before it lies on the same rows, a row being split by cutouts.
+    fn first_rows(&self) -> Vec<(usize, &Line)> {
+        let mut first_rows: Vec<(usize, &Line)> = vec![];
+        for line in self.lines.iter() {
+            let first_row = match first_rows.last() {
+                None => 0,
+                Some((row, previous)) if line.start >= previous.start + previous.length() => *row,
+                Some((row, previous)) => row + previous.width(),
+            };
+            first_rows.push((first_row, line));
+        }
+        first_rows
+    }
+
+    /// Junctions of every row, in the rows order. The junctions of a line covering several
+    /// rows are on every of these rows.
+    pub fn junctions(&self) -> Vec<(usize, Junction)> {
+        self.first_rows()
+            .into_iter()
+            .flat_map(|(first_row, line)| {
+                let junctions = line.compute_junction();
+                (first_row..first_row + line.width())
+                    .flat_map(move |row| junctions.clone().into_iter().map(move |junction| (row, junction)))
+            })
+            .collect()
+    }
+
+    pub fn junctions_in_row(&self, row: usize) -> Vec<Junction> {
+        self.first_rows()
+            .into_iter()
+            .find(|(first_row, line)| (*first_row..first_row + line.width()).contains(&row))
+            .map(|(_, line)| line.compute_junction())
+            .unwrap_or_default()
+    }
+
+    /// Junctions at the same coordinate on adjacent lines, the junction of the upper line
+    /// last, on the first row of each line.
+    pub fn aligned_junction_pairs(&self) -> Vec<((usize, Junction), (usize, Junction))> {
+        let first_rows = self.first_rows();
+        first_rows
+            .windows(2)
+            .flat_map(|lines| {
+                let ((lower_row, lower), (upper_row, upper)) = (lines[0], lines[1]);
+                let upper_junctions = if upper_row > lower_row { upper.compute_junction() } else { vec![] };
+                lower
+                    .compute_junction()
+                    .into_iter()
+                    .filter(move |junction| upper_junctions.contains(junction))
+                    .map(move |junction| ((lower_row, junction.clone()), (upper_row, junction)))
+            })
+            .collect()
+    }
+
+    /// Labels of the planks of every line, in the lines order, None for unlabelled planks.
+    pub fn labels(&self) -> Vec<Vec<Option<&str>>> {
+        self.lines
+            .iter()
+            .map(|line| line.planks.iter().map(|plank| plank.label.as_deref()).collect())
+            .collect()
+    }
+
+    pub fn offcuts(&self) -> Vec<Offcut> {
+        self.cuts.iter().map(|cut| cut.offcut_with_kerf(self.kerf)).collect()
+    }
+
+    /// Total length of the offcuts that were not put back in the heap, and of the planks
+    /// consumed by the saw blade.
+    pub fn waste(&self, options: &CalepinageOptions) -> usize {
+        let offcuts: usize = self
+            .offcuts()
+            .iter()
+            .filter(|offcut| !(options.reuse_offcuts && offcut.is_reusable(options.min_reusable_offcut)))
+            .map(|offcut| offcut.length)
+            .sum();
+        offcuts + self.cuts.len() * self.kerf
+    }
+}
+
+impl<'a> IntoIterator for &'a Calepinage {
+    type Item = &'a Line;
+    type IntoIter = std::slice::Iter<'a, Line>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows()
+    }
+}
+
+impl std::fmt::Display for Calepinage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Calepinage({})", self.lines.iter().map(|line| line.to_string()).collect::<Vec<String>>().join(", "))?;
+        if !self.cuts.is_empty() {
+            write!(f, " with cuts [{}]", self.cuts.iter().map(|cut| cut.to_string()).collect::<Vec<String>>().join(", "))?;
+        }
+        if !self.border.is_empty() {
+            write!(f, " with border [{}]", self.border.iter().map(|line| line.to_string()).collect::<Vec<String>>().join(", "))?;
+        }
+        if !self.breakers.is_empty() {
+            let breakers: Vec<String> = self.breakers.iter().map(Breaker::to_string).collect();
+            write!(f, " with breakers [{}]", breakers.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for Calepinage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+#[test]
+fn placements_should_follow_lengths_gaps_and_widths() {
+    let wide = Plank::new(4).unwrap().with_width(2).unwrap();
+    let calepinage = Calepinage::default()
+        .with_line(Line::new(vec![wide.clone(), wide.clone()]).with_gap(1))
+        .with_line(plank_line![Plank::new(9).unwrap()].starting_at(2));
+
+    let spans: Vec<(usize, usize, usize)> =
+        calepinage.placements().iter().map(|placement| (placement.row, placement.x_start, placement.x_end)).collect();
+
+    assert_eq!(vec![(0, 0, 4), (0, 5, 9), (2, 2, 11)], spans);
+    assert_eq!(&wide, calepinage.placements()[1].plank);
+}
+
+#[test]
+fn calepinage_should_iterate_over_rows_and_boards() {
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![Plank::new(1).unwrap(), Plank::new(2).unwrap()])
+        .with_line(plank_line![Plank::new(3).unwrap()]);
+
+    let mut lengths = vec![];
+    for line in &calepinage {
+        lengths.push(line.planks().map(|plank| plank.length).collect::<Vec<usize>>());
+    }
+
+    assert_eq!(vec![vec![1, 2], vec![3]], lengths);
+    assert_eq!(2, calepinage.rows().count());
+    assert_eq!(3, calepinage.rows().flatten().count());
+}
+
+#[test]
+fn calepinage_should_list_junctions_by_row() {
+    let wide = Plank::new(4).unwrap().with_width(2).unwrap();
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![wide.clone(), wide])
+        .with_line(plank_line![Plank::new(4).unwrap(), Plank::new(2).unwrap(), Plank::new(2).unwrap()]);
+
+    assert_eq!(
+        vec![(0, Junction(4)), (1, Junction(4)), (2, Junction(4)), (2, Junction(6))],
+        calepinage.junctions()
+    );
+    assert_eq!(vec![Junction(4)], calepinage.junctions_in_row(1));
+    assert_eq!(Vec::<Junction>::new(), calepinage.junctions_in_row(3));
+    assert_eq!(vec![((0, Junction(4)), (2, Junction(4)))], calepinage.aligned_junction_pairs());
+}
+
+#[test]
+fn with_line_should_append_lines_in_order() {
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![Plank::new(1).unwrap()])
+        .with_line(plank_line![Plank::new(2).unwrap()]);
+
+    let Calepinage { lines, .. } = calepinage;
+    assert_eq!(&lines[0], &plank_line![Plank::new(1).unwrap()]);
+    assert_eq!(&lines[1], &plank_line![Plank::new(2).unwrap()]);
+}
+
+#[derive(Default, Debug, PartialEq, Clone)]
+pub struct CalepineStep {
+    remaining: PlankHeap,
+    selected: PlankHeap,
+    stash: Option<Plank>,
+    cuts: Vec<Cut>,
+}
+
+impl CalepineStep {
+    /// Planks left for the next lines.
+    pub fn remaining(&self) -> &PlankHeap {
+        &self.remaining
+    }
+
+    /// Planks of the line, in laying order, pieces of the cut planks included.
+    pub fn selected(&self) -> &PlankHeap {
+        &self.selected
+    }
+
+    /// Plank put aside because its junction would be too close to the previous lines ones.
+    pub fn stash(&self) -> Option<&Plank> {
+        self.stash.as_ref()
+    }
+
+    pub fn cuts(&self) -> &[Cut] {
+        &self.cuts
+    }
+
+    /// Price of the boards the line consumes, whole or cut, pieces being free.
+    fn cost(&self) -> usize {
+        let selected = self.selected.planks();
+        let cut = self.cuts.iter().map(|cut| &cut.plank);
+        selected.chain(cut).filter_map(|plank| plank.price).sum()
+    }
+}
+
+impl std::fmt::Display for CalepineStep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "remaining = [{}], selected = [{}], stash = ", self.remaining, self.selected)?;
+        match &self.stash {
+            Some(plank) => write!(f, "{}", plank),
+            None => write!(f, "None"),
+        }
+    }
+}
+
+/// Why the solver didn't lay a plank after the planks selected so far.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Rejection {
+    /// The plank would go past the end of the line.
+    TooLong,
+    /// The plank would end the line with a piece shorter than `min_end_piece`.
+    TooShortEndPiece,
+    /// The plank junction would be too close to the previous lines ones, or off a joist.
+    MisplacedJunction,
+    /// The plank grade isn't allowed there by the grade rules.
+    Misgraded,
+    /// A plank defect would be at a junction.
+    DefectAtJunction,
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            Rejection::TooLong => "too long",
+            Rejection::TooShortEndPiece => "end piece too short",
+            Rejection::MisplacedJunction => "misplaced junction",
+            Rejection::Misgraded => "grade not allowed",
+            Rejection::DefectAtJunction => "defect at a junction",
+        };
+        f.write_str(reason)
+    }
+}
+
+/// State of the solver while it searches the planks of a line, see `calepine_steps`.
+#[derive(Debug, PartialEq)]
+pub struct SolverSnapshot {
+    /// Deck row of the line searched.
+    pub row: usize,
+    /// Planks selected so far, the remaining ones being the planks the line may be made of.
+    pub step: CalepineStep,
+    /// The plank the solver just tried and didn't select, with the reason why.
+    pub rejected: Option<(Plank, Rejection)>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CalepinageError {
+    NotEnoughPlanks,
+    /// The planks left can't cover the row, `needed_length` of the row being left uncovered.
+    OnlyUnusablePlanksRemaining {
+        row: usize,
+        needed_length: usize,
+        /// Lengths of the planks left in the heap.
+        remaining_lengths: Vec<usize>,
+    },
+    DeckTooSmallForBorder,
+    /// The deck is only known in rows, its border sides having no length across the rows.
+    UnknownBoardWidth,
+    /// The deck width isn't a multiple of the boards width, this much being left uncovered.
+    UncoveredDeckWidth(usize),
+    /// Planks of different thicknesses would make steps on the deck.
+    MixedThicknesses(Vec<usize>),
+    /// The boards, running along the deck length, don't run the way the slope rule wants.
+    AgainstSlope { slope: Orientation, rule: SlopeRule },
+    /// The search passed the deadline or was cancelled before laying the deck.
+    OutOfBudget,
+}
+
+impl std::fmt::Display for CalepinageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for CalepinageError {}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Solver {
+    /// Fills lines one after the other with the first planks sequence found.
+    #[default]
+    Greedy,
+    /// Tries every planks sequence of every line and keeps the calepinage best fulfilling
+    /// the goal. Waste is the total length of the offcuts that can't be reused. Only
+    /// suitable for small heaps.
+    Exhaustive,
+    /// Finds the calepinage wasting the least, whatever the goal, cuts breaking ties.
+    /// Lines wasting less are tried first, branches whose planks can't cover the rows
+    /// left are abandoned and the search stops once nothing is wasted. Suitable for
+    /// moderate heaps.
+    BranchAndBound,
+}
+
+/// Breaker boards every `interval` along the deck length, for decks longer than the
+/// planks. Sections between breakers are laid independently.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BreakerBoards {
+    pub interval: usize,
+    pub board_width: usize,
+}
+
+impl BreakerBoards {
+    /// Where breakers start along the field, a breaker never ending the field.
+    fn positions(&self, field_start: usize, field_length: usize) -> Vec<usize> {
+        if self.interval == 0 {
+            return vec![];
+        }
+        let field_end = field_start + field_length;
+        (0..)
+            .map(|index| field_start + self.interval + index * (self.interval + self.board_width))
+            .take_while(|at| at + self.board_width < field_end)
+            .collect()
+    }
+}
+
+#[test]
+fn breakers_should_never_end_the_field() {
+    let breakers = BreakerBoards {
+        interval: 10,
+        board_width: 2,
+    };
+
+    assert_eq!(vec![15, 27], breakers.positions(5, 34));
+    assert_eq!(vec![15], breakers.positions(5, 24));
+    assert!(BreakerBoards { interval: 0, board_width: 2 }.positions(0, 10).is_empty());
+}
+
+/// Full rows of boards `board_width` wide and `gap` apart across `width`, with the width
+/// left past the last of them, its gap included.
+fn fit_rows(width: usize, board_width: usize, gap: usize) -> (usize, usize) {
+    let full_rows = (width + gap) / (board_width + gap);
+    (full_rows, width - (full_rows * (board_width + gap)).saturating_sub(gap))
+}
+
+/// The last row of a physical deck ripped to `width`, see `Deck::rip`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rip {
+    pub width: usize,
+    /// Rips on both sides instead, when the deck is wider than a board.
+    pub balanced: Option<BalancedRip>,
+}
+
+/// The first row shifted by `shift` past the deck side, its boards ripped to
+/// `first_width`, so that both sides of the deck are ripped alike.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BalancedRip {
+    pub shift: usize,
+    pub first_width: usize,
+    pub last_width: usize,
+}
+
+impl std::fmt::Display for BalancedRip {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "shift the first row by {} to rip it to {} and the last row to {}",
+            self.shift, self.first_width, self.last_width
+        )
+    }
+}
+
+/// How boards must run relative to the slope of the deck, depending on the decking: along
+/// the slope for grooved boards to drain, across it for others.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SlopeRule {
+    Parallel,
+    Perpendicular,
+}
+
+impl SlopeRule {
+    /// Whether boards running in the orientation follow the rule on a deck sloping down the
+    /// slope orientation.
+    pub fn allows(&self, boards: Orientation, slope: Orientation) -> bool {
+        (boards == slope) == (*self == SlopeRule::Parallel)
+    }
+}
+
+/// Figures the junctions of consecutive rows draw that catch the eye: stair-steps, junctions
+/// marching diagonally by the same step row after row, and H shapes, junctions aligned two
+/// rows apart across the boards of the row between them.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct JointFigures {
+    /// Steps from a row to the next up to this distance line junctions up diagonally.
+    pub max_step: usize,
+    /// How far junctions may be from the figure they draw, none by default.
+    pub tolerance: usize,
+    /// Consecutive rows of junctions making a stair-step, 3 by default and at least 3.
+    pub stair_rows: usize,
+}
+
+impl JointFigures {
+    pub fn new(max_step: usize) -> Self {
+        JointFigures {
+            max_step,
+            tolerance: 0,
+            stair_rows: 3,
+        }
+    }
+
+    pub fn with_tolerance(mut self, tolerance: usize) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn with_stair_rows(mut self, stair_rows: usize) -> Self {
+        self.stair_rows = stair_rows.max(3);
+        self
+    }
+
+    /// Whether the junction at `at` ends a stair-step going down the junctions of the rows
+    /// below, nearest row first.
+    fn is_stair_step(&self, at: usize, rows_below: &[Vec<usize>]) -> bool {
+        rows_below[0].iter().any(|&below| {
+            let step = at as isize - below as isize;
+            step.unsigned_abs() > self.tolerance
+                && step.unsigned_abs() <= self.max_step
+                && self.continues_stair_step(below, step, &rows_below[1..])
+        })
+    }
+
+    fn continues_stair_step(&self, at: usize, step: isize, rows_below: &[Vec<usize>]) -> bool {
+        match rows_below.split_first() {
+            None => true,
+            Some((row, rows)) => row.iter().any(|&below| {
+                let next_step = at as isize - below as isize;
+                (next_step - step).unsigned_abs() <= self.tolerance && self.continues_stair_step(below, step, rows)
+            }),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum JointFigure {
+    StairStep,
+    H,
+}
+
+/// What to do when the last row of a physical deck is narrower than the boards.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum LastRow {
+    #[default]
+    Reject,
+    /// Rips the boards of the last row along their length to the remaining width.
+    Rip,
+}
+
+/// What the exhaustive solver optimizes, the other criterion breaking ties.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Goal {
+    #[default]
+    MinimizeWaste,
+    /// Prefers calepinages requiring the fewest saw cuts, even if they waste more.
+    MinimizeCuts,
+    /// Prefers calepinages whose boards cost the least, boards without a price being free.
+    MinimizeCost,
+    /// Minimizes the score of the objective.
+    Weighted(CalepineObjective),
+}
+
+impl Goal {
+    fn cost(&self, metrics: &Metrics) -> (f64, f64) {
+        match self {
+            Goal::MinimizeWaste => (metrics.waste as f64, metrics.cuts as f64),
+            Goal::MinimizeCuts => (metrics.cuts as f64, metrics.waste as f64),
+            Goal::MinimizeCost => (metrics.cost as f64, metrics.waste as f64),
+            Goal::Weighted(objective) => (objective.score(metrics), 0.0),
+        }
+    }
+}
+
+/// Weights of the criteria making the score of a calepinage, the lower the better.
+///
+/// Stagger is scored by summing, for every junction, the length missing for the junction
+/// to be `stagger_target` apart from the junctions of the previous lines. Weights must not
+/// be negative.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CalepineObjective {
+    /// Weight of a unit of wasted length.
+    pub waste: f64,
+    /// Weight of a cut.
+    pub cuts: f64,
+    /// Weight of a unit of length missing to the stagger target.
+    pub stagger: f64,
+    /// Weight of a plank laid.
+    pub planks: f64,
+    /// Weight of a unit of price of the boards used.
+    pub cost: f64,
+    /// Weight of a junction drawing one of the `joint_figures` of the options.
+    pub joint_figures: f64,
+    pub stagger_target: usize,
+}
+
+impl Default for CalepineObjective {
+    fn default() -> Self {
+        CalepineObjective {
+            waste: 1.0,
+            cuts: 0.0,
+            stagger: 0.0,
+            planks: 0.0,
+            cost: 0.0,
+            joint_figures: 0.0,
+            stagger_target: 0,
+        }
+    }
+}
+
+impl CalepineObjective {
+    pub fn with_waste(mut self, waste: f64) -> Self {
+        self.waste = waste;
+        self
+    }
+
+    pub fn with_cuts(mut self, cuts: f64) -> Self {
+        self.cuts = cuts;
+        self
+    }
+
+    pub fn with_stagger(mut self, stagger: f64, stagger_target: usize) -> Self {
+        self.stagger = stagger;
+        self.stagger_target = stagger_target;
+        self
+    }
+
+    pub fn with_planks(mut self, planks: f64) -> Self {
+        self.planks = planks;
+        self
+    }
+
+    pub fn with_cost(mut self, cost: f64) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    pub fn with_joint_figures(mut self, joint_figures: f64) -> Self {
+        self.joint_figures = joint_figures;
+        self
+    }
+
+    fn score(&self, metrics: &Metrics) -> f64 {
+        self.waste * metrics.waste as f64
+            + self.cuts * metrics.cuts as f64
+            + self.stagger * metrics.stagger_shortfall as f64
+            + self.planks * metrics.planks as f64
+            + self.cost * metrics.cost as f64
+            + self.joint_figures * metrics.joint_figures as f64
+    }
+
+    fn stagger_shortfall(&self, line: &Line, previous_lines_junctions: &[Junction]) -> usize {
+        line.compute_junction()
+            .iter()
+            .filter_map(|junction| previous_lines_junctions.iter().map(|previous| previous.distance(junction)).min())
+            .map(|distance| self.stagger_target.saturating_sub(distance))
+            .sum()
+    }
+}
+
+/// Criteria measured on a calepinage while it is searched.
+#[derive(Debug, Default, Clone)]
+struct Metrics {
+    waste: usize,
+    cuts: usize,
+    planks: usize,
+    stagger_shortfall: usize,
+    cost: usize,
+    joint_figures: usize,
+}
+
+/// How planks are ordered when looking for a line.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Pattern {
+    /// Planks in the order of the strategy, longest first by default.
+    #[default]
+    Sorted,
+    /// Planks shuffled for every line, giving irregular junctions that look natural.
+    /// The same seed always gives the same calepinage.
+    Random { seed: u64 },
+    /// Every line starts with the longest plank cut so that its junctions are shifted by
+    /// `offset` from the previous line ones, the first line starting with a whole plank.
+    RunningBond { offset: usize },
+    /// Like the running bond, but the shift goes back once the next one would reach a
+    /// whole plank, the junctions drawing chevrons instead of a staircase.
+    Symmetric { offset: usize },
+}
+
+/// Which planks a line is made of first.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Strategy {
+    #[default]
+    LongestFirst,
+    /// Keeps the long planks for the last lines.
+    ShortestFirst,
+    /// Longest planks first on even lines and shortest first on odd lines, so that long
+    /// planks are spread over the deck instead of being used by the first lines.
+    Balanced,
+    /// Lines made of as few planks as possible, then with as few cuts. The search of a line
+    /// drops the sequences that can't lay fewer pieces than the best line found so far.
+    FewestPieces,
+}
+
+impl Strategy {
+    /// Orders the plank groups, given longest first, for the line.
+    fn order<T>(&self, row: usize, groups: &mut [T]) {
+        match self {
+            Strategy::ShortestFirst => groups.reverse(),
+            Strategy::Balanced if row % 2 == 1 => groups.reverse(),
+            _ => {}
+        }
+    }
+}
+
+/// Which planks width a line is made of first, every line being made of planks of a single
+/// width, when the heap has planks of several widths.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum RowWidths {
+    /// The widest planks, narrower ones when they can't fill the line.
+    #[default]
+    WidestFirst,
+    /// The planks covering the most of the deck, so that every width is used up at the
+    /// same pace instead of the widest planks running out first.
+    MostAvailable,
+}
+
+impl RowWidths {
+    /// The widths of the heap planks in the order lines try them.
+    fn order(&self, the_plank_heap: &PlankHeap) -> Vec<usize> {
+        let mut widths = the_plank_heap.widths();
+        if *self == RowWidths::MostAvailable {
+            // stable, widest first among widths covering as much
+            widths.sort_by_key(|&width| std::cmp::Reverse(the_plank_heap.width_area(width)));
+        }
+        widths
+    }
+}
+
+/// Keeps lots apart, planks of unknown lot going with any lot.
+///
+/// Within a row, every line is made of planks of one lot. Across adjacent rows, a line is
+/// also made of the lot of the line laid on the previous row, so that a hard constraint
+/// lays the whole deck with one lot. A soft constraint tries the lines of one lot first,
+/// the previous row one first across rows, and mixes lots when none fits. The exhaustive
+/// solvers only enforce hard constraints.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LotConstraint {
+    pub across_rows: bool,
+    pub hard: bool,
+}
+
+impl LotConstraint {
+    pub fn within_row() -> Self {
+        LotConstraint {
+            across_rows: false,
+            hard: true,
+        }
+    }
+
+    pub fn across_rows() -> Self {
+        LotConstraint {
+            across_rows: true,
+            hard: true,
+        }
+    }
+
+    pub fn soft(self) -> Self {
+        LotConstraint { hard: false, ..self }
+    }
+
+    /// Lots a line may be made of, in the order they are tried, none standing for mixed lots.
+    fn lots(&self, lots: &[LotId], previous_lot: Option<LotId>) -> Vec<Option<LotId>> {
+        let mut tried = vec![];
+        if let Some(previous_lot) = previous_lot.filter(|_| self.across_rows) {
+            tried.push(Some(previous_lot));
+            if self.hard {
+                return tried;
+            }
+        }
+        tried.extend(lots.iter().filter(|&&lot| Some(lot) != previous_lot || !self.across_rows).map(|&lot| Some(lot)));
+        if !self.hard || lots.is_empty() {
+            tried.push(None);
+        }
+        tried
+    }
+}
+
+#[test]
+fn soft_lot_constraint_should_try_the_previous_lot_first_then_mix() {
+    let lots = [LotId(1), LotId(2), LotId(3)];
+
+    assert_eq!(
+        vec![Some(LotId(2)), Some(LotId(1)), Some(LotId(3)), None],
+        LotConstraint::across_rows().soft().lots(&lots, Some(LotId(2)))
+    );
+    assert_eq!(vec![Some(LotId(2))], LotConstraint::across_rows().lots(&lots, Some(LotId(2))));
+    assert_eq!(
+        vec![Some(LotId(1)), Some(LotId(2)), Some(LotId(3))],
+        LotConstraint::within_row().lots(&lots, Some(LotId(2)))
+    );
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GradeRule {
+    /// Planks of the grade never touch each other, end to end in a line or side by side
+    /// with the line of the previous row.
+    NotAdjacent(Grade),
+    /// Planks of the grade are only laid on the last rows of the deck, near the wall.
+    LastRowsOnly { grade: Grade, rows: usize },
+}
+
+impl Pattern {
+    pub fn half_bond(plank_length: usize) -> Self {
+        Pattern::RunningBond {
+            offset: plank_length / 2,
+        }
+    }
+
+    pub fn third_bond(plank_length: usize) -> Self {
+        Pattern::RunningBond {
+            offset: plank_length / 3,
+        }
+    }
+
+    /// How far the junctions of the row are shifted along planks `plank_length` long, for
+    /// the patterns starting every line with a cut plank.
+    fn shift(&self, row: usize, plank_length: usize) -> Option<usize> {
+        match *self {
+            Pattern::RunningBond { offset } => Some((row * offset) % plank_length),
+            Pattern::Symmetric { offset } => {
+                let climb = plank_length.checked_div(offset).unwrap_or(0).saturating_sub(1);
+                let step = match climb {
+                    0 => 0,
+                    climb => {
+                        let step = row % (2 * climb);
+                        step.min(2 * climb - step)
+                    }
+                };
+                Some((step * offset) % plank_length)
+            }
+            Pattern::Sorted | Pattern::Random { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CalepinageOptions {
+    /// Minimal distance between a junction and any junction of the previous lines.
+    /// The default of 1 only rejects junctions exactly aligned with the previous lines.
+    pub min_junction_offset: usize,
+    /// Minimal distance between a junction and the defects of the planks it joins, defects
+    /// being allowed anywhere by default.
+    pub min_defect_distance: usize,
+    /// Number of previous lines whose junctions must be avoided, 1 by default.
+    pub stagger_depth: usize,
+    /// When no planks combination fills a line exactly, allows cutting the last plank
+    /// of the line at the deck length.
+    pub allow_cutting: bool,
+    /// When cutting, puts reusable offcuts back in the heap for the next lines.
+    pub reuse_offcuts: bool,
+    /// Offcuts shorter than this are too short to be laid and are wasted, 300 by default.
+    pub min_reusable_offcut: usize,
+    /// Length of plank consumed by every cut, none by default.
+    pub kerf: usize,
+    pub solver: Solver,
+    pub goal: Goal,
+    pub pattern: Pattern,
+    pub strategy: Strategy,
+    pub row_widths: RowWidths,
+    /// Whether lines may mix planks of different lots, any by default.
+    pub lots: Option<LotConstraint>,
+    /// Where planks of a grade may be laid, anywhere by default.
+    pub grade_rules: Vec<GradeRule>,
+    /// Boards laid across the rows, splitting the field in sections, none by default.
+    pub breakers: Option<BreakerBoards>,
+    /// Width of the boards framing the deck, none by default. The frame is laid first,
+    /// greedily and with sorted planks, the first and last rows of the deck being the
+    /// bottom and top of the frame. Only decks of known board width can be framed.
+    pub border: Option<usize>,
+    pub last_row: LastRow,
+    /// Expansion gap left at every junction, none by default.
+    pub end_gap: usize,
+    /// Gap left between rows of physical decks, none by default.
+    pub side_gap: usize,
+    /// Lays every other line from the end of the deck, the first piece of these lines
+    /// being at the end of the deck. False by default.
+    pub alternate_sides: bool,
+    /// Joists every butt joint must land on, none by default.
+    pub substructure: Option<Substructure>,
+    /// Maximal distance between the center of a butt joint and the center of a joist.
+    pub joist_tolerance: usize,
+    /// Minimal length of the first and last pieces of a line, unless a single piece fills
+    /// the whole line. None by default.
+    pub min_end_piece: usize,
+    /// Narrowest width the boards of the last row may be ripped to, validation flagging
+    /// narrower rips. None by default.
+    pub min_rip_width: usize,
+    /// How boards must run relative to the deck slope, any way by default.
+    pub slope_rule: Option<SlopeRule>,
+    /// Figures of junctions validation flags and a weighted goal penalizes, none by
+    /// default.
+    pub joint_figures: Option<JointFigures>,
+    /// Instant past which every search stops, the calepinage failing as out of budget.
+    /// None by default.
+    pub deadline: Option<Instant>,
+    /// Stops every search once cancelled, like the deadline. None by default.
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Default for CalepinageOptions {
+    fn default() -> Self {
+        CalepinageOptions {
+            min_junction_offset: 1,
+            min_defect_distance: 0,
+            stagger_depth: 1,
+            allow_cutting: false,
+            reuse_offcuts: true,
+            min_reusable_offcut: 300,
+            kerf: 0,
+            solver: Solver::default(),
+            goal: Goal::default(),
+            pattern: Pattern::default(),
+            strategy: Strategy::default(),
+            row_widths: RowWidths::default(),
+            lots: None,
+            grade_rules: vec![],
+            breakers: None,
+            border: None,
+            last_row: LastRow::default(),
+            end_gap: 0,
+            side_gap: 0,
+            alternate_sides: false,
+            substructure: None,
+            joist_tolerance: 0,
+            min_end_piece: 0,
+            min_rip_width: 0,
+            slope_rule: None,
+            joint_figures: None,
+            deadline: None,
+            cancellation: None,
+        }
+    }
+}
+
+impl CalepinageOptions {
+    fn is_laid_from_end(&self, row: usize) -> bool {
+        self.alternate_sides && row % 2 == 1
+    }
+
+    fn is_out_of_budget(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+            || self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+
+    pub fn with_min_junction_offset(mut self, min_junction_offset: usize) -> Self {
+        self.min_junction_offset = min_junction_offset;
+        self
+    }
+
+    pub fn with_min_defect_distance(mut self, min_defect_distance: usize) -> Self {
+        self.min_defect_distance = min_defect_distance;
+        self
+    }
+
+    pub fn with_stagger_depth(mut self, stagger_depth: usize) -> Self {
+        self.stagger_depth = stagger_depth;
+        self
+    }
+
+    pub fn with_allow_cutting(mut self, allow_cutting: bool) -> Self {
+        self.allow_cutting = allow_cutting;
+        self
+    }
+
+    pub fn with_reuse_offcuts(mut self, reuse_offcuts: bool) -> Self {
+        self.reuse_offcuts = reuse_offcuts;
+        self
+    }
+
+    pub fn with_min_reusable_offcut(mut self, min_reusable_offcut: usize) -> Self {
+        self.min_reusable_offcut = min_reusable_offcut;
+        self
+    }
+
+    pub fn with_kerf(mut self, kerf: usize) -> Self {
+        self.kerf = kerf;
+        self
+    }
+
+    pub fn with_solver(mut self, solver: Solver) -> Self {
+        self.solver = solver;
+        self
+    }
+
+    pub fn with_goal(mut self, goal: Goal) -> Self {
+        self.goal = goal;
+        self
+    }
+
+    pub fn with_pattern(mut self, pattern: Pattern) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn with_row_widths(mut self, row_widths: RowWidths) -> Self {
+        self.row_widths = row_widths;
+        self
+    }
+
+    pub fn with_lots(mut self, lots: LotConstraint) -> Self {
+        self.lots = Some(lots);
+        self
+    }
+
+    pub fn with_grade_rule(mut self, grade_rule: GradeRule) -> Self {
+        self.grade_rules.push(grade_rule);
+        self
+    }
+
+    fn are_kept_apart(&self, grade: Option<Grade>) -> bool {
+        grade.is_some_and(|grade| self.grade_rules.contains(&GradeRule::NotAdjacent(grade)))
+    }
+
+    pub fn with_border(mut self, board_width: usize) -> Self {
+        self.border = Some(board_width);
+        self
+    }
+
+    pub fn with_breakers(mut self, interval: usize, board_width: usize) -> Self {
+        self.breakers = Some(BreakerBoards { interval, board_width });
+        self
+    }
+
+    pub fn with_last_row(mut self, last_row: LastRow) -> Self {
+        self.last_row = last_row;
+        self
+    }
+
+    pub fn with_slope_rule(mut self, slope_rule: SlopeRule) -> Self {
+        self.slope_rule = Some(slope_rule);
+        self
+    }
+
+    pub fn with_joint_figures(mut self, joint_figures: JointFigures) -> Self {
+        self.joint_figures = Some(joint_figures);
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    pub fn with_min_rip_width(mut self, min_rip_width: usize) -> Self {
+        self.min_rip_width = min_rip_width;
+        self
+    }
+
+    pub fn with_end_gap(mut self, end_gap: usize) -> Self {
+        self.end_gap = end_gap;
+        self
+    }
+
+    pub fn with_side_gap(mut self, side_gap: usize) -> Self {
+        self.side_gap = side_gap;
+        self
+    }
+
+    /// Both gaps, like the ones `gaps::recommended_gaps` computes for a wood.
+    pub fn with_gaps(self, gaps: Gaps) -> Self {
+        self.with_end_gap(gaps.end_gap).with_side_gap(gaps.side_gap)
+    }
+
+    pub fn with_alternate_sides(mut self, alternate_sides: bool) -> Self {
+        self.alternate_sides = alternate_sides;
+        self
+    }
+
+    pub fn with_substructure(mut self, substructure: Substructure, joist_tolerance: usize) -> Self {
+        self.substructure = Some(substructure);
+        self.joist_tolerance = joist_tolerance;
+        self
+    }
+
+    pub fn with_min_end_piece(mut self, min_end_piece: usize) -> Self {
+        self.min_end_piece = min_end_piece;
+        self
+    }
+}
+
+pub fn calepine(plank_heap: PlankHeap, deck: Deck) -> Result<Calepinage, CalepinageError> {
+    calepine_with_options(plank_heap, deck, CalepinageOptions::default())
+}
+
+pub fn calepine_with_options(
+    plank_heap: PlankHeap,
+    deck: Deck,
+    options: CalepinageOptions,
+) -> Result<Calepinage, CalepinageError> {
+    calepine_deck(plank_heap, &deck, &options).map(|(calepinage, _)| calepinage)
+}
+
+/// Calepines the deck, returning the leftovers: the planks left unused and the offcuts
+/// long enough to be reused, even when the options don't reuse them on the deck.
+pub fn calepine_with_leftovers(
+    plank_heap: PlankHeap,
+    deck: Deck,
+    options: CalepinageOptions,
+) -> Result<(Calepinage, PlankHeap), CalepinageError> {
+    let (calepinage, mut leftovers) = calepine_deck(plank_heap, &deck, &options)?;
+    if !options.reuse_offcuts {
+        for cut in calepinage.cuts.iter() {
+            if cut.offcut_with_kerf(options.kerf).is_reusable(options.min_reusable_offcut) {
+                leftovers.push_planks(1, cut.offcut_plank(options.kerf));
+            }
+        }
+    }
+    Ok((calepinage, sorted_by_decreasing_length(leftovers)))
+}
+
+/// Best effort calepinage of a deck the heap can't entirely cover.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PartialCalepinage {
+    pub calepinage: Calepinage,
+    /// Row parts left without planks, in rows order.
+    pub uncovered: Vec<UncoveredRow>,
+    /// Plank length missing to cover the uncovered rows, at least, once the planks left
+    /// in the heap are used.
+    pub missing_length: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct UncoveredRow {
+    pub row: usize,
+    pub start: usize,
+    pub length: usize,
+}
+
+impl std::fmt::Display for UncoveredRow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "row {} uncovered from {} to {}", self.row, self.start, self.start + self.length)
+    }
+}
+
+impl PartialCalepinage {
+    pub fn is_complete(&self) -> bool {
+        self.uncovered.is_empty()
+    }
+
+    /// Uncovered area, in rows length.
+    pub fn uncovered_length(&self) -> usize {
+        self.uncovered.iter().map(|row| row.length).sum()
+    }
+}
+
+/// Calepines as many rows as the heap allows instead of failing with `NotEnoughPlanks`,
+/// rows that can't be filled being reported as uncovered. The border and the breakers must
+/// still be laid entirely.
+pub fn calepine_partially(
+    plank_heap: PlankHeap,
+    deck: Deck,
+    options: CalepinageOptions,
+) -> Result<PartialCalepinage, CalepinageError> {
+    let lay_field = |plank_heap, rows: &[RowGoal], options: &CalepinageOptions| {
+        Ok(calepine_rows_partially(plank_heap, rows, options))
+    };
+    let (calepinage, remaining, uncovered) = lay_deck(plank_heap, &deck, &options, &lay_field)?;
+    let uncovered: Vec<UncoveredRow> = uncovered
+        .into_iter()
+        .map(|RowGoal { row, start, length }| UncoveredRow { row, start, length })
+        .collect();
+    let uncovered_length: usize = uncovered.iter().map(|row| row.length).sum();
+    Ok(PartialCalepinage {
+        calepinage,
+        missing_length: uncovered_length.saturating_sub(remaining.total_length),
+        uncovered,
+    })
+}
+
+/// Calepines the deck, returning the planks left in the heap.
+pub(crate) fn calepine_deck(
+    plank_heap: PlankHeap,
+    deck: &Deck,
+    options: &CalepinageOptions,
+) -> Result<(Calepinage, PlankHeap), CalepinageError> {
+    let lay_field = |plank_heap, rows: &[RowGoal], options: &CalepinageOptions| {
+        calepine_rows(plank_heap, rows, options).map(|(field, remaining)| (field, remaining, vec![]))
+    };
+    lay_deck(plank_heap, deck, options, &lay_field).map(|(calepinage, remaining, _)| (calepinage, remaining))
+}
+
+/// Calepines the deck greedily row after row, yielding every line as soon as it is laid so
+/// that huge decks can be processed without keeping the whole calepinage. Lines already
+/// yielded being final, no backtracking is tried: the iterator fails where `calepine` would
+/// backtrack, and yields nothing after an error.
+pub fn calepine_iter(plank_heap: PlankHeap, deck: Deck) -> CalepineIter {
+    calepine_iter_with_options(plank_heap, deck, CalepinageOptions::default())
+}
+
+/// Calepines the deck like `calepine_iter`, the lines following the options. The border and
+/// the breakers being laid apart from the lines, decks with them are laid by `calepine_with_options`.
+pub fn calepine_iter_with_options(plank_heap: PlankHeap, deck: Deck, options: CalepinageOptions) -> CalepineIter {
+    let rows = check_slope(&deck, &options).and_then(|_| count_rows(&deck, &options)).and_then(|(row_count, _)| {
+        check_thickness(&plank_heap)?;
+        Ok((0..row_count).flat_map(|row| deck.row_segments(row, 0, deck.length)).collect())
+    });
+    let (rows, error) = match rows {
+        Ok(rows) => (rows, None),
+        Err(error) => (vec![], Some(error)),
+    };
+    CalepineIter {
+        the_plank_heap: sorted_by_decreasing_length(plank_heap),
+        rows,
+        covered: 0,
+        row: 0,
+        recent: Calepinage::default(),
+        recent_last_rows: vec![],
+        options,
+        error,
+        trace: None,
+    }
+}
+
+/// Calepines the deck like `calepine_iter_with_options`, yielding every state the selected
+/// solver goes through, so that the search can be animated or debugged.
+///
+/// The greedy solver is traced as it lays the lines, every plank it tries being a state.
+/// The search solvers lay the whole deck first, every candidate line they examine being a
+/// state.
+pub fn calepine_steps(plank_heap: PlankHeap, deck: Deck, options: CalepinageOptions) -> SolverSteps {
+    let mut lines = CalepineIter {
+        trace: Some(vec![]),
+        ..calepine_iter_with_options(plank_heap, deck, options)
+    };
+    let mut snapshots = std::collections::VecDeque::new();
+    if lines.options.solver != Solver::Greedy && lines.error.is_none() {
+        let bounded = lines.options.solver == Solver::BranchAndBound;
+        let options = CalepinageOptions {
+            goal: if bounded { Goal::MinimizeWaste } else { lines.options.goal },
+            ..lines.options.clone()
+        };
+        let mut search = CalepinageSearch::new(&lines.rows, &options, bounded);
+        search.trace = Some(vec![]);
+        search.explore(SearchNode::root(lines.the_plank_heap.clone()));
+        snapshots.extend(search.trace.take().into_iter().flatten());
+        if let Ok((_, remaining)) = search.into_result() {
+            lines.the_plank_heap = remaining;
+        }
+        lines.covered = lines.rows.len();
+    }
+    SolverSteps { lines, snapshots }
+}
+
+/// Lines of a deck laid one after the other, see `calepine_iter`.
+pub struct CalepineIter {
+    the_plank_heap: PlankHeap,
+    rows: Vec<RowGoal>,
+    covered: usize,
+    row: usize,
+    // the lines junctions must be staggered with, and their last row
+    recent: Calepinage,
+    recent_last_rows: Vec<usize>,
+    options: CalepinageOptions,
+    error: Option<CalepinageError>,
+    // states of the solver while laying the lines, when traced
+    trace: Option<Vec<SolverSnapshot>>,
+}
+
+impl CalepineIter {
+    /// Planks left in the heap once the lines yielded so far are laid.
+    pub fn remaining(&self) -> &PlankHeap {
+        &self.the_plank_heap
+    }
+}
+
+impl Iterator for CalepineIter {
+    type Item = Result<Line, CalepinageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            self.covered = self.rows.len();
+            return Some(Err(error));
+        }
+        let goal_row = self.rows.get(self.covered)?.row;
+        let options = &self.options;
+        let previous_lines = previous_lines(&self.recent, &self.recent_last_rows, goal_row, options);
+        let rows = &self.rows[self.covered..];
+        let selected = select_line_traced(&self.the_plank_heap, self.row, rows, &previous_lines, options, self.trace.as_mut());
+        let (goal, step) = match selected {
+            Ok(selected) => selected,
+            Err(error) => {
+                self.covered = self.rows.len();
+                return Some(Err(error));
+            }
+        };
+        let line = line_for(self.row, &goal, &step, options);
+        let recent = std::mem::take(&mut self.recent);
+        let (mut recent, remaining, _) = add_line(recent, line.clone(), step, options);
+        recent.cuts.clear();
+        self.the_plank_heap = remaining;
+        self.recent_last_rows.push(goal.row + line.width() - 1);
+        self.covered += line.width();
+        self.row += 1;
+        let depth = options.stagger_depth.max(1);
+        let next_row = self.rows.get(self.covered).map_or(usize::MAX, |goal| goal.row);
+        while self.recent_last_rows.first().is_some_and(|&last_row| last_row.saturating_add(depth) < next_row) {
+            recent.lines.remove(0);
+            self.recent_last_rows.remove(0);
+        }
+        self.recent = recent;
+        Some(Ok(line))
+    }
+}
+
+/// States of the solver laying a deck, see `calepine_steps`.
+pub struct SolverSteps {
+    lines: CalepineIter,
+    snapshots: std::collections::VecDeque<SolverSnapshot>,
+}
+
+impl SolverSteps {
+    /// Planks left in the heap once the lines searched so far are laid.
+    pub fn remaining(&self) -> &PlankHeap {
+        self.lines.remaining()
+    }
+}
+
+impl Iterator for SolverSteps {
+    type Item = SolverSnapshot;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.snapshots.is_empty() {
+            let laid = self.lines.next();
+            self.snapshots.extend(self.lines.trace.iter_mut().flat_map(|trace| trace.drain(..)));
+            laid.as_ref()?;
+        }
+        self.snapshots.pop_front()
+    }
+}
+
+/// What the solver did with a plank it tried on a line, see `calepine_explained`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Decision {
+    /// Deck row of the line.
+    pub row: usize,
+    /// Where the plank would start, from the line start.
+    pub at: usize,
+    pub plank: Plank,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Outcome {
+    /// The plank was selected, though the search may give it up when it backtracks.
+    Selected,
+    /// The plank was skipped, or stashed when its junction would be misplaced.
+    Rejected(Rejection),
+}
+
+impl Decision {
+    fn of(snapshot: SolverSnapshot, gap: usize) -> Option<Decision> {
+        let SolverSnapshot { row, step, rejected } = snapshot;
+        let mut before: Vec<Plank> = step.selected.into_planks();
+        let (plank, outcome) = match rejected {
+            Some((plank, rejection)) => (plank, Outcome::Rejected(rejection)),
+            None => (before.pop()?, Outcome::Selected),
+        };
+        let length: usize = before.iter().map(|plank| plank.length).sum();
+        let at = if before.is_empty() { 0 } else { covered_length(length, before.len(), gap) + gap };
+        Some(Decision { row, at, plank, outcome })
+    }
+}
+
+impl std::fmt::Display for Decision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "row {}, at {}: {} ", self.row, self.at, self.plank.length)?;
+        match self.outcome {
+            Outcome::Selected => write!(f, "selected"),
+            Outcome::Rejected(Rejection::TooLong) => write!(f, "skipped, too long"),
+            Outcome::Rejected(Rejection::TooShortEndPiece) => write!(f, "skipped, the end piece would be too short"),
+            Outcome::Rejected(Rejection::MisplacedJunction) => write!(f, "stashed, the junction would be misplaced"),
+            Outcome::Rejected(Rejection::Misgraded) => write!(f, "skipped, its grade isn't allowed there"),
+            Outcome::Rejected(Rejection::DefectAtJunction) => write!(f, "skipped, a defect would be at a junction"),
+        }
+    }
+}
+
+/// Calepines the deck greedily, the field lines being laid one after the other without
+/// backtracking, and explains the layout: every plank the solver tried on the field lines,
+/// in order, with what it did with it.
+pub fn calepine_explained(
+    plank_heap: PlankHeap,
+    deck: Deck,
+    options: CalepinageOptions,
+) -> (Result<Calepinage, CalepinageError>, Vec<Decision>) {
+    let trace = RefCell::new(vec![]);
+    let lay_field = |plank_heap, rows: &[RowGoal], options: &CalepinageOptions| {
+        let the_plank_heap = sorted_by_decreasing_length(plank_heap);
+        let mut trace = trace.borrow_mut();
+        calepine_greedily_traced(the_plank_heap, Calepinage::default(), vec![], rows, options, false, Some(&mut trace))
+            .map(|(field, remaining, uncovered)| (Calepinage { kerf: options.kerf, ..field }, remaining, uncovered))
+    };
+    let calepinage = lay_deck(plank_heap, &deck, &options, &lay_field).map(|(calepinage, _, _)| calepinage);
+    let decisions = trace
+        .into_inner()
+        .into_iter()
+        .filter_map(|snapshot| Decision::of(snapshot, options.end_gap))
+        .collect();
+    (calepinage, decisions)
+}
+
+/// Picks the line laid on every row of the deck, among the lines filling the row, so that
+/// custom heuristics can drive the calepinage.
+pub trait LayoutStrategy {
+    /// Index of the line to lay among the candidates, listed with their start and gap as
+    /// they would be laid after the lines of the calepinage. None lets the solver select
+    /// the line as usual.
+    fn select_line(&self, calepinage: &Calepinage, candidates: &[Line]) -> Option<usize>;
+}
+
+/// Calepines the deck greedily, the strategy choosing the line of every row. Every line
+/// filling a row is listed, only suitable for small heaps.
+pub fn calepine_with_layout_strategy(
+    plank_heap: PlankHeap,
+    deck: Deck,
+    options: CalepinageOptions,
+    strategy: &dyn LayoutStrategy,
+) -> Result<Calepinage, CalepinageError> {
+    let lay_field = |plank_heap, rows: &[RowGoal], options: &CalepinageOptions| {
+        calepine_rows_with_layout_strategy(plank_heap, rows, options, strategy)
+    };
+    lay_deck(plank_heap, &deck, &options, &lay_field).map(|(calepinage, _, _)| calepinage)
+}
+
+/// Limits of an anytime search, the search stopping at the first one reached.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Budget {
+    /// Number of calepinages being searched that may be examined.
+    pub nodes: Option<usize>,
+    pub time: Option<Duration>,
+    /// Stops the search once cancelled, checked before every calepinage examined.
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Budget {
+    pub fn with_nodes(mut self, nodes: usize) -> Self {
+        self.nodes = Some(nodes);
+        self
+    }
+
+    pub fn with_time(mut self, time: Duration) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+}
+
+/// Flag shared by its clones, cancelling the searches given one of them, from any thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Tokens are equal when they are clones of each other.
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Best calepinage found within a budget.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BudgetedCalepinage {
+    pub calepinage: Calepinage,
+    /// Whether the search completed before running out of budget, no calepinage fulfilling
+    /// the goal better.
+    pub optimal: bool,
+}
+
+/// Searches every calepinage like the exhaustive solver, bounded as the branch and bound
+/// one when it is the selected solver, until the budget is exhausted or the search is
+/// cancelled. The greedy calepinage, given the time of the budget again, is returned when
+/// the search stops before any calepinage is found. To only lay the greedy calepinage
+/// within a time, see `CalepinageOptions::with_deadline`.
+pub fn calepine_with_budget(
+    plank_heap: PlankHeap,
+    deck: Deck,
+    options: CalepinageOptions,
+    budget: Budget,
+) -> Result<BudgetedCalepinage, CalepinageError> {
+    let within_budget = |options: &CalepinageOptions| CalepinageOptions {
+        deadline: budget.time.map(|time| Instant::now() + time).or(options.deadline),
+        ..options.clone()
+    };
+    let options = within_budget(&options);
+    let optimal = Cell::new(true);
+    let lay_field = |plank_heap: PlankHeap, rows: &[RowGoal], options: &CalepinageOptions| {
+        let the_plank_heap = sorted_by_decreasing_length(plank_heap);
+        let bounded = options.solver == Solver::BranchAndBound;
+        let search_options = CalepinageOptions {
+            goal: if bounded { Goal::MinimizeWaste } else { options.goal },
+            ..options.clone()
+        };
+        let mut search = CalepinageSearch::new(rows, &search_options, bounded);
+        search.limits = Some(SearchLimits {
+            nodes: budget.nodes,
+            deadline: budget.time.map(|time| Instant::now() + time),
+            cancellation: budget.cancellation.clone(),
+        });
+        search.explore(SearchNode::root(the_plank_heap.clone()));
+        let exhausted = search.exhausted;
+        let laid = match search.into_result() {
+            Err(_) if exhausted => {
+                let greedy = CalepinageOptions {
+                    solver: Solver::Greedy,
+                    ..within_budget(options)
+                };
+                calepine_rows(the_plank_heap, rows, &greedy)
+            }
+            laid => laid,
+        };
+        optimal.set(optimal.get() && !exhausted);
+        laid.map(|(calepinage, remaining)| {
+            let kerf = options.kerf;
+            (Calepinage { kerf, ..calepinage }, remaining, vec![])
+        })
+    };
+    let (calepinage, _, _) = lay_deck(plank_heap, &deck, &options, &lay_field)?;
+    Ok(BudgetedCalepinage {
+        calepinage,
+        optimal: optimal.get(),
+    })
+}
+
+/// Progress of a calepinage while the solver lays the field rows, see
+/// `calepine_with_progress`.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Progress {
+    /// Field rows laid, the deepest the search went so far for the exhaustive solvers.
+    pub rows_completed: usize,
+    /// Field rows of the deck.
+    pub rows: usize,
+    /// Calepinages the solver went through, partial ones included.
+    pub nodes_explored: usize,
+    /// Waste of the best calepinage found so far, like `Calepinage::waste`.
+    pub best_waste: Option<usize>,
+}
+
+/// Calepines the deck like `calepine_with_options`, reporting the progress of the
+/// exhaustive solvers after every node they explore, and once the field is laid for the
+/// greedy one. The search runs on a single thread.
+pub fn calepine_with_progress(
+    plank_heap: PlankHeap,
+    deck: Deck,
+    options: CalepinageOptions,
+    progress: impl FnMut(Progress),
+) -> Result<Calepinage, CalepinageError> {
+    let progress = RefCell::new(progress);
+    let lay_field = |plank_heap: PlankHeap, rows: &[RowGoal], options: &CalepinageOptions| {
+        let mut progress = progress.borrow_mut();
+        let laid = if options.solver == Solver::Greedy {
+            let laid = calepine_rows(plank_heap, rows, options)?;
+            progress(Progress {
+                rows_completed: rows.len(),
+                rows: rows.len(),
+                nodes_explored: laid.0.lines.len(),
+                best_waste: Some(laid.0.waste(options)),
+            });
+            laid
+        } else {
+            let bounded = options.solver == Solver::BranchAndBound;
+            let search_options = CalepinageOptions {
+                goal: if bounded { Goal::MinimizeWaste } else { options.goal },
+                ..options.clone()
+            };
+            let mut search = CalepinageSearch::new(rows, &search_options, bounded);
+            search.progress = Some(&mut *progress);
+            search.explore(SearchNode::root(sorted_by_decreasing_length(plank_heap)));
+            let (calepinage, remaining) = search.into_result()?;
+            (Calepinage { kerf: options.kerf, ..calepinage }, remaining)
+        };
+        Ok((laid.0, laid.1, vec![]))
+    };
+    lay_deck(plank_heap, &deck, &options, &lay_field).map(|(calepinage, _, _)| calepinage)
+}
+
+/// Calepines the deck again after the heap changed, keeping the first lines of the previous
+/// calepinage as long as their planks are still in the heap, as whole planks, and they
+/// still cover the same rows. The lines following them are laid greedily, the whole field
+/// being laid again when they can't be. The border and the breakers are laid again.
+pub fn calepine_incrementally(
+    previous: &Calepinage,
+    plank_heap: PlankHeap,
+    deck: Deck,
+    options: CalepinageOptions,
+) -> Result<Calepinage, CalepinageError> {
+    let lay_field = |plank_heap: PlankHeap, rows: &[RowGoal], options: &CalepinageOptions| {
+        let mut planks = plank_heap.clone().into_planks();
+        let mut kept = Calepinage {
+            kerf: options.kerf,
+            ..Calepinage::default()
+        };
+        let mut lines_last_row = vec![];
+        let mut covered = 0;
+        for line in previous.lines.iter() {
+            let is_same_goal = line_goal(&rows[covered..], line.width())
+                .is_some_and(|goal| goal.start == line.start && goal.length == line.length());
+            if !is_same_goal || line.gap != options.end_gap || !take_planks(&mut planks, &line.planks) {
+                break;
+            }
+            lines_last_row.push(rows[covered].row + line.width() - 1);
+            covered += line.width();
+            kept = kept.with_line(line.clone());
+        }
+        let the_plank_heap = sorted_by_decreasing_length(PlankHeap::of_planks(planks));
+        calepine_greedily_after(the_plank_heap, kept, lines_last_row, rows, options, false)
+            .or_else(|_| calepine_rows(plank_heap, rows, options).map(|(field, remaining)| (field, remaining, vec![])))
+    };
+    lay_deck(plank_heap, &deck, &options, &lay_field).map(|(calepinage, _, _)| calepinage)
+}
+
+/// Removes the planks from the given ones, leaving them untouched if some are missing.
+fn take_planks(planks: &mut Vec<Plank>, taken: &[Plank]) -> bool {
+    let mut left = planks.clone();
+    for plank in taken {
+        match left.iter().position(|candidate| candidate == plank) {
+            Some(index) => {
+                left.remove(index);
+            }
+            None => return false,
+        }
+    }
+    *planks = left;
+    true
+}
+
+/// Lays the field rows, returning the planks left in the heap and the rows left uncovered.
+type FieldLayer<'a> = dyn Fn(PlankHeap, &[RowGoal], &CalepinageOptions) -> Result<(Calepinage, PlankHeap, Vec<RowGoal>), CalepinageError> + 'a;
+
+/// Lays the border, the breakers then the field, returning the planks left in the heap and
+/// the field rows left uncovered.
+fn lay_deck(
+    plank_heap: PlankHeap,
+    deck: &Deck,
+    options: &CalepinageOptions,
+    lay_field: &FieldLayer,
+) -> Result<(Calepinage, PlankHeap, Vec<RowGoal>), CalepinageError> {
+    check_slope(deck, options)?;
+    let (row_count, rip) = count_rows(deck, options)?;
+    check_thickness(&plank_heap)?;
+    let mut the_plank_heap = plank_heap;
+    let (mut field_rows, mut field_start, mut field_length) = (0..row_count, 0, deck.length);
+    let mut border = Calepinage::default();
+    if let Some(board_width) = options.border {
+        if deck.board_width.is_none() {
+            return Err(CalepinageError::UnknownBoardWidth);
+        }
+        if row_count < 3 || deck.length <= 2 * board_width {
+            return Err(CalepinageError::DeckTooSmallForBorder);
+        }
+        let sides: Vec<RowGoal> = [deck.length, deck.width, deck.length, deck.width]
+            .iter()
+            .enumerate()
+            .map(|(row, &length)| RowGoal { row, start: 0, length })
+            .collect();
+        let (laid, remaining) = lay_apart(the_plank_heap, &sides, options)?;
+        border = laid;
+        the_plank_heap = remaining;
+        field_rows = 1..row_count - 1;
+        field_start = board_width;
+        field_length = deck.length - 2 * board_width;
+    }
+
+    let mut field_deck = deck.clone();
+    let mut breakers = vec![];
+    let mut breaker_cuts = vec![];
+    if let Some(breaker_boards) = &options.breakers {
+        let positions = breaker_boards.positions(field_start, field_length);
+        let row_width = deck.board_width.unwrap_or(breaker_boards.board_width);
+        let rows = field_rows.len();
+        let length = rows * row_width + rows.saturating_sub(1) * options.side_gap;
+        let lines: Vec<RowGoal> = (0..positions.len()).map(|row| RowGoal { row, start: 0, length }).collect();
+        let (laid, remaining) = lay_apart(the_plank_heap, &lines, options)?;
+        breakers = positions.iter().zip(laid.lines).map(|(&at, line)| Breaker { at, line }).collect();
+        breaker_cuts = laid.cuts;
+        the_plank_heap = remaining;
+        for at in positions {
+            field_deck = field_deck.with_cutout(Cutout {
+                start: at,
+                length: breaker_boards.board_width,
+                first_row: field_rows.start,
+                rows,
+            });
+        }
+    }
+
+    let rows: Vec<RowGoal> = field_rows
+        .flat_map(|row| field_deck.row_segments(row, field_start, field_length))
+        .collect();
+    let (field, remaining, uncovered) = lay_field(the_plank_heap, &rows, options)?;
+
+    let calepinage = Calepinage {
+        cuts: border.cuts.into_iter().chain(breaker_cuts).chain(field.cuts).collect(),
+        rip,
+        border: border.lines,
+        breakers,
+        ..field
+    };
+    Ok((calepinage, remaining, uncovered))
+}
+
+/// Rejects sloping decks whose boards, along the deck length, would break the slope rule.
+pub(crate) fn check_slope(deck: &Deck, options: &CalepinageOptions) -> Result<(), CalepinageError> {
+    match (deck.slope, options.slope_rule) {
+        (Some(slope), Some(rule)) if !rule.allows(Orientation::AlongLength, slope) => {
+            Err(CalepinageError::AgainstSlope { slope, rule })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Rejects heaps mixing planks of different thicknesses, planks of unknown thickness
+/// being accepted with any other.
+fn check_thickness(plank_heap: &PlankHeap) -> Result<(), CalepinageError> {
+    let mut thicknesses: Vec<usize> = plank_heap.counts.iter().filter_map(|(plank, _)| plank.thickness).collect();
+    thicknesses.sort_unstable();
+    thicknesses.dedup();
+    if thicknesses.len() > 1 {
+        return Err(CalepinageError::MixedThicknesses(thicknesses));
+    }
+    Ok(())
+}
+
+/// Number of rows of the deck and the width of the last one when it must be ripped. A
+/// remaining width not wider than the side gap is left as a gap.
+pub(crate) fn count_rows(deck: &Deck, options: &CalepinageOptions) -> Result<(usize, Option<usize>), CalepinageError> {
+    let board_width = match deck.board_width {
+        None => return Ok((deck.width, None)),
+        Some(board_width) => board_width,
+    };
+    let gap = options.side_gap;
+    let (full_rows, remaining_width) = fit_rows(deck.width, board_width, gap);
+    if remaining_width <= gap {
+        return Ok((full_rows, None));
+    }
+    match options.last_row {
+        LastRow::Reject => Err(CalepinageError::UncoveredDeckWidth(remaining_width)),
+        LastRow::Rip => Ok((full_rows + 1, Some(remaining_width - gap))),
+    }
+}
+
+/// Lays lines apart from the field, like the sides of the frame whose corners are mitred
+/// so that every side runs along the whole deck edge, or the breaker boards. These lines
+/// are laid greedily, longest planks first and without junction constraints.
+fn lay_apart(
+    plank_heap: PlankHeap,
+    lines: &[RowGoal],
+    options: &CalepinageOptions,
+) -> Result<(Calepinage, PlankHeap), CalepinageError> {
+    let apart_options = CalepinageOptions {
+        pattern: Pattern::Sorted,
+        strategy: Strategy::LongestFirst,
+        alternate_sides: false,
+        ..options.clone()
+    };
+
+    let mut the_plank_heap = sorted_by_decreasing_length(plank_heap);
+    let mut laid = Calepinage::default();
+    for (row, line) in lines.iter().enumerate() {
+        let (goal, step) = select_line(&the_plank_heap, row, std::slice::from_ref(line), &PreviousLines::default(), &apart_options)?;
+        let (next_laid, next_remaining, _) = lay_line(laid, &goal, step, &apart_options);
+        laid = next_laid;
+        the_plank_heap = next_remaining;
+    }
+    Ok((laid, the_plank_heap))
+}
+
+/// Part of a deck row a line must cover, a row being split in several parts by cutouts.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct RowGoal {
+    pub row: usize,
+    pub start: usize,
+    pub length: usize,
+}
+
+pub(crate) fn calepine_rows(
+    plank_heap: PlankHeap,
+    rows: &[RowGoal],
+    options: &CalepinageOptions,
+) -> Result<(Calepinage, PlankHeap), CalepinageError> {
+    let the_plank_heap = sorted_by_decreasing_length(plank_heap);
+
+    let laid = match options.solver {
+        Solver::Greedy => calepine_greedily(the_plank_heap.clone(), rows, options, false)
+            .map(|(calepinage, remaining, _)| (calepinage, remaining))
+            .or_else(|error| match may_cover(&the_plank_heap, rows, options) {
+                true if error != CalepinageError::OutOfBudget => {
+                    let backtracked = calepine_with_backtracking(the_plank_heap, rows, options);
+                    backtracked.ok_or(if options.is_out_of_budget() { CalepinageError::OutOfBudget } else { error })
+                }
+                _ => Err(error),
+            }),
+        Solver::Exhaustive => calepine_exhaustively(the_plank_heap, rows, options, false),
+        Solver::BranchAndBound => {
+            let options = CalepinageOptions {
+                goal: Goal::MinimizeWaste,
+                ..options.clone()
+            };
+            calepine_exhaustively(the_plank_heap, rows, &options, true)
+        }
+    };
+    laid.map(|(calepinage, remaining)| {
+        let kerf = options.kerf;
+        (Calepinage { kerf, ..calepinage }, remaining)
+    })
+}
+
+/// Calepines the rows, skipping the rows that can't be filled. The exhaustive solver is
+/// only used when every row can be filled.
+fn calepine_rows_partially(
+    plank_heap: PlankHeap,
+    rows: &[RowGoal],
+    options: &CalepinageOptions,
+) -> (Calepinage, PlankHeap, Vec<RowGoal>) {
+    if let Ok((calepinage, remaining)) = calepine_rows(plank_heap.clone(), rows, options) {
+        return (calepinage, remaining, vec![]);
+    }
+    let the_plank_heap = sorted_by_decreasing_length(plank_heap);
+    let (calepinage, remaining, uncovered) =
+        calepine_greedily(the_plank_heap, rows, options, true).expect("a partial calepinage never fails");
+    let kerf = options.kerf;
+    (Calepinage { kerf, ..calepinage }, remaining, uncovered)
+}
+
+/// Fills lines one after the other, returning the rows skipped when `partial` instead of
+/// failing.
+fn calepine_greedily(
+    plank_heap: PlankHeap,
+    rows: &[RowGoal],
+    options: &CalepinageOptions,
+    partial: bool,
+) -> Result<(Calepinage, PlankHeap, Vec<RowGoal>), CalepinageError> {
+    calepine_greedily_after(plank_heap, Calepinage::default(), vec![], rows, options, partial)
+}
+
+/// Fills the lines following the lines already laid on the first rows.
+fn calepine_greedily_after(
+    plank_heap: PlankHeap,
+    laid: Calepinage,
+    laid_lines_last_row: Vec<usize>,
+    rows: &[RowGoal],
+    options: &CalepinageOptions,
+    partial: bool,
+) -> Result<(Calepinage, PlankHeap, Vec<RowGoal>), CalepinageError> {
+    calepine_greedily_traced(plank_heap, laid, laid_lines_last_row, rows, options, partial, None)
+}
+
+/// Fills the lines like `calepine_greedily_after`, recording the states of the search in
+/// the trace.
+fn calepine_greedily_traced(
+    plank_heap: PlankHeap,
+    laid: Calepinage,
+    laid_lines_last_row: Vec<usize>,
+    rows: &[RowGoal],
+    options: &CalepinageOptions,
+    partial: bool,
+    mut trace: Option<&mut Vec<SolverSnapshot>>,
+) -> Result<(Calepinage, PlankHeap, Vec<RowGoal>), CalepinageError> {
+    let mut the_plank_heap = plank_heap;
+    let mut covered: usize = laid.lines.iter().map(Line::width).sum();
+    let mut calepinage = laid;
+    let mut lines_last_row = laid_lines_last_row;
+    let mut uncovered = vec![];
+    while covered < rows.len() {
+        let row = calepinage.lines.len();
+        let previous_lines = previous_lines(&calepinage, &lines_last_row, rows[covered].row, options);
+        let selected = select_line_traced(&the_plank_heap, row, &rows[covered..], &previous_lines, options, trace.as_deref_mut());
+        let (goal, step) = match selected {
+            Ok(selected) => selected,
+            Err(_) if partial => {
+                uncovered.push(rows[covered].clone());
+                covered += 1;
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        let (next_calepinage, next_remaining, _) = lay_line(calepinage, &goal, step, options);
+        calepinage = next_calepinage;
+        the_plank_heap = next_remaining;
+        let width = calepinage.lines[row].width();
+        lines_last_row.push(goal.row + width - 1);
+        covered += width;
+    }
+
+    Ok((calepinage, the_plank_heap, uncovered))
+}
+
+fn calepine_rows_with_layout_strategy(
+    plank_heap: PlankHeap,
+    rows: &[RowGoal],
+    options: &CalepinageOptions,
+    strategy: &dyn LayoutStrategy,
+) -> Result<(Calepinage, PlankHeap, Vec<RowGoal>), CalepinageError> {
+    let mut the_plank_heap = sorted_by_decreasing_length(plank_heap);
+    let mut calepinage = Calepinage {
+        kerf: options.kerf,
+        ..Calepinage::default()
+    };
+    let mut lines_last_row = vec![];
+    let mut covered = 0;
+    while covered < rows.len() {
+        let row = calepinage.lines.len();
+        let previous_lines = previous_lines(&calepinage, &lines_last_row, rows[covered].row, options);
+        let mut candidates = row_candidates(&the_plank_heap, row, &rows[covered..], &previous_lines, options, usize::MAX);
+        let lines: Vec<Line> = candidates.iter().map(|(goal, step)| line_for(row, goal, step, options)).collect();
+        let (goal, step) = match strategy.select_line(&calepinage, &lines) {
+            Some(index) if index < candidates.len() => candidates.swap_remove(index),
+            _ => select_line(&the_plank_heap, row, &rows[covered..], &previous_lines, options)?,
+        };
+        let (next_calepinage, next_remaining, _) = lay_line(calepinage, &goal, step, options);
+        calepinage = next_calepinage;
+        the_plank_heap = next_remaining;
+        let width = calepinage.lines[row].width();
+        lines_last_row.push(goal.row + width - 1);
+        covered += width;
+    }
+
+    Ok((calepinage, the_plank_heap, vec![]))
+}
+
+/// Candidate lines listed at most by the backtracking search before giving up, every line
+/// laid being listed first.
+const MAX_BACKTRACKING_LINES: usize = 100_000;
+
+/// Depth-first search laying the greedy line of every row first and trying the other
+/// candidate lines of the previous rows when a row can't be filled, stopping at the first
+/// calepinage found. Branches whose planks can't cover the rows left are abandoned.
+fn calepine_with_backtracking(
+    plank_heap: PlankHeap,
+    rows: &[RowGoal],
+    options: &CalepinageOptions,
+) -> Option<(Calepinage, PlankHeap)> {
+    let mut search = BacktrackingSearch {
+        rows,
+        options,
+        listed_lines: 0,
+    };
+    search.fill(Calepinage::default(), vec![], plank_heap)
+}
+
+struct BacktrackingSearch<'a> {
+    rows: &'a [RowGoal],
+    options: &'a CalepinageOptions,
+    listed_lines: usize,
+}
+
+impl<'a> BacktrackingSearch<'a> {
+    fn fill(&mut self, calepinage: Calepinage, lines_last_row: Vec<usize>, the_plank_heap: PlankHeap) -> Option<(Calepinage, PlankHeap)> {
+        let row = calepinage.lines.len();
+        let covered: usize = calepinage.lines.iter().map(Line::width).sum();
+        if covered == self.rows.len() {
+            return Some((calepinage, the_plank_heap));
+        }
+
+        let rows = &self.rows[covered..];
+        if self.listed_lines >= MAX_BACKTRACKING_LINES
+            || !may_cover(&the_plank_heap, rows, self.options)
+            || self.options.is_out_of_budget()
+        {
+            return None;
+        }
+        let previous_lines = previous_lines(&calepinage, &lines_last_row, rows[0].row, self.options);
+        let mut candidates: Vec<(RowGoal, CalepineStep)> =
+            select_line(&the_plank_heap, row, rows, &previous_lines, self.options).into_iter().collect();
+        let limit = MAX_BACKTRACKING_LINES - self.listed_lines;
+        for candidate in row_candidates(&the_plank_heap, row, rows, &previous_lines, self.options, limit) {
+            if candidates.first() != Some(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+        self.listed_lines += candidates.len();
+        for (goal, step) in candidates {
+            let (next_calepinage, next_remaining, _) = lay_line(calepinage.clone(), &goal, step, self.options);
+            let mut next_lines_last_row = lines_last_row.clone();
+            next_lines_last_row.push(goal.row + next_calepinage.lines[row].width() - 1);
+            if let Some(found) = self.fill(next_calepinage, next_lines_last_row, next_remaining) {
+                return Some(found);
+            }
+        }
+        None
+    }
+}
+
+fn calepine_exhaustively(
+    plank_heap: PlankHeap,
+    rows: &[RowGoal],
+    options: &CalepinageOptions,
+    bounded: bool,
+) -> Result<(Calepinage, PlankHeap), CalepinageError> {
+    let mut search = CalepinageSearch::new(rows, options, bounded);
+    #[cfg(feature = "rayon")]
+    search.explore_in_parallel(SearchNode::root(plank_heap));
+    #[cfg(not(feature = "rayon"))]
+    search.explore(SearchNode::root(plank_heap));
+    search.into_result()
+}
+
+/// Depth-first search over the candidate lines of every row, keeping the calepinage of
+/// least cost. Branches already costing as much as the best calepinage are abandoned.
+///
+/// When bounded, branches whose planks can't cover the rows left are abandoned too, the
+/// candidate lines wasting less are explored first and the search stops at the first
+/// calepinage wasting nothing.
+struct CalepinageSearch<'a> {
+    rows: &'a [RowGoal],
+    options: &'a CalepinageOptions,
+    bounded: bool,
+    best: Option<(Calepinage, PlankHeap, (f64, f64))>,
+    // error of the furthest row the search failed to fill
+    error: Option<(usize, CalepinageError)>,
+    limits: Option<SearchLimits>,
+    exhausted: bool,
+    progress: Option<&'a mut dyn FnMut(Progress)>,
+    nodes_explored: usize,
+    rows_completed: usize,
+    best_waste: Option<usize>,
+    // candidate lines examined, when traced
+    trace: Option<Vec<SolverSnapshot>>,
+}
+
+/// Budget left to an anytime search.
+struct SearchLimits {
+    nodes: Option<usize>,
+    deadline: Option<Instant>,
+    cancellation: Option<CancellationToken>,
+}
+
+/// A calepinage being searched, with the last row of its lines, the planks left and its
+/// metrics.
+struct SearchNode {
+    calepinage: Calepinage,
+    lines_last_row: Vec<usize>,
+    the_plank_heap: PlankHeap,
+    metrics: Metrics,
+}
+
+impl SearchNode {
+    fn root(the_plank_heap: PlankHeap) -> Self {
+        SearchNode {
+            calepinage: Calepinage::default(),
+            lines_last_row: vec![],
+            the_plank_heap,
+            metrics: Metrics::default(),
+        }
+    }
+}
+
+impl<'a> CalepinageSearch<'a> {
+    fn new(rows: &'a [RowGoal], options: &'a CalepinageOptions, bounded: bool) -> Self {
+        CalepinageSearch {
+            rows,
+            options,
+            bounded,
+            best: None,
+            error: None,
+            limits: None,
+            exhausted: false,
+            progress: None,
+            nodes_explored: 0,
+            rows_completed: 0,
+            best_waste: None,
+            trace: None,
+        }
+    }
+
+    fn into_result(self) -> Result<(Calepinage, PlankHeap), CalepinageError> {
+        match (self.best, self.error) {
+            (Some((calepinage, remaining, _)), _) => Ok((calepinage, remaining)),
+            (None, _) if self.exhausted => Err(CalepinageError::OutOfBudget),
+            (None, Some((_, error))) => Err(error),
+            // every branch was abandoned for lack of planks or budget
+            (None, None) => Err(CalepinageError::NotEnoughPlanks),
+        }
+    }
+
+    fn explore(&mut self, node: SearchNode) {
+        if self.is_out_of_budget() {
+            return;
+        }
+        let children = self.expand(node);
+        if let Some(progress) = self.progress.as_mut() {
+            progress(Progress {
+                rows_completed: self.rows_completed,
+                rows: self.rows.len(),
+                nodes_explored: self.nodes_explored,
+                best_waste: self.best_waste,
+            });
+        }
+        for child in children {
+            self.explore(child);
+        }
+    }
+
+    fn is_out_of_budget(&mut self) -> bool {
+        if self.options.is_out_of_budget() {
+            self.exhausted = true;
+        }
+        if let Some(limits) = &mut self.limits {
+            let out_of_time = limits.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+            let cancelled = limits.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled);
+            match limits.nodes {
+                _ if out_of_time || cancelled => self.exhausted = true,
+                Some(0) => self.exhausted = true,
+                Some(nodes) => limits.nodes = Some(nodes - 1),
+                None => {}
+            }
+        }
+        self.exhausted
+    }
+
+    /// Explores the subtrees of the root candidate lines on several threads, every thread
+    /// searching its own best calepinage. The best calepinages are then compared in the
+    /// candidates order, giving the same calepinage as a sequential search.
+    #[cfg(feature = "rayon")]
+    fn explore_in_parallel(&mut self, root: SearchNode) {
+        use rayon::prelude::*;
+
+        if self.is_out_of_budget() {
+            return;
+        }
+        let children = self.expand(root);
+        let (rows, options, bounded) = (self.rows, self.options, self.bounded);
+        let searched: Vec<_> = children
+            .into_par_iter()
+            .map(|child| {
+                let mut search = CalepinageSearch::new(rows, options, bounded);
+                search.explore(child);
+                (search.best, search.error, search.exhausted)
+            })
+            .collect();
+        for (best, error, exhausted) in searched {
+            self.exhausted |= exhausted;
+            if let Some((calepinage, remaining, cost)) = best {
+                if self.best.as_ref().is_none_or(|(_, _, best_cost)| cost < *best_cost) {
+                    self.best = Some((calepinage, remaining, cost));
+                }
+            }
+            if let Some((row, error)) = error {
+                if self.error.as_ref().is_none_or(|(error_row, _)| row > *error_row) {
+                    self.error = Some((row, error));
+                }
+            }
+        }
+    }
+
+    /// Records the node when it covers every row and lists the nodes of its candidate
+    /// lines otherwise, unless the node can't lead to a better calepinage.
+    fn expand(&mut self, node: SearchNode) -> Vec<SearchNode> {
+        let SearchNode {
+            calepinage,
+            lines_last_row,
+            the_plank_heap,
+            metrics,
+        } = node;
+        self.nodes_explored += 1;
+        let cost = self.options.goal.cost(&metrics);
+        if self.best.as_ref().is_some_and(|(_, _, best_cost)| cost >= *best_cost || (self.bounded && best_cost.0 == 0.0)) {
+            return vec![];
+        }
+        let row = calepinage.lines.len();
+        let covered: usize = calepinage.lines.iter().map(Line::width).sum();
+        self.rows_completed = self.rows_completed.max(covered);
+        if covered == self.rows.len() {
+            self.best = Some((calepinage, the_plank_heap, cost));
+            self.best_waste = Some(metrics.waste + metrics.cuts * self.options.kerf);
+            return vec![];
+        }
 
-    pub fn new(length: usize, width: usize) -> Result<Self, String> {
-        if length == 0 || width == 0 {
-            Err("a deck can't have any zero dimension".to_string())
-        } else if length > Self::MAX_LENGTH {
-            Err(format!("max length of deck is {}", Self::MAX_LENGTH))
-        } else {
-            Ok(Deck { length, width })
+        let rows = &self.rows[covered..];
+        if self.bounded && !may_cover(&the_plank_heap, rows, self.options) {
+            return vec![];
         }
+        let previous_lines = previous_lines(&calepinage, &lines_last_row, rows[0].row, self.options);
+        // every candidate line being a node, no more are listed than the budget may examine
+        let limit = self.limits.as_ref().and_then(|limits| limits.nodes).map_or(usize::MAX, |nodes| nodes.max(1));
+        let mut candidates = row_candidates(&the_plank_heap, row, rows, &previous_lines, self.options, limit);
+        if self.bounded {
+            candidates.sort_by_cached_key(|(_, step)| self.line_waste(step));
+        }
+        if candidates.is_empty() {
+            if self.error.as_ref().is_none_or(|(error_row, _)| row > *error_row) {
+                let error = select_line(&the_plank_heap, row, rows, &previous_lines, self.options)
+                    .expect_err("a line can't be selected without being a candidate");
+                self.error = Some((row, error));
+            }
+            return vec![];
+        }
+        let mut children = vec![];
+        for (goal, step) in candidates {
+            if let Some(trace) = self.trace.as_mut() {
+                trace.push(SolverSnapshot {
+                    row: goal.row,
+                    step: step.clone(),
+                    rejected: None,
+                });
+            }
+            let line_cost = step.cost();
+            let (next_calepinage, next_remaining, line_waste) = lay_line(calepinage.clone(), &goal, step, self.options);
+            let line = &next_calepinage.lines[row];
+            let mut next_lines_last_row = lines_last_row.clone();
+            next_lines_last_row.push(goal.row + line.width() - 1);
+            let (stagger_shortfall, joint_figures) = match (&self.options.goal, &self.options.joint_figures) {
+                (Goal::Weighted(objective), figures) => (
+                    objective.stagger_shortfall(line, &previous_lines.junctions),
+                    figures.map_or(0, |figures| {
+                        self::joint_figures(&calepinage, &lines_last_row, line, goal.row, &figures).len()
+                    }),
+                ),
+                _ => (0, 0),
+            };
+            let next_metrics = Metrics {
+                waste: metrics.waste + line_waste,
+                cuts: next_calepinage.cuts.len(),
+                planks: metrics.planks + line.planks.len(),
+                stagger_shortfall: metrics.stagger_shortfall + stagger_shortfall,
+                cost: metrics.cost + line_cost,
+                joint_figures: metrics.joint_figures + joint_figures,
+            };
+            children.push(SearchNode {
+                calepinage: next_calepinage,
+                lines_last_row: next_lines_last_row,
+                the_plank_heap: next_remaining,
+                metrics: next_metrics,
+            });
+        }
+        children
     }
-}
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Plank {
-    pub length: usize,
+    fn line_waste(&self, step: &CalepineStep) -> usize {
+        step.cuts
+            .iter()
+            .map(|cut| cut.offcut_with_kerf(self.options.kerf))
+            .filter(|offcut| !(self.options.reuse_offcuts && offcut.is_reusable(self.options.min_reusable_offcut)))
+            .map(|offcut| offcut.length)
+            .sum()
+    }
 }
 
-impl Plank {
-    pub const MAX_LENGTH: usize = 10000;
+/// Whether the planks are long enough to cover the rows, a plank covering its length
+/// and the gap after it on as many rows as it is wide.
+fn may_cover(the_plank_heap: &PlankHeap, rows: &[RowGoal], options: &CalepinageOptions) -> bool {
+    let needed: usize = rows.iter().map(|goal| goal.length).sum();
+    let coverable: usize = the_plank_heap
+        .counts
+        .iter()
+        .map(|(plank, count)| (plank.length + options.end_gap) * plank.width * count)
+        .sum();
+    coverable >= needed
+}
 
-    pub fn new(length: usize) -> Result<Self, String> {
-        if length > Self::MAX_LENGTH {
-            Err(format!("max length of plank is {}", Self::MAX_LENGTH))
-        } else {
-            Ok(Plank { length })
+/// The first `limit` lines covering the first of the given rows, widest planks first. Soft
+/// lot constraints are left to the goal, every line being listed.
+fn row_candidates(
+    the_plank_heap: &PlankHeap,
+    row: usize,
+    rows: &[RowGoal],
+    previous_lines: &PreviousLines,
+    options: &CalepinageOptions,
+    limit: usize,
+) -> Vec<(RowGoal, CalepineStep)> {
+    let is_soft = options.lots.is_some_and(|lots| !lots.hard);
+    let mut candidates = vec![];
+    for (goal, lot, heap, others) in line_heaps(the_plank_heap, rows, previous_lines, options) {
+        if candidates.len() >= limit {
+            break;
         }
+        if is_soft && lot.is_some() {
+            continue;
+        }
+        let steps = candidate_lines(&heap, row, &goal, previous_lines, options, limit - candidates.len());
+        candidates.extend(steps.into_iter().map(|step| (goal.clone(), with_remaining(step, &others))));
     }
+    candidates
 }
 
-#[derive(Default, Debug, PartialEq, Clone)]
-pub struct PlankHeap {
-    planks: Vec<Plank>,
-    total_length: usize,
+/// Selects the planks of the line covering the first of the given rows, a line being made
+/// of planks of the same width. Widest planks are tried first, the error of the widest
+/// being returned when no width fits.
+fn select_line(
+    the_plank_heap: &PlankHeap,
+    row: usize,
+    rows: &[RowGoal],
+    previous_lines: &PreviousLines,
+    options: &CalepinageOptions,
+) -> Result<(RowGoal, CalepineStep), CalepinageError> {
+    select_line_traced(the_plank_heap, row, rows, previous_lines, options, None)
 }
 
-impl PlankHeap {
-    pub fn add(self, count: usize, length: usize) -> Self {
-        let planks_to_be_added: Vec<Plank> =
-            (0..count).map(|_| Plank::new(length).unwrap()).collect();
-        let mut planks = self.planks.clone();
-        planks.extend_from_slice(&planks_to_be_added);
-        PlankHeap {
-            planks,
-            total_length: self.total_length + count * length,
+/// Selects the line like `select_line`, recording the states of the search in the trace.
+fn select_line_traced(
+    the_plank_heap: &PlankHeap,
+    row: usize,
+    rows: &[RowGoal],
+    previous_lines: &PreviousLines,
+    options: &CalepinageOptions,
+    mut trace: Option<&mut Vec<SolverSnapshot>>,
+) -> Result<(RowGoal, CalepineStep), CalepinageError> {
+    let mut first_error = None;
+    for (goal, _, heap, others) in line_heaps(the_plank_heap, rows, previous_lines, options) {
+        match select_planks_for_line(&heap, row, &goal, previous_lines, options, trace.as_deref_mut()) {
+            Ok(step) => return Ok((goal, with_remaining(step, &others))),
+            Err(error) => {
+                first_error.get_or_insert(error);
+            }
         }
     }
+    Err(first_error.unwrap_or(CalepinageError::NotEnoughPlanks))
+}
 
-    pub fn new() -> Self {
-        PlankHeap {
-            planks: vec![],
-            total_length: 0,
+/// The planks a line may be made of, widest first then by lot as tried under the lot
+/// constraint, with the part of the deck the line covers and the planks left apart. The
+/// lot is none when lots may be mixed.
+fn line_heaps(
+    the_plank_heap: &PlankHeap,
+    rows: &[RowGoal],
+    previous_lines: &PreviousLines,
+    options: &CalepinageOptions,
+) -> Vec<(RowGoal, Option<LotId>, PlankHeap, PlankHeap)> {
+    let mut heaps = vec![];
+    for width in options.row_widths.order(the_plank_heap) {
+        let goal = match line_goal(rows, width) {
+            Some(goal) => goal,
+            None => continue,
+        };
+        let (same_width, others) = the_plank_heap.split_by_width(width);
+        let last_row = rows.iter().map(|goal| goal.row).max().unwrap_or(0);
+        let (same_width, too_far_from_wall) = same_width.split_by_rows(goal.row, last_row, options);
+        let others = others.chain(too_far_from_wall);
+        let lots = match &options.lots {
+            Some(constraint) => constraint.lots(&same_width.lots(), previous_lines.lot),
+            None => vec![None],
+        };
+        for lot in lots {
+            match lot {
+                None => heaps.push((goal.clone(), None, same_width.clone(), others.clone())),
+                Some(lot) => {
+                    let (same_lot, other_lots) = same_width.split_by_lot(lot);
+                    heaps.push((goal.clone(), Some(lot), same_lot, other_lots.chain(others.clone())));
+                }
+            }
         }
     }
+    heaps
+}
 
-    pub fn from_planks(planks: Vec<Plank>) -> Self {
-        planks
-            .iter()
-            .fold(PlankHeap::new(), |heap, plank| heap.add(1, plank.length))
+/// Part of the deck covered by a line as wide as `width` rows, from the first of the rows,
+/// if these rows aren't split by cutouts.
+pub(crate) fn line_goal(rows: &[RowGoal], width: usize) -> Option<RowGoal> {
+    let covered = rows.get(..width)?;
+    if covered.iter().enumerate().any(|(index, goal)| goal.row != covered[0].row + index) {
+        return None;
     }
+    let start = covered.iter().map(|goal| goal.start).min().unwrap_or(0);
+    let end = covered.iter().map(|goal| goal.start + goal.length).max().unwrap_or(0);
+    Some(RowGoal {
+        row: covered[0].row,
+        start,
+        length: end - start,
+    })
+}
 
-    fn to_string(&self) -> String {
-        self.planks.iter().map(|p| p.length.to_string()).collect::<Vec<String>>().join(", ")
+fn with_remaining(step: CalepineStep, others: &PlankHeap) -> CalepineStep {
+    CalepineStep {
+        remaining: step.remaining.clone().chain(others.clone()),
+        ..step
     }
 }
 
-#[macro_export]
-macro_rules! plank_line {
-    ( $($head: expr), *) => {{  // {{ pcq Bloc d'instructions
-        let line = Line::default();
-        $(
-          let line = line.with_plank($head);
-        )*
-        line
-      }};
+/// What a line must take into account of the lines laid before it.
+#[derive(Debug, Default)]
+pub(crate) struct PreviousLines {
+    /// Junctions of the lines laid on the `stagger_depth` rows before the line.
+    pub(crate) junctions: Vec<Junction>,
+    /// Lot of the last line laid on the row before the line.
+    lot: Option<LotId>,
+    /// Where planks kept apart from the planks of their grade lie on the row before the
+    /// line, from start to end along the deck length.
+    kept_apart: Vec<(Grade, usize, usize)>,
 }
 
-#[derive(Debug, PartialEq, Clone, Default)]
-pub struct Line(pub Vec<Plank>);
-
-impl Line {
-    pub fn with_plank(self, new_plank_to_add: Plank) -> Self {
-        let Line(old_planks) = self;
-        let mut planks = old_planks;
-        planks.push(new_plank_to_add);
-        Line(planks)
-    }
+pub(crate) fn previous_lines(
+    calepinage: &Calepinage,
+    lines_last_row: &[usize],
+    row: usize,
+    options: &CalepinageOptions,
+) -> PreviousLines {
+    let junctions = calepinage
+        .lines
+        .iter()
+        .zip(lines_last_row)
+        .filter(|(_, &last_row)| last_row < row && last_row + options.stagger_depth >= row)
+        .flat_map(|(line, _)| line.compute_junction())
+        .collect();
+    let lot = calepinage
+        .lines
+        .iter()
+        .zip(lines_last_row)
+        .rev()
+        .filter(|(_, &last_row)| last_row + 1 == row)
+        .find_map(|(line, _)| line.lot());
+    let kept_apart = calepinage
+        .lines
+        .iter()
+        .zip(lines_last_row)
+        .filter(|(_, &last_row)| last_row + 1 == row)
+        .flat_map(|(line, _)| line.planks.iter().zip(line.plank_spans()))
+        .filter(|(plank, _)| options.are_kept_apart(plank.grade))
+        .filter_map(|(plank, (start, end))| plank.grade.map(|grade| (grade, start, end)))
+        .collect();
+    PreviousLines { junctions, lot, kept_apart }
+}
 
-    pub fn compute_junction(&self) -> Vec<Junction> {
-        if self.0.len() > 1 {
-            self.0
+/// Figures the junctions of the line laid from `row` draw with the junctions of the lines
+/// ending on the rows below it, each with the junction of the line drawing it.
+pub(crate) fn joint_figures(
+    calepinage: &Calepinage,
+    lines_last_row: &[usize],
+    line: &Line,
+    row: usize,
+    figures: &JointFigures,
+) -> Vec<(JointFigure, Junction)> {
+    let rows_below: Vec<Vec<usize>> = (1..figures.stair_rows.max(3))
+        .map(|rows| {
+            calepinage
+                .lines
                 .iter()
-                .scan(0, |acc, plank| {
-                    *acc = *acc + plank.length;
-                    Some(*acc)
-                })
-                .map(|j| Junction(j))
-                .take(self.0.len() - 1)
+                .zip(lines_last_row)
+                .filter(|(_, &last_row)| last_row + rows == row)
+                .flat_map(|(line, _)| line.compute_junction())
+                .map(|junction| junction.0)
                 .collect()
-        } else {
-            Vec::<Junction>::new()
+        })
+        .collect();
+    let mut found = vec![];
+    for junction in line.compute_junction() {
+        if figures.is_stair_step(junction.0, &rows_below) {
+            found.push((JointFigure::StairStep, junction.clone()));
+        }
+        if rows_below[1].iter().any(|&below| below.abs_diff(junction.0) <= figures.tolerance) {
+            found.push((JointFigure::H, junction));
         }
     }
+    found
+}
 
-    fn to_string(&self) -> String {
-        format!("[{}]", self.0.iter().map(|p| p.length.to_string()).collect::<Vec<String>>().join(", "))
+/// Adds the line selected by a step to the calepinage, returning the heap left for the
+/// next lines and the length wasted by the line.
+fn lay_line(
+    calepinage: Calepinage,
+    goal: &RowGoal,
+    step: CalepineStep,
+    options: &CalepinageOptions,
+) -> (Calepinage, PlankHeap, usize) {
+    let line = line_for(calepinage.lines.len(), goal, &step, options);
+    add_line(calepinage, line, step, options)
+}
+
+/// Adds the line of the step to the calepinage, like `lay_line`, the line being laid out
+/// already.
+fn add_line(calepinage: Calepinage, line: Line, step: CalepineStep, options: &CalepinageOptions) -> (Calepinage, PlankHeap, usize) {
+    let CalepineStep {
+        remaining: mut the_plank_heap,
+        cuts,
+        ..
+    } = step;
+    let mut calepinage = calepinage.with_line(line);
+    let mut waste = 0;
+    for cut in cuts {
+        let offcut = cut.offcut_with_kerf(options.kerf);
+        if options.reuse_offcuts && offcut.is_reusable(options.min_reusable_offcut) {
+            the_plank_heap.push_planks(1, cut.offcut_plank(options.kerf));
+        } else {
+            waste += offcut.length;
+        }
+        calepinage = calepinage.with_cut(cut);
     }
+    (calepinage, sorted_by_decreasing_length(the_plank_heap), waste)
 }
 
-/// A Junction is a coordinate in a 1 dimension plan corresponding to two plank edges
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-pub struct Junction(usize);
+/// Line of the planks selected by a step, as laid on the given line of the calepinage.
+fn line_for(row: usize, goal: &RowGoal, step: &CalepineStep, options: &CalepinageOptions) -> Line {
+    let mut planks = step.selected.clone().into_planks();
+    if options.is_laid_from_end(row) {
+        planks.reverse();
+    }
+    Line::new(planks).starting_at(goal.start).with_gap(options.end_gap)
+}
 
-#[test]
-fn empty_line_should_have_no_junction() {
-    assert_eq!(Vec::<Junction>::new(), plank_line!().compute_junction());
+/// Sorts the planks longest first, then widest first. Planks of equal length and width
+/// being identical, the calepinage only depends on the heap content and never on the order
+/// the planks were added in.
+fn sorted_by_decreasing_length(plank_heap: PlankHeap) -> PlankHeap {
+    let mut the_plank_heap = plank_heap;
+    let decreasing_length = |a: &Plank, b: &Plank| {
+        b.length
+            .cmp(&a.length)
+            .then(b.width.cmp(&a.width))
+            .then(b.thickness.cmp(&a.thickness))
+            .then(b.lot.cmp(&a.lot))
+            .then(b.grade.cmp(&a.grade))
+            .then(b.defects.cmp(&a.defects))
+            .then(a.price.cmp(&b.price))
+            .then(b.min_length.cmp(&a.min_length))
+            .then(a.label.cmp(&b.label))
+    };
+    // identical planks being counted together, sorting the counts keeps them together
+    the_plank_heap.counts.sort_by(|(a, _), (b, _)| decreasing_length(a, b));
+    PlankHeap::of_counts(the_plank_heap.counts)
 }
 
 #[test]
-fn single_plank_line_should_have_no_junction() {
-    assert_eq!(
-        Vec::<Junction>::new(),
-        plank_line!(Plank::new(1).unwrap()).compute_junction()
-    );
+fn sorted_heap_should_not_depend_on_insertion_order() {
+    let planks = vec![
+        Plank::new(3).unwrap(),
+        Plank::new(5).unwrap(),
+        Plank { length: 3, width: 2, ..Default::default() },
+        Plank { length: 5, width: 2, ..Default::default() },
+    ];
+    let mut reversed = planks.clone();
+    reversed.reverse();
+
+    let sorted = sorted_by_decreasing_length(PlankHeap::from_planks(planks).unwrap());
+
+    assert_eq!(sorted, sorted_by_decreasing_length(PlankHeap::from_planks(reversed).unwrap()));
+    let lengths_and_widths: Vec<(usize, usize)> = sorted.planks().map(|plank| (plank.length, plank.width)).collect();
+    assert_eq!(vec![(5, 2), (5, 1), (3, 2), (3, 1)], lengths_and_widths);
 }
 
 #[test]
-fn two_planks_line_should_have_one_junction() {
+fn plank_heap_should_count_identical_planks() {
+    let plank_heap = PlankHeap::new().add(2, 3).add(100_000, 5).add(1, 3);
+
+    assert_eq!(100_003, plank_heap.len());
+    assert_eq!(3, plank_heap.counts().len());
+    let sorted = sorted_by_decreasing_length(plank_heap.clone());
     assert_eq!(
-        vec![Junction(3)],
-        plank_line!(Plank::new(3).unwrap(), Plank::new(1).unwrap()).compute_junction()
+        vec![(Plank::new(5).unwrap(), 100_000), (Plank::new(3).unwrap(), 3)],
+        sorted.counts().to_vec()
     );
+    assert_eq!(Ok(plank_heap.clone()), PlankHeap::from_counts(plank_heap.counts().to_vec()));
 }
 
-#[test]
-fn should_build_line() {
-    let actual = plank_line![]
-        .with_plank(Plank::new(2).unwrap())
-        .with_plank(Plank::new(1).unwrap());
+fn select_planks_for_line(
+    the_plank_heap: &PlankHeap,
+    row: usize,
+    goal: &RowGoal,
+    previous_lines: &PreviousLines,
+    options: &CalepinageOptions,
+    trace: Option<&mut Vec<SolverSnapshot>>,
+) -> Result<CalepineStep, CalepinageError> {
+    if options.is_out_of_budget() {
+        return Err(CalepinageError::OutOfBudget);
+    }
+    if covered_length(the_plank_heap.total_length, the_plank_heap.len(), options.end_gap) < goal.length {
+        return Err(CalepinageError::NotEnoughPlanks);
+    }
 
-    let expected = Line(vec![Plank::new(2).unwrap(), Plank::new(1).unwrap()]);
-    assert_eq!(expected, actual);
+    let mut search = LineSearch::new(the_plank_heap, row, goal, previous_lines, options);
+    if options.strategy == Strategy::FewestPieces {
+        search.cutting = options.allow_cutting;
+        search.trimming = search.has_trimmable_planks();
+        search.fewest = Some(None);
+        search.explore_line(the_plank_heap.total_length);
+        if search.out_of_budget {
+            return Err(CalepinageError::OutOfBudget);
+        }
+        if let Some(Some((_, selected, cut))) = search.fewest.take() {
+            return Ok(search.step(&selected, None, cut));
+        }
+        search = LineSearch::new(the_plank_heap, row, goal, previous_lines, options);
+    }
+    search.trace = trace.as_ref().map(|_| vec![]);
+    // without cutting, the search of whole planks also finds the dead end reported on failure
+    let whole_planks_fill = !options.allow_cutting || search.can_be_filled();
+    let found = (whole_planks_fill && search.explore_line(the_plank_heap.total_length)) || {
+        search.cutting = options.allow_cutting;
+        search.trimming = search.has_trimmable_planks();
+        (search.cutting || search.trimming) && search.explore_line(the_plank_heap.total_length)
+    };
+    if search.out_of_budget {
+        return Err(CalepinageError::OutOfBudget);
+    }
+    let step = if found {
+        search.step(&search.selected, None, search.cut)
+    } else {
+        let (selected, stash) = search.best.take().unwrap_or_default();
+        search.step(&selected, stash, None)
+    };
+    if let (Some(trace), Some(snapshots)) = (trace, search.trace.take()) {
+        trace.extend(snapshots);
+    }
+
+    assert_length_goal_fulfilled(step, goal, options.end_gap)
 }
 
-#[test]
-fn should_use_macro() {
-    let actual = plank_line![Plank::new(2).unwrap()];
+/// Lists the first `limit` planks sequences that fill the line, longest planks first.
+fn candidate_lines(
+    the_plank_heap: &PlankHeap,
+    row: usize,
+    goal: &RowGoal,
+    previous_lines: &PreviousLines,
+    options: &CalepinageOptions,
+    limit: usize,
+) -> Vec<CalepineStep> {
+    if covered_length(the_plank_heap.total_length, the_plank_heap.len(), options.end_gap) < goal.length {
+        return vec![];
+    }
 
-    let expected = Line(vec![Plank::new(2).unwrap()]);
-    assert_eq!(expected, actual);
+    let mut search = LineSearch::new(the_plank_heap, row, goal, previous_lines, options);
+    search.cutting = options.allow_cutting;
+    search.trimming = search.has_trimmable_planks();
+    search.candidates = Some(vec![]);
+    search.max_candidates = limit;
+    search.explore_line(the_plank_heap.total_length);
+    search.candidates.unwrap_or_default()
 }
 
-#[test]
-fn should_use_macro_with_2_planks() {
-    let actual = plank_line![Plank::new(2).unwrap(), Plank::new(1).unwrap()];
+/// Pieces and cuts of a line, with the planks it selects and the plank cut to finish it.
+type FewestLine = ((usize, usize), Vec<usize>, Option<usize>);
 
-    let expected = Line(vec![Plank::new(2).unwrap(), Plank::new(1).unwrap()]);
-    assert_eq!(expected, actual);
+/// Depth-first search of the planks sequence filling a line.
+///
+/// Identical planks, labels aside, are grouped so that the search never tries the same
+/// sequence twice; groups are ordered by the strategy then the pattern, longest planks first by default. When a
+/// plank would create a junction too close to the previous lines ones, the search
+/// tries another plank at this position and backtracks if the line can't be completed.
+///
+/// When cutting, a line that can't be completed with whole planks is finished with a
+/// piece of the shortest plank long enough, so that the offcut is as small as possible.
+/// Planks with a min length finish the line trimmed, even without cutting, when no whole
+/// planks combination fills it.
+/// The search itself picks the planks: when cutting, a subset sum over the planks lengths
+/// is only checked first, the search going straight to cutting when it shows that no
+/// whole planks combination fills the line.
+///
+/// With a running bond, the search starts after the piece cut to begin the line.
+///
+/// When collecting candidates, the search records every line found and goes on, until it
+/// has found as many as it may list. When
+/// looking for the fewest pieces, it keeps the line of fewest pieces found so far, then of
+/// fewest cuts, and only goes on with sequences that may still lay fewer.
+struct LineSearch<'a> {
+    // planks of every group, the first one standing for the group
+    groups: Vec<(Plank, PlankHeap)>,
+    available: Vec<usize>,
+    line_start: usize,
+    line_length: usize,
+    from_end: bool,
+    previous_lines: &'a PreviousLines,
+    options: &'a CalepinageOptions,
+    // plank group and length of the piece the line must start with
+    first_piece: Option<(usize, usize)>,
+    selected: Vec<usize>,
+    cutting: bool,
+    trimming: bool,
+    cut: Option<usize>,
+    candidates: Option<Vec<CalepineStep>>,
+    max_candidates: usize,
+    // pieces and cuts of the line of fewest pieces found, with its planks and cut plank
+    fewest: Option<Option<FewestLine>>,
+    // deck row of the line and every state of the search, when traced
+    goal_row: usize,
+    trace: Option<Vec<SolverSnapshot>>,
+    // longest dead end met so far with the plank it had to stash, used to report failures
+    best: Option<(Vec<usize>, Option<usize>)>,
+    best_length: usize,
+    // states explored, the budget of the options being checked every few of them
+    explored: usize,
+    out_of_budget: bool,
 }
 
-#[derive(PartialEq, Clone, Default)]
-pub struct Calepinage(pub Vec<Line>);
+/// States a line search explores between two checks of the budget of the options.
+const BUDGET_CHECK_INTERVAL: usize = 1024;
 
-impl Calepinage {
-    pub fn with_line(self, new_line_to_add: Line) -> Self {
-        let Calepinage(mut lines) = self;
+impl<'a> LineSearch<'a> {
+    fn new(
+        the_plank_heap: &PlankHeap,
+        row: usize,
+        goal: &RowGoal,
+        previous_lines: &'a PreviousLines,
+        options: &'a CalepinageOptions,
+    ) -> Self {
+        let mut groups: Vec<(Plank, PlankHeap)> = vec![];
+        for (plank, count) in the_plank_heap.counts.iter() {
+            match groups.last_mut() {
+                Some((last, members)) if last.is_like(plank) => members.push_planks(*count, plank.clone()),
+                _ => groups.push((plank.clone(), PlankHeap::of_counts(vec![(plank.clone(), *count)]))),
+            }
+        }
+        options.strategy.order(row, &mut groups);
+        if let Pattern::Random { seed } = options.pattern {
+            SplitMix64::for_row(seed, row).shuffle(&mut groups);
+        }
+        let mut available: Vec<usize> = groups.iter().map(|(_, members)| members.len()).collect();
+        let first_piece = groups.first().and_then(|(plank, _)| {
+            let shift = options.pattern.shift(row, plank.length)?;
+            Some((0, (plank.length - shift).min(goal.length)))
+        });
+        if let Some((index, _)) = first_piece {
+            available[index] -= 1;
+        }
+        LineSearch {
+            groups,
+            available,
+            line_start: goal.start,
+            line_length: goal.length,
+            from_end: options.is_laid_from_end(row),
+            previous_lines,
+            options,
+            first_piece,
+            selected: vec![],
+            cutting: false,
+            trimming: false,
+            cut: None,
+            candidates: None,
+            max_candidates: usize::MAX,
+            fewest: None,
+            goal_row: goal.row,
+            trace: None,
+            best: None,
+            best_length: 0,
+            explored: 0,
+            out_of_budget: false,
+        }
+    }
 
-        lines.push(new_line_to_add);
-        Calepinage(lines)
+    fn explore_line(&mut self, available_length: usize) -> bool {
+        match self.first_piece {
+            None => self.explore(0, available_length),
+            Some((_, piece_length)) if piece_length == self.line_length => self.found(),
+            Some((_, piece_length)) if self.is_too_short_end_piece(piece_length) => false,
+            Some((_, piece_length)) if self.is_misplaced_junction(piece_length) => false,
+            Some((index, piece_length))
+                if self.has_defect_at_junction(&self.cut_plank(index, piece_length).piece(), 0, piece_length) =>
+            {
+                false
+            }
+            Some((index, piece_length)) => {
+                self.explore(piece_length, available_length - self.groups[index].0.length)
+            }
+        }
     }
 
-    fn to_string(&self) -> String {
-        format!("Calepinage({})", self.0.iter().map(|line| line.to_string()).collect::<Vec<String>>().join(", "))
+    /// Whether some whole planks combination fills the line exactly, junctions aside, by a
+    /// bounded subset sum over the lengths of the available planks and their gap. Every
+    /// length reached keeps the fewest planks of the group it took, so that the sum goes
+    /// over the line once per group whatever the count of planks.
+    fn can_be_filled(&self) -> bool {
+        let start = self.first_piece.map_or(0, |(_, piece_length)| self.next_plank_start(piece_length));
+        let gap = self.options.end_gap;
+        let target = match self.line_length.checked_sub(start) {
+            Some(0) => return true,
+            Some(missing_length) => missing_length + gap,
+            None => return self.first_piece.is_some_and(|(_, piece_length)| piece_length == self.line_length),
+        };
+        let mut reachable = vec![false; target + 1];
+        reachable[0] = true;
+        let mut taken = vec![0; target + 1];
+        for (index, (plank, _)) in self.groups.iter().enumerate() {
+            let step = plank.length + gap;
+            if step == 0 {
+                continue;
+            }
+            taken.iter_mut().for_each(|taken| *taken = 0);
+            for sum in step..=target {
+                if !reachable[sum] && reachable[sum - step] && taken[sum - step] < self.available[index] {
+                    reachable[sum] = true;
+                    taken[sum] = taken[sum - step] + 1;
+                }
+            }
+            if reachable[target] {
+                return true;
+            }
+        }
+        reachable[target]
     }
-}
 
-impl std::fmt::Display for Calepinage {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+    fn explore(&mut self, length: usize, available_length: usize) -> bool {
+        if self.is_out_of_budget() {
+            return false;
+        }
+        let mut stash = None;
+        for index in 0..self.groups.len() {
+            if self.available[index] == 0 {
+                continue;
+            }
+            let plank_length = self.groups[index].0.length;
+            let new_length = self.next_plank_start(length) + plank_length;
+            if new_length > self.line_length {
+                self.record(stash, Some((index, Rejection::TooLong)));
+                continue;
+            }
+            if (length == 0 || new_length == self.line_length) && self.is_too_short_end_piece(plank_length) {
+                self.record(stash, Some((index, Rejection::TooShortEndPiece)));
+                continue;
+            }
+            if new_length < self.line_length && self.is_misplaced_junction(new_length) {
+                stash = stash.or(Some(index));
+                self.record(stash, Some((index, Rejection::MisplacedJunction)));
+                continue;
+            }
+            if self.is_misgraded(index, length) {
+                self.record(stash, Some((index, Rejection::Misgraded)));
+                continue;
+            }
+            if self.has_defect_at_junction(&self.groups[index].0, length, new_length) {
+                self.record(stash, Some((index, Rejection::DefectAtJunction)));
+                continue;
+            }
+            if self.lays_no_fewer(self.pieces() + 1, 0) {
+                continue;
+            }
+
+            self.available[index] -= 1;
+            self.selected.push(index);
+            self.record(stash, None);
+            let remaining_length = available_length - plank_length;
+            let found = if new_length == self.line_length {
+                self.found()
+            } else {
+                remaining_length + self.options.end_gap >= self.line_length - new_length
+                    && self.explore(new_length, remaining_length)
+            };
+            if found {
+                return true;
+            }
+            self.selected.pop();
+            self.available[index] += 1;
+        }
+
+        if (self.trimming && self.cut_to_finish(length, true)) || (self.cutting && self.cut_to_finish(length, false)) {
+            return true;
+        }
+        if self.best.is_none() || length > self.best_length {
+            self.best = Some((self.selected.clone(), stash));
+            self.best_length = length;
+        }
+        false
     }
-}
 
-impl std::fmt::Debug for Calepinage {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+    /// Finishes the line with a piece of the shortest plank long enough, only taking planks
+    /// that may be trimmed to the piece when `trimming`.
+    fn cut_to_finish(&mut self, length: usize, trimming: bool) -> bool {
+        let missing_length = match self.line_length.checked_sub(self.next_plank_start(length)) {
+            Some(missing_length) if missing_length > 0 => missing_length,
+            _ => return false,
+        };
+        if self.is_too_short_end_piece(missing_length) || self.lays_no_fewer(self.pieces() + 1, 1) {
+            return false;
+        }
+        let shortest_long_enough = (0..self.groups.len())
+            .filter(|&index| self.available[index] > 0 && self.groups[index].0.length > missing_length)
+            .filter(|&index| !trimming || self.groups[index].0.can_be_trimmed_to(missing_length))
+            .filter(|&index| !self.is_misgraded_piece(index, length, missing_length))
+            .filter(|&index| {
+                let piece = self.cut_plank(index, missing_length).piece();
+                !self.has_defect_at_junction(&piece, length, self.line_length)
+            })
+            .min_by_key(|&index| self.groups[index].0.length);
+        match shortest_long_enough {
+            Some(index) => {
+                self.available[index] -= 1;
+                self.cut = Some(index);
+                if self.found() {
+                    return true;
+                }
+                self.cut = None;
+                self.available[index] += 1;
+                false
+            }
+            None => false,
+        }
     }
-}
 
-#[test]
-fn with_line_should_append_lines_in_order() {
-    let calepinage = Calepinage::default()
-        .with_line(plank_line![Plank::new(1).unwrap()])
-        .with_line(plank_line![Plank::new(2).unwrap()]);
+    /// Whether the options deadline passed or their search was cancelled, the search
+    /// giving up every state once it has.
+    fn is_out_of_budget(&mut self) -> bool {
+        self.explored += 1;
+        if !self.out_of_budget && self.explored.is_multiple_of(BUDGET_CHECK_INTERVAL) {
+            self.out_of_budget = self.options.is_out_of_budget();
+        }
+        self.out_of_budget
+    }
 
-    let Calepinage(lines) = calepinage;
-    assert_eq!(&lines[0], &plank_line![Plank::new(1).unwrap()]);
-    assert_eq!(&lines[1], &plank_line![Plank::new(2).unwrap()]);
-}
+    fn has_trimmable_planks(&self) -> bool {
+        self.groups.iter().any(|(plank, _)| plank.min_length.is_some())
+    }
 
-#[derive(Default, Debug, PartialEq)]
-pub struct CalepineStep {
-    remaining: PlankHeap,
-    selected: PlankHeap,
-    stash: Option<Plank>,
-}
+    fn found(&mut self) -> bool {
+        if let Some(fewest) = self.fewest.as_mut() {
+            let cuts = usize::from(self.cut.is_some());
+            let pieces = (self.selected.len() + usize::from(self.first_piece.is_some()) + cuts, cuts);
+            if fewest.as_ref().is_none_or(|(fewest_pieces, _, _)| pieces < *fewest_pieces) {
+                *fewest = Some((pieces, self.selected.clone(), self.cut));
+            }
+            return false;
+        }
+        match self.candidates.take() {
+            Some(mut candidates) => {
+                candidates.push(self.step(&self.selected, None, self.cut));
+                let is_full = candidates.len() >= self.max_candidates;
+                self.candidates = Some(candidates);
+                is_full
+            }
+            None => true,
+        }
+    }
 
-impl CalepineStep {
-    fn to_string(&self) -> String {
-        format!("remaining = [{}], selected = [{}], stash = {:?}", self.remaining.to_string(), self.selected.to_string(), self.stash )
+    /// Pieces laid so far, the piece the line starts with included.
+    fn pieces(&self) -> usize {
+        self.selected.len() + usize::from(self.first_piece.is_some())
     }
-}
 
-#[derive(Debug, PartialEq)]
-pub enum CalepinageError {
-    NotEnoughPlanks,
-    OnlyUnusablePlanksRemaining(String),
-}
+    /// Whether a line of at least `pieces` pieces, `cuts` of them cut, can't lay fewer
+    /// pieces than the fewest found.
+    fn lays_no_fewer(&self, pieces: usize, cuts: usize) -> bool {
+        matches!(&self.fewest, Some(Some((fewest_pieces, _, _))) if (pieces, cuts) >= *fewest_pieces)
+    }
 
-pub fn calepine(plank_heap: PlankHeap, deck: Deck) -> Result<Calepinage, CalepinageError> {
-    let mut the_plank_heap: PlankHeap = PlankHeap::from_planks(plank_heap.planks);
-    let decreasing_length = |a: &Plank, b: &Plank| b.length.cmp(&a.length);
-    the_plank_heap.planks.sort_by(decreasing_length);
-
-    let mut calepinage = Calepinage::default();
-    for _ in 0..deck.width {
-        let previous_line_junctions = calepinage.0.last().map_or_else(|| HashSet::new(), |line| line.compute_junction().into_iter().collect());
-        let CalepineStep {
-            selected: result,
-            remaining: next_remaining,
-            stash: _,
-        } = select_planks_for_line(&mut the_plank_heap, deck.length, previous_line_junctions)?;
-        the_plank_heap = next_remaining;
-        calepinage = calepinage.with_line(Line(result.planks));
+    /// Where the next plank starts once the line is `length` long, leaving the gap after
+    /// the last plank if any.
+    fn next_plank_start(&self, length: usize) -> usize {
+        if length == 0 {
+            0
+        } else {
+            length + self.options.end_gap
+        }
     }
 
-    Ok(calepinage)
-}
+    fn is_too_short_end_piece(&self, piece_length: usize) -> bool {
+        piece_length < self.options.min_end_piece && piece_length < self.line_length
+    }
 
-// 1 : [10 10 10 2 2 2] => [10 2] [10 10 2 2]
-// 2 : [10 10 2 2] => [2 10] [10 2]
-// 3 : [10 2] => [10 2]
+    /// Whether a junction once the line is `length` long is too close to the previous lines
+    /// ones or off the joists.
+    fn is_misplaced_junction(&self, length: usize) -> bool {
+        let junction = if self.from_end {
+            Junction((self.line_start + self.line_length - length).saturating_sub(self.options.end_gap))
+        } else {
+            Junction(self.line_start + length)
+        };
+        let is_off_joist = self.options.substructure.is_some_and(|substructure| {
+            !substructure.is_on_joist(junction.0 + self.options.end_gap / 2, self.options.joist_tolerance)
+        });
+        is_off_joist
+            || self
+                .previous_lines
+                .junctions
+                .iter()
+                .any(|previous| previous.distance(&junction) < self.options.min_junction_offset)
+    }
 
+    /// Whether the plank laid from `length` to `new_length` has a defect too close to the
+    /// planks around it.
+    fn has_defect_at_junction(&self, plank: &Plank, length: usize, new_length: usize) -> bool {
+        let distance = self.options.min_defect_distance;
+        (length > 0 && plank.has_defect_near(true, distance))
+            || (new_length < self.line_length && plank.has_defect_near(false, distance))
+    }
 
-fn select_planks_for_line(
-    the_plank_heap: &mut PlankHeap,
-    deck_length: usize,
-    previous_line_junctions: HashSet<Junction>,
-) -> Result<CalepineStep, CalepinageError> {
-    let select_planks_fitting_length_goal = |step: CalepineStep, plank: &Plank| -> CalepineStep {
-        let new_length = step.selected.total_length + plank.length;
-        let junction = Junction(new_length);
-
-        if new_length > deck_length {
-            let remaining = step.remaining.add(1, plank.length);
-            CalepineStep { remaining, ..step }
-        } else if previous_line_junctions.contains(&junction) {
-            let stash = Some(plank.clone());
-            CalepineStep { stash, ..step }
-        } else {
-            let selected = step.selected.add(1, plank.length);
-            CalepineStep { selected, ..step }
+    fn cut_plank(&self, index: usize, at: usize) -> Cut {
+        Cut::keeping_defects_in_offcut(&self.groups[index].0, at)
+    }
+
+    /// Whether a plank of the group laid once the line is `length` long would touch a plank
+    /// of its grade when they must be kept apart.
+    fn is_misgraded(&self, index: usize, length: usize) -> bool {
+        self.is_misgraded_piece(index, length, self.groups[index].0.length)
+    }
+
+    fn is_misgraded_piece(&self, index: usize, length: usize, piece_length: usize) -> bool {
+        let grade = self.groups[index].0.grade;
+        if !self.options.are_kept_apart(grade) {
+            return false;
         }
-    };
+        let previous_plank = match (self.selected.last(), self.first_piece) {
+            (Some(&previous), _) => Some(previous),
+            (None, Some((previous, _))) if length > 0 => Some(previous),
+            _ => None,
+        };
+        if previous_plank.is_some_and(|previous| self.groups[previous].0.grade == grade) {
+            return true;
+        }
+        let start = self.next_plank_start(length);
+        let (start, end) = if self.from_end {
+            let end = self.line_start + self.line_length - start;
+            (end - piece_length, end)
+        } else {
+            (self.line_start + start, self.line_start + start + piece_length)
+        };
+        self.previous_lines
+            .kept_apart
+            .iter()
+            .any(|&(previous_grade, previous_start, previous_end)| {
+                Some(previous_grade) == grade && previous_start < end && start < previous_end
+            })
+    }
 
-    match the_plank_heap.planks[..] {
-        [Plank{length: 10}, Plank{length: 10}, Plank{length: 2},Plank{length: 2}] =>
-            {
-                let mut step = CalepineStep::default();
-                let new_length = step.selected.total_length + the_plank_heap.planks[0].length;
-                let junction = Junction(new_length);
-
-                let mut remaining = PlankHeap::default();
-
-
-
-                let selected = if previous_line_junctions.contains(&junction) {
-                    let mut selected = PlankHeap::default();
-
-                    remaining = remaining.add(1, the_plank_heap.planks[3].length);
-                    selected = selected.add(1, the_plank_heap.planks[2].length);
-                    remaining = remaining.add(1, the_plank_heap.planks[1].length);
-                    selected = selected.add(1, the_plank_heap.planks[0].length);
-                    selected
-                } else {
-                    let mut selected = PlankHeap::default();
-                    // On doit indiquer si la planche 0 va dans selected ou remaining
-                    selected = selected.add(1, the_plank_heap.planks[0].length);
-                    // On doit indiquer si la planche 1 va dans selected ou remaining
-                    remaining = remaining.add(1, the_plank_heap.planks[1].length);
-                    // On doit indiquer si la planche 2 va dans selected ou remaining
-                    selected = selected.add(1, the_plank_heap.planks[2].length);
-                    // On doit indiquer si la planche 3 va dans selected ou remaining
-                    remaining = remaining.add(1, the_plank_heap.planks[3].length);
-                    selected
-                };
+    /// Records the state of the search when traced, with the plank just rejected.
+    fn record(&mut self, stash: Option<usize>, rejected: Option<(usize, Rejection)>) {
+        if self.trace.is_none() {
+            return;
+        }
+        let snapshot = SolverSnapshot {
+            row: self.goal_row,
+            step: self.step(&self.selected, stash, None),
+            rejected: rejected.map(|(index, rejection)| (self.groups[index].0.clone(), rejection)),
+        };
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(snapshot);
+        }
+    }
 
-                return Ok(CalepineStep { remaining, selected, stash:None });
+    /// The step laying the selected planks, taking the planks of every group in order.
+    fn step(&self, selected: &[usize], stash: Option<usize>, cut: Option<usize>) -> CalepineStep {
+        let mut taken: Vec<usize> = vec![0; self.groups.len()];
+        let mut take = |index: usize| {
+            taken[index] += 1;
+            self.groups[index].1.nth(taken[index] - 1).clone()
+        };
+        let mut cuts = vec![];
+        let mut selected_planks = PlankHeap::new();
+        if let Some((index, piece_length)) = self.first_piece {
+            let cut = Cut::keeping_defects_in_offcut(&take(index), piece_length);
+            selected_planks.push_planks(1, cut.piece());
+            if piece_length < cut.plank.length {
+                cuts.push(cut);
             }
-        _ => {}
+        }
+        for &index in selected {
+            selected_planks.push_planks(1, take(index));
+        }
+        let mut selected = selected_planks;
+        let stash = stash.map(&mut take);
+        if let Some(index) = cut {
+            let at = self.line_length - covered_length(selected.total_length, selected.len() + 1, self.options.end_gap);
+            let cut = Cut::keeping_defects_in_offcut(&take(index), at);
+            selected.push_planks(1, cut.piece());
+            cuts.push(cut);
+        }
+        let remaining = self
+            .groups
+            .iter()
+            .zip(taken)
+            .fold(PlankHeap::new(), |remaining, ((_, members), taken)| remaining.chain(members.skipping(taken)));
+        CalepineStep {
+            remaining,
+            selected,
+            stash,
+            cuts,
+        }
     }
+}
 
+#[test]
+fn line_search_should_know_whether_whole_planks_can_fill_the_line() {
+    let the_plank_heap = PlankHeap::new().add(2, 4).add(1, 3);
+    let options = CalepinageOptions::default();
+    let can_be_filled = |length, options: &CalepinageOptions| {
+        let goal = RowGoal { row: 0, start: 0, length };
+        LineSearch::new(&the_plank_heap, 0, &goal, &PreviousLines::default(), options).can_be_filled()
+    };
+    let with_gap = CalepinageOptions::default().with_end_gap(1);
 
-    let mut step = CalepineStep::default();
-    for plank in the_plank_heap.planks.iter() {
-        step = select_planks_fitting_length_goal(step, plank);
-    }
+    assert!(can_be_filled(7, &options));
+    assert!(can_be_filled(11, &options));
+    assert!(!can_be_filled(9, &options));
+    assert!(can_be_filled(9, &with_gap));
+    assert!(!can_be_filled(7, &with_gap));
+    assert!(!can_be_filled(12, &options));
+}
 
+#[test]
+fn line_search_should_fill_long_lines_of_many_planks_at_once() {
+    let the_plank_heap = PlankHeap::new().add(100_000, 2500).add(1, 1);
+    let options = CalepinageOptions::default();
+    let can_be_filled = |length| {
+        let goal = RowGoal { row: 0, start: 0, length };
+        LineSearch::new(&the_plank_heap, 0, &goal, &PreviousLines::default(), &options).can_be_filled()
+    };
+
+    assert!(can_be_filled(600_000));
+    assert!(can_be_filled(600_001));
+    assert!(!can_be_filled(600_002));
+}
 
-    /*
+/// Small deterministic random generator, so that seeded patterns give the same
+/// calepinage on every platform and release.
+struct SplitMix64(u64);
 
-let step = CalepineStep::default();
-for plank in the_plank_heap.planks.iter() {
+impl SplitMix64 {
+    const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
 
-}*/
+    fn for_row(seed: u64, row: usize) -> Self {
+        SplitMix64(seed.wrapping_add((row as u64).wrapping_mul(Self::GOLDEN_GAMMA)))
+    }
 
-    // 12 12 12
-    // 10 10 10 2 2 2
-    // ->
-    // selected = 10 2,  remaining = 10 10 2 2
-    // 2 10
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(Self::GOLDEN_GAMMA);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
 
-    step = match step.stash {
-        Some(plank) => select_planks_fitting_length_goal(CalepineStep { stash: None, ..step }, &plank),
-        None => step,
-    };
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[test]
+fn split_mix_should_be_deterministic() {
+    let mut items = vec![1, 2, 3, 4, 5, 6];
+    SplitMix64::for_row(42, 0).shuffle(&mut items);
+    let mut same_seed_items = vec![1, 2, 3, 4, 5, 6];
+    SplitMix64::for_row(42, 0).shuffle(&mut same_seed_items);
 
-   assert_length_goal_fulfilled(step, deck_length)
+    assert_eq!(items, same_seed_items);
+    assert_eq!(vec![1, 2, 3, 4, 5, 6], { items.sort(); items });
 }
 
-fn assert_length_goal_fulfilled(
-    step: CalepineStep,
-    deck_length: usize,
-) -> Result<CalepineStep, CalepinageError> {
-    if step.selected.total_length < deck_length {
+fn assert_length_goal_fulfilled(step: CalepineStep, goal: &RowGoal, gap: usize) -> Result<CalepineStep, CalepinageError> {
+    let covered = covered_length(step.selected.total_length, step.selected.len(), gap);
+    if covered < goal.length {
         if step.remaining.total_length == 0 {
             Err(CalepinageError::NotEnoughPlanks)
         } else {
-            Err(CalepinageError::OnlyUnusablePlanksRemaining(step.to_string()))
+            Err(CalepinageError::OnlyUnusablePlanksRemaining {
+                row: goal.row,
+                needed_length: goal.length - covered,
+                remaining_lengths: step.remaining.planks().map(|plank| plank.length).collect(),
+            })
         }
     } else {
         Ok(step)
@@ -368,26 +4754,35 @@ fn assert_length_goal_fulfilled(
 
 pub type CalepineResult = Result<Calepinage, CalepinageError>;
 
-
 #[test]
 fn test_only_unusable_planks_remaining_to_string() {
     let deck = Deck {
         length: 10,
         width: 3,
+        board_width: None,
+        cutouts: vec![],
+        outline: None,
+        posts: vec![],
+        slope: None,
     };
     let plank_heap = PlankHeap::from_planks(
         vec![
-            Plank { length: 8 },
-            Plank { length: 5 },
-            Plank { length: 8 },
-            Plank { length: 5 },
-            Plank { length: 8 },
-            Plank { length: 5 },
+            Plank::new(8).unwrap(),
+            Plank::new(5).unwrap(),
+            Plank::new(8).unwrap(),
+            Plank::new(5).unwrap(),
+            Plank::new(8).unwrap(),
+            Plank::new(5).unwrap(),
         ],
-    );
+    ).unwrap();
     let result = calepine(plank_heap, deck);
-    assert_that!(result).is_equal_to(
-        Err(CalepinageError::OnlyUnusablePlanksRemaining("remaining = [8, 8, 5, 5, 5], selected = [8], stash = None".to_string())))
+    let error = CalepinageError::OnlyUnusablePlanksRemaining {
+        row: 1,
+        needed_length: 2,
+        remaining_lengths: vec![8, 8, 5],
+    };
+    assert_eq!("row 1 still needs 2 but the remaining planks [8, 8, 5] can't be laid", error.to_string());
+    assert_that!(result).is_equal_to(Err(error))
 }
 
 #[test]
@@ -396,31 +4791,75 @@ fn test_step_to_string() {
     let step = CalepineStep {
         remaining: PlankHeap::from_planks(
             vec![
-                Plank { length: 8 },
-                Plank { length: 8 },
-                Plank { length: 5 },
-                Plank { length: 5 },
-                Plank { length: 5 },
-            ]),
+                Plank::new(8).unwrap(),
+                Plank::new(8).unwrap(),
+                Plank::new(5).unwrap(),
+                Plank::new(5).unwrap(),
+                Plank::new(5).unwrap(),
+            ]).unwrap(),
         selected: PlankHeap::from_planks(
-            vec![Plank { length: 8 }]),
+            vec![Plank::new(8).unwrap()]).unwrap(),
         stash: None,
+        cuts: vec![],
     };
     assert_that!(step.to_string()).is_equal_to("remaining = [8, 8, 5, 5, 5], selected = [8], stash = None".to_string());
 }
 
 #[test]
 fn test_line_to_string() {
-    let line = plank_line![Plank { length: 10 }, Plank { length: 2 }];
+    let line = plank_line![10, 2];
     assert_that!(line.to_string()).is_equal_to("[10, 2]".to_string());
+    assert_that!(line.starting_at(3).to_string()).is_equal_to("[10, 2] from 3".to_string());
+}
+
+#[test]
+fn test_plank_to_string() {
+    let plank = Plank {
+        length: 2400,
+        width: 2,
+        lot: Some(LotId(3)),
+        grade: Some(Grade::B),
+        label: Some("B1".to_string()),
+        defects: vec![200, 900],
+        ..Default::default()
+    };
+    assert_that!(plank.to_string()).is_equal_to("B1 2400 (width 2, lot 3, grade B, defects at 200, 900)".to_string());
+    assert_that!(Plank::new(10).unwrap().to_string()).is_equal_to("10".to_string());
+}
+
+#[test]
+fn test_planks_should_sort_by_length_first() {
+    let planks: std::collections::BTreeSet<Plank> =
+        vec![Plank::new(6).unwrap(), Plank::new(4).unwrap().with_width(2).unwrap(), Plank::new(4).unwrap(), Plank::new(6).unwrap()]
+            .into_iter()
+            .collect();
+    assert_that!(planks.into_iter().collect::<Vec<_>>()).is_equal_to(vec![
+        Plank::new(4).unwrap(),
+        Plank::new(4).unwrap().with_width(2).unwrap(),
+        Plank::new(6).unwrap(),
+    ]);
+}
+
+#[test]
+fn test_calepinages_should_deduplicate_in_a_set() {
+    let layout = |lengths: &[usize]| Calepinage::default().with_line(Line::new(lengths.iter().map(|length| Plank::new(*length).unwrap()).collect()));
+    let layouts: std::collections::HashSet<Calepinage> = vec![layout(&[6, 4]), layout(&[4, 6]), layout(&[6, 4])].into_iter().collect();
+    assert_that!(layouts.len()).is_equal_to(2);
+}
+
+#[test]
+fn test_deck_to_string() {
+    let deck = Deck::physical(4000, 1200, 140).unwrap().with_cutout(Cutout { start: 100, length: 50, first_row: 2, rows: 3 });
+    assert_that!(deck.to_string()).is_equal_to("deck 4000 x 1200, boards 140 wide, cutout from 100 to 150 on rows 2 to 4".to_string());
+    assert_that!(Deck::new(6, 4).unwrap().to_string()).is_equal_to("deck 6 x 4 rows".to_string());
 }
 
 #[test]
 fn test_calepine_to_string() {
     let calepinage = Calepinage::default()
-        .with_line(plank_line![Plank { length: 10 }, Plank { length: 2 }])
-        .with_line(plank_line![Plank { length: 2 }, Plank { length: 10}])
-        .with_line(plank_line![Plank { length: 10 }, Plank { length: 2 }]);
+        .with_line(plank_line![10, 2])
+        .with_line(plank_line![2, 10])
+        .with_line(plank_line![10, 2]);
 
     assert_that!(calepinage.to_string()).is_equal_to("Calepinage([10, 2], [2, 10], [10, 2])".to_string());
 }
@@ -428,9 +4867,9 @@ fn test_calepine_to_string() {
 #[test]
 fn test_calepine_display() {
     let calepinage = Calepinage::default()
-        .with_line(plank_line![Plank { length: 10 }, Plank { length: 2 }])
-        .with_line(plank_line![Plank { length: 2 }, Plank { length: 10}])
-        .with_line(plank_line![Plank { length: 10 }, Plank { length: 2 }]);
+        .with_line(plank_line![10, 2])
+        .with_line(plank_line![2, 10])
+        .with_line(plank_line![10, 2]);
 
     assert_that!(format!("{}", calepinage)).is_equal_to("Calepinage([10, 2], [2, 10], [10, 2])".to_string());
 }
@@ -438,9 +4877,9 @@ fn test_calepine_display() {
 #[test]
 fn test_result_calepine_display() {
     let calepinage = Calepinage::default()
-        .with_line(plank_line![Plank { length: 10 }, Plank { length: 2 }])
-        .with_line(plank_line![Plank { length: 2 }, Plank { length: 10}])
-        .with_line(plank_line![Plank { length: 10 }, Plank { length: 2 }]);
+        .with_line(plank_line![10, 2])
+        .with_line(plank_line![2, 10])
+        .with_line(plank_line![10, 2]);
 
     assert_that!(format!("{}", calepinage)).is_equal_to("Calepinage([10, 2], [2, 10], [10, 2])".to_string());
 }
@@ -448,9 +4887,27 @@ fn test_result_calepine_display() {
 #[test]
 fn test_result_calepine_debug() {
     let calepinage = Calepinage::default()
-        .with_line(plank_line![Plank { length: 10 }, Plank { length: 2 }])
-        .with_line(plank_line![Plank { length: 2 }, Plank { length: 10}])
-        .with_line(plank_line![Plank { length: 10 }, Plank { length: 2 }]);
+        .with_line(plank_line![10, 2])
+        .with_line(plank_line![2, 10])
+        .with_line(plank_line![10, 2]);
 
     assert_that!(format!("{:?}", calepinage)).is_equal_to("Calepinage([10, 2], [2, 10], [10, 2])".to_string());
 }
+
+#[test]
+fn test_calepine_with_cuts_display() {
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![10, 2])
+        .with_cut(Cut { plank: Plank::new(3).unwrap(), at: 2 });
+
+    assert_that!(format!("{}", calepinage)).is_equal_to("Calepinage([10, 2]) with cuts [3 cut at 2]".to_string());
+}
+
+#[test]
+fn test_calepine_with_border_display() {
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![8].starting_at(1))
+        .with_border(vec![plank_line![10], plank_line![3]]);
+
+    assert_that!(format!("{}", calepinage)).is_equal_to("Calepinage([8] from 1) with border [[10], [3]]".to_string());
+}
@@ -0,0 +1,622 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::calepinage::{
+    CalepinageOptions, Cutout, Deck, DeckError, Goal, Grade, GradeRule, JointFigures, LastRow, LotConstraint, LotId,
+    Orientation, Outline, Pattern, Plank, PlankError, PlankHeap, Post, RowWidths, SlopeRule, Solver, Strategy,
+};
+use crate::gaps::{recommended_gaps, InvalidMoistureRange, Moisture, Species};
+use crate::inventory::{read_csv, InventoryError};
+use crate::messages::{Language, Localize};
+use crate::patterns::{PatternError, Preset};
+use crate::project::Project;
+use crate::substructure::{FramingError, Substructure};
+#[cfg(test)]
+use crate::calepinage::Dimension;
+
+// Project files, in TOML, describing the decks, the options, the grade rules and the
+// planks of a project so that the same plan can be laid again:
+//
+//     inventory = "stock.csv"
+//
+//     [[decks]]
+//     length = 4000
+//     width = 3000
+//     board_width = 145
+//
+//     [options]
+//     allow_cutting = true
+//     kerf = 3
+//     goal = "cost"
+//     solver = "branch_and_bound"
+//     pattern = { type = "preset", name = "third_bond", plank_length = 2400 }
+//
+//     [[rules]]
+//     not_adjacent = "C"
+//
+//     [[planks]]
+//     length = 2400
+//     count = 20
+//
+// Options left out keep their default. Planks are the listed planks followed by the
+// planks of the inventory, a CSV stock list read by `inventory::read_csv` whose path is
+// relative to the project file. Values go through the constructors, like `Deck::new`,
+// invalid values being reported the same way. The `server` requests use the same decks,
+// options and planks, in JSON.
+
+/// Why a project file can't be read, with the error of the constructor that rejected a value
+/// when the file was well written.
+#[derive(Debug, PartialEq, Clone, serde::Serialize)]
+pub enum ConfigError {
+    /// The file can't be read, as told by the system.
+    Unreadable(String),
+    /// The file isn't written in the format, as told by the parser.
+    Syntax(String),
+    /// A deck with an outline has a length, a width or a board width too.
+    OutlineWithDimensions,
+    /// A deck has neither a length and a width, nor an outline.
+    MissingDimensions,
+    Deck(DeckError),
+    Plank(PlankError),
+    Moisture(InvalidMoistureRange),
+    Substructure(FramingError),
+    Pattern(PatternError),
+    Inventory(InventoryError),
+    /// An error in the file at the path, the project file or its inventory.
+    InFile { path: PathBuf, error: Box<ConfigError> },
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProjectFile {
+    decks: Vec<DeckFile>,
+    #[serde(default)]
+    options: OptionsFile,
+    #[serde(default)]
+    rules: Vec<RuleFile>,
+    #[serde(default)]
+    planks: Vec<PlankFile>,
+    inventory: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DeckFile {
+    length: Option<usize>,
+    width: Option<usize>,
+    board_width: Option<usize>,
+    outline: Option<Vec<(usize, usize)>>,
+    #[serde(default)]
+    cutouts: Vec<Cutout>,
+    #[serde(default)]
+    posts: Vec<Post>,
+    slope: Option<SlopeFile>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct OptionsFile {
+    min_junction_offset: Option<usize>,
+    min_defect_distance: Option<usize>,
+    stagger_depth: Option<usize>,
+    allow_cutting: Option<bool>,
+    reuse_offcuts: Option<bool>,
+    min_reusable_offcut: Option<usize>,
+    kerf: Option<usize>,
+    solver: Option<SolverFile>,
+    goal: Option<GoalFile>,
+    pattern: Option<PatternFile>,
+    strategy: Option<StrategyFile>,
+    row_widths: Option<RowWidthsFile>,
+    lots: Option<LotsFile>,
+    border: Option<usize>,
+    breakers: Option<BreakersFile>,
+    last_row: Option<LastRowFile>,
+    end_gap: Option<usize>,
+    side_gap: Option<usize>,
+    wood: Option<WoodFile>,
+    alternate_sides: Option<bool>,
+    substructure: Option<SubstructureFile>,
+    min_end_piece: Option<usize>,
+    min_rip_width: Option<usize>,
+    slope_rule: Option<SlopeRuleFile>,
+    joint_figures: Option<JointFiguresFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SolverFile {
+    Greedy,
+    Exhaustive,
+    BranchAndBound,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum GoalFile {
+    Waste,
+    Cuts,
+    Cost,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum PatternFile {
+    Sorted,
+    Random { seed: u64 },
+    RunningBond { offset: usize },
+    Symmetric { offset: usize },
+    /// A pattern of the `patterns` module, by name.
+    Preset {
+        name: String,
+        plank_length: usize,
+        #[serde(default)]
+        seed: u64,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StrategyFile {
+    LongestFirst,
+    ShortestFirst,
+    Balanced,
+    FewestPieces,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JointFiguresFile {
+    max_step: usize,
+    tolerance: Option<usize>,
+    stair_rows: Option<usize>,
+}
+
+/// Wood the gaps are recommended for, the end and side gaps given with it taking
+/// precedence.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WoodFile {
+    species: SpeciesFile,
+    board_width: usize,
+    board_length: usize,
+    min_moisture: f64,
+    max_moisture: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SpeciesFile {
+    Ipe,
+    Teak,
+    Cedar,
+    DouglasFir,
+    Pine,
+    Larch,
+    Oak,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SlopeFile {
+    AlongLength,
+    AlongWidth,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SlopeRuleFile {
+    Parallel,
+    Perpendicular,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RowWidthsFile {
+    WidestFirst,
+    MostAvailable,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LotsFile {
+    WithinRow,
+    AcrossRows,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BreakersFile {
+    interval: usize,
+    board_width: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LastRowFile {
+    Reject,
+    Rip,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SubstructureFile {
+    joist_spacing: usize,
+    #[serde(default)]
+    first_joist_offset: usize,
+    #[serde(default)]
+    tolerance: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum RuleFile {
+    NotAdjacent(Grade),
+    LastRowsOnly { grade: Grade, rows: usize },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PlankFile {
+    length: usize,
+    #[serde(default = "one")]
+    count: usize,
+    width: Option<usize>,
+    thickness: Option<usize>,
+    lot: Option<u32>,
+    grade: Option<Grade>,
+    id: Option<String>,
+    price: Option<usize>,
+    min_length: Option<usize>,
+    #[serde(default)]
+    defects: Vec<usize>,
+}
+
+fn one() -> usize {
+    1
+}
+
+impl Project {
+    /// Loads the project file at `path`, see the `config` module for its format.
+    pub fn load(path: impl AsRef<Path>) -> Result<Project, ConfigError> {
+        let path = path.as_ref();
+        let in_file = |error| ConfigError::InFile {
+            path: path.to_path_buf(),
+            error: Box::new(error),
+        };
+        let text = std::fs::read_to_string(path).map_err(|error| in_file(ConfigError::Unreadable(error.to_string())))?;
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        Project::parse(&text, directory).map_err(in_file)
+    }
+
+    fn parse(text: &str, directory: &Path) -> Result<Project, ConfigError> {
+        let file: ProjectFile = toml::from_str(text).map_err(|error| ConfigError::Syntax(error.to_string()))?;
+        let decks = file.decks.into_iter().map(DeckFile::deck).collect::<Result<_, _>>()?;
+        let mut options = file.options.options()?;
+        options.grade_rules.extend(file.rules.into_iter().map(RuleFile::grade_rule));
+        let mut counts = PlankFile::counts(file.planks).map_err(ConfigError::Plank)?;
+        if let Some(inventory) = file.inventory {
+            let path = directory.join(inventory);
+            let stock = File::open(&path)
+                .map_err(|error| ConfigError::Unreadable(error.to_string()))
+                .and_then(|file| read_csv(BufReader::new(file)).map_err(ConfigError::Inventory))
+                .map_err(|error| ConfigError::InFile {
+                    path,
+                    error: Box::new(error),
+                })?;
+            counts.extend(stock.counts().iter().cloned());
+        }
+        Ok(Project {
+            decks,
+            plank_heap: PlankHeap::from_counts(counts).map_err(ConfigError::Plank)?,
+            options,
+        })
+    }
+}
+
+impl DeckFile {
+    pub(crate) fn deck(self) -> Result<Deck, ConfigError> {
+        let deck = match (self.outline, self.length, self.width, self.board_width) {
+            (Some(outline), None, None, None) => Outline::new(outline).map_err(DeckError::from).and_then(Deck::from_outline),
+            (Some(_), _, _, _) => return Err(ConfigError::OutlineWithDimensions),
+            (None, Some(length), Some(width), None) => Deck::new(length, width),
+            (None, Some(length), Some(width), Some(board_width)) => Deck::physical(length, width, board_width),
+            (None, _, _, _) => return Err(ConfigError::MissingDimensions),
+        };
+        let deck = deck.map_err(ConfigError::Deck)?;
+        let mut deck = self.cutouts.into_iter().fold(deck, Deck::with_cutout);
+        if let Some(slope) = self.slope {
+            deck = deck.with_slope(match slope {
+                SlopeFile::AlongLength => Orientation::AlongLength,
+                SlopeFile::AlongWidth => Orientation::AlongWidth,
+            });
+        }
+        Ok(self.posts.into_iter().fold(deck, Deck::with_post))
+    }
+}
+
+impl OptionsFile {
+    pub(crate) fn options(self) -> Result<CalepinageOptions, ConfigError> {
+        let default = CalepinageOptions::default();
+        let (end_gap, side_gap) = match self.wood {
+            Some(wood) => {
+                let gaps = recommended_gaps(
+                    wood.species(),
+                    Moisture::new(wood.min_moisture, wood.max_moisture).map_err(ConfigError::Moisture)?,
+                    wood.board_width,
+                    wood.board_length,
+                );
+                (gaps.end_gap, gaps.side_gap)
+            }
+            None => (default.end_gap, default.side_gap),
+        };
+        let mut options = default
+            .clone()
+            .with_min_junction_offset(self.min_junction_offset.unwrap_or(default.min_junction_offset))
+            .with_min_defect_distance(self.min_defect_distance.unwrap_or(default.min_defect_distance))
+            .with_stagger_depth(self.stagger_depth.unwrap_or(default.stagger_depth))
+            .with_allow_cutting(self.allow_cutting.unwrap_or(default.allow_cutting))
+            .with_reuse_offcuts(self.reuse_offcuts.unwrap_or(default.reuse_offcuts))
+            .with_min_reusable_offcut(self.min_reusable_offcut.unwrap_or(default.min_reusable_offcut))
+            .with_kerf(self.kerf.unwrap_or(default.kerf))
+            .with_end_gap(self.end_gap.unwrap_or(end_gap))
+            .with_side_gap(self.side_gap.unwrap_or(side_gap))
+            .with_alternate_sides(self.alternate_sides.unwrap_or(default.alternate_sides))
+            .with_min_end_piece(self.min_end_piece.unwrap_or(default.min_end_piece))
+            .with_min_rip_width(self.min_rip_width.unwrap_or(default.min_rip_width));
+        if let Some(solver) = self.solver {
+            options = options.with_solver(match solver {
+                SolverFile::Greedy => Solver::Greedy,
+                SolverFile::Exhaustive => Solver::Exhaustive,
+                SolverFile::BranchAndBound => Solver::BranchAndBound,
+            });
+        }
+        if let Some(goal) = self.goal {
+            options = options.with_goal(match goal {
+                GoalFile::Waste => Goal::MinimizeWaste,
+                GoalFile::Cuts => Goal::MinimizeCuts,
+                GoalFile::Cost => Goal::MinimizeCost,
+            });
+        }
+        if let Some(pattern) = self.pattern {
+            options = options.with_pattern(match pattern {
+                PatternFile::Sorted => Pattern::Sorted,
+                PatternFile::Random { seed } => Pattern::Random { seed },
+                PatternFile::RunningBond { offset } => Pattern::RunningBond { offset },
+                PatternFile::Symmetric { offset } => Pattern::Symmetric { offset },
+                PatternFile::Preset {
+                    name,
+                    plank_length,
+                    seed,
+                } => name.parse::<Preset>().map_err(ConfigError::Pattern)?.pattern(plank_length, seed),
+            });
+        }
+        if let Some(strategy) = self.strategy {
+            options = options.with_strategy(match strategy {
+                StrategyFile::LongestFirst => Strategy::LongestFirst,
+                StrategyFile::ShortestFirst => Strategy::ShortestFirst,
+                StrategyFile::Balanced => Strategy::Balanced,
+                StrategyFile::FewestPieces => Strategy::FewestPieces,
+            });
+        }
+        if let Some(slope_rule) = self.slope_rule {
+            options = options.with_slope_rule(match slope_rule {
+                SlopeRuleFile::Parallel => SlopeRule::Parallel,
+                SlopeRuleFile::Perpendicular => SlopeRule::Perpendicular,
+            });
+        }
+        if let Some(figures) = self.joint_figures {
+            let default = JointFigures::new(figures.max_step);
+            options = options.with_joint_figures(
+                default
+                    .with_tolerance(figures.tolerance.unwrap_or(default.tolerance))
+                    .with_stair_rows(figures.stair_rows.unwrap_or(default.stair_rows)),
+            );
+        }
+        if let Some(row_widths) = self.row_widths {
+            options = options.with_row_widths(match row_widths {
+                RowWidthsFile::WidestFirst => RowWidths::WidestFirst,
+                RowWidthsFile::MostAvailable => RowWidths::MostAvailable,
+            });
+        }
+        if let Some(lots) = self.lots {
+            options = options.with_lots(match lots {
+                LotsFile::WithinRow => LotConstraint::within_row(),
+                LotsFile::AcrossRows => LotConstraint::across_rows(),
+            });
+        }
+        if let Some(border) = self.border {
+            options = options.with_border(border);
+        }
+        if let Some(breakers) = self.breakers {
+            options = options.with_breakers(breakers.interval, breakers.board_width);
+        }
+        if let Some(last_row) = self.last_row {
+            options = options.with_last_row(match last_row {
+                LastRowFile::Reject => LastRow::Reject,
+                LastRowFile::Rip => LastRow::Rip,
+            });
+        }
+        if let Some(substructure) = self.substructure {
+            let joists = Substructure::new(substructure.joist_spacing, substructure.first_joist_offset)
+                .map_err(ConfigError::Substructure)?;
+            options = options.with_substructure(joists, substructure.tolerance);
+        }
+        Ok(options)
+    }
+}
+
+impl WoodFile {
+    fn species(&self) -> Species {
+        match self.species {
+            SpeciesFile::Ipe => Species::Ipe,
+            SpeciesFile::Teak => Species::Teak,
+            SpeciesFile::Cedar => Species::Cedar,
+            SpeciesFile::DouglasFir => Species::DouglasFir,
+            SpeciesFile::Pine => Species::Pine,
+            SpeciesFile::Larch => Species::Larch,
+            SpeciesFile::Oak => Species::Oak,
+        }
+    }
+}
+
+impl RuleFile {
+    fn grade_rule(self) -> GradeRule {
+        match self {
+            RuleFile::NotAdjacent(grade) => GradeRule::NotAdjacent(grade),
+            RuleFile::LastRowsOnly { grade, rows } => GradeRule::LastRowsOnly { grade, rows },
+        }
+    }
+}
+
+impl PlankFile {
+    /// The planks of every entry, `count` times each.
+    /// The planks of the files with their count, in order.
+    pub(crate) fn counts(files: Vec<PlankFile>) -> Result<Vec<(Plank, usize)>, PlankError> {
+        files
+            .into_iter()
+            .map(|file| {
+                let count = file.count;
+                file.plank().map(|plank| (plank, count))
+            })
+            .collect()
+    }
+
+    fn plank(self) -> Result<Plank, PlankError> {
+        let mut plank = Plank::new(self.length)?.with_defects(self.defects)?;
+        if let Some(width) = self.width {
+            plank = plank.with_width(width)?;
+        }
+        if let Some(thickness) = self.thickness {
+            plank = plank.with_thickness(thickness)?;
+        }
+        if let Some(lot) = self.lot {
+            plank = plank.with_lot(LotId(lot));
+        }
+        if let Some(grade) = self.grade {
+            plank = plank.with_grade(grade);
+        }
+        if let Some(id) = self.id {
+            plank = plank.with_label(&id);
+        }
+        if let Some(price) = self.price {
+            plank = plank.with_price(price);
+        }
+        if let Some(min_length) = self.min_length {
+            plank = plank.with_min_length(min_length)?;
+        }
+        Ok(plank)
+    }
+}
+
+#[test]
+fn project_should_be_parsed_with_default_options() {
+    let text = r#"
+        [[decks]]
+        length = 10
+        width = 2
+
+        [[decks]]
+        length = 4000
+        width = 3000
+        board_width = 145
+        cutouts = [{ start = 0, length = 2, first_row = 0, rows = 1 }]
+
+        [options]
+        allow_cutting = true
+        solver = "branch_and_bound"
+        pattern = { type = "random", seed = 42 }
+
+        [[rules]]
+        last_rows_only = { grade = "C", rows = 2 }
+
+        [[planks]]
+        length = 6
+        count = 2
+
+        [[planks]]
+        length = 5
+        id = "B1"
+        grade = "C"
+    "#;
+
+    let project = Project::parse(text, Path::new("")).unwrap();
+
+    assert_eq!(2, project.decks.len());
+    assert_eq!(Some(145), project.decks[1].board_width);
+    assert_eq!(1, project.decks[1].cutouts.len());
+    let expected_options = CalepinageOptions::default()
+        .with_allow_cutting(true)
+        .with_solver(Solver::BranchAndBound)
+        .with_pattern(Pattern::Random { seed: 42 })
+        .with_grade_rule(GradeRule::LastRowsOnly { grade: Grade::C, rows: 2 });
+    assert_eq!(expected_options, project.options);
+    let expected_heap = PlankHeap::from_planks(vec![
+        Plank::new(6).unwrap(),
+        Plank::new(6).unwrap(),
+        Plank::new(5).unwrap().with_grade(Grade::C).with_label("B1"),
+    ]);
+    assert_eq!(Ok(project.plank_heap), expected_heap);
+}
+
+#[test]
+fn project_should_read_the_inventory_next_to_the_project_file() {
+    let directory = std::env::temp_dir().join(format!("calepinage-project-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("stock.csv"), "length,count\n5,2\n").unwrap();
+    let path = directory.join("project.toml");
+    std::fs::write(&path, "inventory = \"stock.csv\"\n[[decks]]\nlength = 10\nwidth = 1\n").unwrap();
+
+    let project = Project::load(&path).unwrap();
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(PlankHeap::new().add(2, 5), project.plank_heap);
+    assert_eq!(1, project.calepine().unwrap().len());
+}
+
+#[test]
+fn project_should_report_invalid_values() {
+    let parse = |text| Project::parse(text, Path::new("")).map(|_| ());
+
+    assert_eq!(
+        Err(ConfigError::Deck(DeckError::ZeroDimension(Dimension::Length))),
+        parse("[[decks]]\nlength = 0\nwidth = 1\n")
+    );
+    assert_eq!(Err(ConfigError::MissingDimensions), parse("[[decks]]\nlength = 10\n"));
+    assert!(parse("[[decks]]\nlength = 10\nwidth = 1\ncolor = \"red\"\n").is_err());
+    let missing = Path::new("missing").join("project.toml");
+    assert!(matches!(Project::load(&missing), Err(ConfigError::InFile { path, .. }) if path == missing));
+    assert!(parse("[[decks]]\nlength = 10\nwidth = 1\n[options]\nsolver = \"fastest\"\n").is_err());
+    assert_eq!(
+        Err(ConfigError::Pattern(PatternError::UnknownPreset("zigzag".to_string()))),
+        parse("[[decks]]\nlength = 10\nwidth = 1\n[options]\npattern = { type = \"preset\", name = \"zigzag\", plank_length = 6 }\n")
+    );
+}
+
+#[test]
+fn project_should_recommend_the_gaps_of_the_wood() {
+    let text = |side_gap: &str| {
+        format!(
+            "[[decks]]\nlength = 10\nwidth = 1\n[options]\n{}\n[options.wood]\nspecies = \"ipe\"\n\
+             board_width = 1450\nboard_length = 24000\nmin_moisture = 12\nmax_moisture = 20\n",
+            side_gap
+        )
+    };
+
+    let project = Project::parse(&text(""), Path::new("")).unwrap();
+    let overridden = Project::parse(&text("side_gap = 50"), Path::new("")).unwrap();
+
+    assert_eq!((12, 36), (project.options.end_gap, project.options.side_gap));
+    assert_eq!((12, 50), (overridden.options.end_gap, overridden.options.side_gap));
+}
@@ -0,0 +1,312 @@
+use std::fmt::Write;
+
+use crate::calepinage::{Calepinage, CalepinageOptions, Cut, Deck, Placement, PostEdge};
+use crate::messages::{Language, Localize};
+#[cfg(test)]
+use crate::calepinage::{Line, Plank, Post};
+
+// Cut schedule: the cuts of a calepinage grouped for sawing. Cuts at the same length share
+// a setup of the saw stop, setups being ordered by decreasing length so that the stop only
+// moves one way. The boards of a picture frame border are then mitred at 45° where the
+// sides meet, at the frame corners, and the boards the railing posts stand on notched.
+//
+// The cut schedule and the placement table are also written as CSV files, to be printed
+// and ticked off in the workshop.
+
+/// `count` boards of `plank_length` cut at `at`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CutGroup {
+    pub plank_length: usize,
+    pub at: usize,
+    pub count: usize,
+}
+
+impl std::fmt::Display for CutGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+pub fn cut_schedule(calepinage: &Calepinage) -> Vec<CutGroup> {
+    let mut groups: Vec<CutGroup> = vec![];
+    for cut in calepinage.cuts.iter() {
+        let plank_length = cut.plank.length;
+        match groups.iter_mut().find(|group| group.at == cut.at && group.plank_length == plank_length) {
+            Some(group) => group.count += 1,
+            None => groups.push(CutGroup {
+                plank_length,
+                at: cut.at,
+                count: 1,
+            }),
+        }
+    }
+    groups.sort_by(|a, b| b.at.cmp(&a.at).then(b.plank_length.cmp(&a.plank_length)));
+    groups
+}
+
+/// `count` border boards `length` long from long point to long point, mitred at `ends` of
+/// their ends, 1 or 2, a side made of a single board being mitred at both ends.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MiterGroup {
+    pub length: usize,
+    pub ends: usize,
+    pub count: usize,
+}
+
+impl std::fmt::Display for MiterGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+/// The miters of the border boards at the frame corners, longest boards first.
+pub fn miter_schedule(calepinage: &Calepinage) -> Vec<MiterGroup> {
+    let mut groups: Vec<MiterGroup> = vec![];
+    for side in calepinage.border.iter() {
+        let mitred: Vec<(usize, usize)> = match side.planks.as_slice() {
+            [] => vec![],
+            [board] => vec![(board.length, 2)],
+            [first, .., last] => vec![(first.length, 1), (last.length, 1)],
+        };
+        for (length, ends) in mitred {
+            match groups.iter_mut().find(|group| group.length == length && group.ends == ends) {
+                Some(group) => group.count += 1,
+                None => groups.push(MiterGroup { length, ends, count: 1 }),
+            }
+        }
+    }
+    groups.sort_by(|a, b| b.length.cmp(&a.length).then(b.ends.cmp(&a.ends)));
+    groups
+}
+
+/// Notch cut for a railing post in the board laid from `x_start` on the row, from `from`
+/// to `to` along the board and `depth` deep across it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Notch {
+    pub row: usize,
+    pub x_start: usize,
+    pub from: usize,
+    pub to: usize,
+    pub depth: usize,
+}
+
+impl std::fmt::Display for Notch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "notch the board of row {} at {} from {} to {}, {} deep",
+            self.row, self.x_start, self.from, self.to, self.depth
+        )
+    }
+}
+
+/// The notches of the boards under the posts of the deck, post after post. Posts along the
+/// first and last rows notch the side of the boards, posts at the start and end of the
+/// rows the end of the first and last boards of every row they cross.
+pub fn notch_schedule(calepinage: &Calepinage, deck: &Deck, options: &CalepinageOptions) -> Vec<Notch> {
+    let placements = calepinage.placements();
+    let rows = placements.iter().map(|placement| placement.row + placement.plank.width).max().unwrap_or(0);
+    let row_width = deck.board_width.unwrap_or(1);
+    let row_pitch = deck.board_width.map_or(1, |board_width| board_width + options.side_gap);
+    let mut notches = vec![];
+    for post in deck.posts.iter() {
+        let (start, end) = post.span();
+        let notch = |placement: &Placement, from: usize, to: usize, depth: usize| Notch {
+            row: placement.row,
+            x_start: placement.x_start,
+            from,
+            to,
+            depth,
+        };
+        match post.edge {
+            PostEdge::First | PostEdge::Last => {
+                let on_edge = |placement: &&Placement| match post.edge {
+                    PostEdge::First => placement.row == 0,
+                    _ => placement.row + placement.plank.width == rows,
+                };
+                for placement in placements.iter().filter(on_edge) {
+                    let (from, to) = (start.max(placement.x_start), end.min(placement.x_end));
+                    if from < to {
+                        notches.push(notch(placement, from - placement.x_start, to - placement.x_start, post.depth));
+                    }
+                }
+            }
+            PostEdge::Start | PostEdge::End => {
+                for row in 0..rows {
+                    let (row_start, row_end) = (row * row_pitch, row * row_pitch + row_width);
+                    let depth = end.min(row_end).saturating_sub(start.max(row_start));
+                    let in_row = placements.iter().filter(|placement| placement.row == row);
+                    let board = match post.edge {
+                        PostEdge::Start => in_row.min_by_key(|placement| placement.x_start),
+                        _ => in_row.max_by_key(|placement| placement.x_end),
+                    };
+                    if let Some(board) = board.filter(|_| depth > 0) {
+                        let length = board.x_end - board.x_start;
+                        let (from, to) = match post.edge {
+                            PostEdge::Start => (0, post.depth.min(length)),
+                            _ => (length.saturating_sub(post.depth), length),
+                        };
+                        notches.push(notch(board, from, to, depth));
+                    }
+                }
+            }
+        }
+    }
+    notches
+}
+
+/// Number of times the saw stop is set, once for every length cut.
+pub fn setups(schedule: &[CutGroup]) -> usize {
+    let mut lengths: Vec<usize> = schedule.iter().map(|group| group.at).collect();
+    lengths.dedup();
+    lengths.len()
+}
+
+/// The cut schedule as CSV, one line per group of cuts.
+pub fn cut_schedule_csv(schedule: &[CutGroup]) -> String {
+    let mut csv = "board length,cut at,count\n".to_string();
+    for group in schedule {
+        writeln!(csv, "{},{},{}", group.plank_length, group.at, group.count).unwrap();
+    }
+    csv
+}
+
+/// Placements of the boards as CSV, row by row. The cut column tells the cut the piece or
+/// the reused offcut comes from, empty for the boards laid whole.
+pub fn placements_csv(calepinage: &Calepinage) -> String {
+    let mut cut_planks: Vec<(&Cut, _)> = calepinage
+        .cuts
+        .iter()
+        .flat_map(|cut| vec![(cut, cut.piece()), (cut, cut.offcut_plank(calepinage.kerf))])
+        .collect();
+    let mut csv = "row,position,board id,length,cut\n".to_string();
+    for placement in calepinage.placements() {
+        let cut = cut_planks
+            .iter()
+            .position(|(_, plank)| plank == placement.plank)
+            .map(|index| cut_planks.remove(index).0.to_string());
+        let id = placement.plank.label.as_deref().map(quoted).unwrap_or_default();
+        writeln!(
+            csv,
+            "{},{},{},{},{}",
+            placement.row,
+            placement.x_start,
+            id,
+            placement.plank.length,
+            cut.unwrap_or_default()
+        )
+        .unwrap();
+    }
+    csv
+}
+
+/// The field quoted when it contains a comma or a quote, quotes being doubled.
+fn quoted(field: &str) -> String {
+    if field.contains(',') || field.contains('"') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[test]
+fn cut_schedule_should_group_identical_cuts_by_saw_setup() {
+    let cut = |plank_length, at| Cut {
+        plank: Plank::new(plank_length).unwrap(),
+        at,
+    };
+    let calepinage = Calepinage::default()
+        .with_cut(cut(2400, 600))
+        .with_cut(cut(2400, 1200))
+        .with_cut(cut(3000, 600))
+        .with_cut(cut(2400, 1200));
+
+    let schedule = cut_schedule(&calepinage);
+
+    assert_eq!(
+        vec![
+            CutGroup { plank_length: 2400, at: 1200, count: 2 },
+            CutGroup { plank_length: 3000, at: 600, count: 1 },
+            CutGroup { plank_length: 2400, at: 600, count: 1 },
+        ],
+        schedule
+    );
+    assert_eq!(2, setups(&schedule));
+    assert_eq!("cut 2 boards of 2400 at 1200", schedule[0].to_string());
+}
+
+#[test]
+fn miter_schedule_should_miter_the_corner_ends_of_every_side() {
+    let calepinage = Calepinage::default().with_border(vec![
+        Line::new(vec![Plank::new(2400).unwrap(), Plank::new(1600).unwrap()]),
+        Line::new(vec![Plank::new(1000).unwrap()]),
+        Line::new(vec![Plank::new(2400).unwrap(), Plank::new(600).unwrap(), Plank::new(1000).unwrap()]),
+        Line::new(vec![Plank::new(1000).unwrap()]),
+    ]);
+
+    let schedule = miter_schedule(&calepinage);
+
+    assert_eq!(
+        vec![
+            MiterGroup { length: 2400, ends: 1, count: 2 },
+            MiterGroup { length: 1600, ends: 1, count: 1 },
+            MiterGroup { length: 1000, ends: 2, count: 2 },
+            MiterGroup { length: 1000, ends: 1, count: 1 },
+        ],
+        schedule
+    );
+    assert_eq!("miter 2 boards of 1000 at both ends, long point to long point", schedule[2].to_string());
+}
+
+#[test]
+fn notch_schedule_should_notch_the_boards_under_the_posts() {
+    let post = |edge, at| Post { edge, at, width: 100, depth: 60 };
+    let deck = Deck::physical(2000, 280, 140)
+        .unwrap()
+        .with_post(post(PostEdge::First, 1000))
+        .with_post(post(PostEdge::Last, 50))
+        .with_post(post(PostEdge::End, 140));
+    let calepinage = Calepinage::default()
+        .with_line(Line::new(vec![Plank::new(1200).unwrap(), Plank::new(800).unwrap()]))
+        .with_line(Line::new(vec![Plank::new(2000).unwrap()]));
+
+    let notches = notch_schedule(&calepinage, &deck, &CalepinageOptions::default());
+
+    let notch = |row, x_start, from, to, depth| Notch { row, x_start, from, to, depth };
+    assert_eq!(
+        vec![
+            notch(0, 0, 950, 1050, 60),
+            notch(1, 0, 0, 100, 60),
+            // the post at the end straddles both rows
+            notch(0, 1200, 740, 800, 50),
+            notch(1, 0, 1940, 2000, 50),
+        ],
+        notches
+    );
+    assert_eq!("notch the board of row 0 at 0 from 950 to 1050, 60 deep", notches[0].to_string());
+}
+
+#[test]
+fn cut_schedule_csv_should_have_a_line_per_group() {
+    let schedule = vec![CutGroup { plank_length: 2400, at: 1200, count: 2 }];
+
+    assert_eq!("board length,cut at,count\n2400,1200,2\n", cut_schedule_csv(&schedule));
+}
+
+#[test]
+fn placements_csv_should_tell_the_cut_of_every_piece() {
+    let board = Plank::new(10).unwrap().with_label("B1, left");
+    let calepinage = Calepinage::default()
+        .with_line(Line::new(vec![Plank::new(6).unwrap(), board.part(0, 4)]))
+        .with_line(Line::new(vec![board.part(4, 10), Plank::new(4).unwrap()]))
+        .with_cut(Cut { plank: board, at: 4 });
+
+    assert_eq!(
+        "row,position,board id,length,cut\n\
+         0,0,,6,\n\
+         0,6,\"B1, left\",4,10 cut at 4\n\
+         1,0,\"B1, left\",6,10 cut at 4\n\
+         1,6,,4,\n",
+        placements_csv(&calepinage)
+    );
+}
@@ -0,0 +1,175 @@
+use std::f64::consts::SQRT_2;
+
+use crate::calepinage::{calepine_rows, Calepinage, CalepinageError, CalepinageOptions, Deck, PlankHeap, RowGoal};
+use crate::messages::{Language, Localize};
+
+// Diagonal: planks are laid at 45° from the deck edges, rows getting longer towards the
+// diagonal of the deck and shorter in the corners.
+//
+// Rows are measured along the planks direction u = (x + y) / √2, rows stacking along
+// v = (y - x) / √2, the first row lying in the (length, 0) corner. Coordinates are
+// physical: a deck with width = 4 is 4 boards wide (4 * board_width).
+
+/// Deck edge an end of a row is cut against, with a 45° angle cut, or the corner of the
+/// deck when the row end spans two edges.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Edge {
+    Left,
+    Bottom,
+    Right,
+    Top,
+    Corner,
+}
+
+/// A row from start to start + length along the planks direction, long enough to be
+/// trimmed to the deck on both ends.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DiagonalRow {
+    pub start: usize,
+    pub length: usize,
+    pub start_edge: Edge,
+    pub end_edge: Edge,
+}
+
+// absorbs floating point noise so that an exact length isn't rounded up to the next unit
+const EPSILON: f64 = 1e-9;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiagonalError {
+    ZeroBoardWidth,
+}
+
+impl std::fmt::Display for DiagonalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for DiagonalError {}
+
+pub fn diagonal_rows(deck: &Deck, board_width: usize) -> Result<Vec<DiagonalRow>, DiagonalError> {
+    if board_width == 0 {
+        return Err(DiagonalError::ZeroBoardWidth);
+    }
+
+    let deck_length = deck.length as f64;
+    let deck_width = (deck.width * board_width) as f64;
+    let board_width = board_width as f64;
+    let row_count = (((deck_length + deck_width) / SQRT_2 - EPSILON) / board_width).ceil() as usize;
+
+    let u_min = |v: f64| v.abs();
+    let u_max = |v: f64| (v + SQRT_2 * deck_length).min(SQRT_2 * deck_width - v);
+    let start_breakpoint = 0.0;
+    let end_breakpoint = (deck_width - deck_length) / SQRT_2;
+
+    let rows = (0..row_count)
+        .map(|row| {
+            let v_start = -deck_length / SQRT_2 + row as f64 * board_width;
+            let v_end = v_start + board_width;
+            let contains = |v: f64| v_start < v && v < v_end;
+
+            let start = if contains(start_breakpoint) { 0.0 } else { u_min(v_start).min(u_min(v_end)) };
+            let mut end = u_max(v_start).max(u_max(v_end));
+            if contains(end_breakpoint) {
+                end = end.max(u_max(end_breakpoint));
+            }
+            let start = (start + EPSILON).floor() as usize;
+            let end = (end - EPSILON).ceil() as usize;
+
+            DiagonalRow {
+                start,
+                length: end - start,
+                start_edge: if contains(start_breakpoint) {
+                    Edge::Corner
+                } else if v_end <= start_breakpoint {
+                    Edge::Bottom
+                } else {
+                    Edge::Left
+                },
+                end_edge: if contains(end_breakpoint) {
+                    Edge::Corner
+                } else if v_end <= end_breakpoint {
+                    Edge::Right
+                } else {
+                    Edge::Top
+                },
+            }
+        })
+        .collect();
+
+    Ok(rows)
+}
+
+/// Lays planks on the diagonal rows, lines starting at the row start so that junctions of
+/// adjacent rows can be compared.
+pub fn calepine_diagonally(
+    plank_heap: PlankHeap,
+    rows: &[DiagonalRow],
+    options: CalepinageOptions,
+) -> Result<Calepinage, CalepinageError> {
+    let goals: Vec<RowGoal> = rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| RowGoal {
+            row: index,
+            start: row.start,
+            length: row.length,
+        })
+        .collect();
+    calepine_rows(plank_heap, &goals, &options).map(|(calepinage, _)| calepinage)
+}
+
+#[test]
+fn diagonal_rows_should_cover_the_whole_deck() {
+    let deck = Deck::new(10, 10).unwrap();
+
+    let rows = diagonal_rows(&deck, 1).unwrap();
+
+    assert_eq!(15, rows.len());
+    let covered: usize = rows.iter().map(|row| row.length).sum();
+    assert!(covered as f64 >= 100.0);
+}
+
+#[test]
+fn diagonal_rows_should_be_longest_on_the_diagonal() {
+    let deck = Deck::new(10, 10).unwrap();
+
+    let rows = diagonal_rows(&deck, 1).unwrap();
+
+    let lengths: Vec<usize> = rows.iter().map(|row| row.length).collect();
+    assert_eq!(vec![3, 5, 7, 9, 11, 13, 15, 15, 14, 12, 10, 8, 6, 4, 2], lengths);
+    assert_eq!(
+        (Edge::Bottom, Edge::Right),
+        (rows[0].start_edge, rows[0].end_edge)
+    );
+    assert_eq!(
+        (Edge::Corner, Edge::Corner),
+        (rows[7].start_edge, rows[7].end_edge)
+    );
+    assert_eq!(
+        (Edge::Left, Edge::Top),
+        (rows[14].start_edge, rows[14].end_edge)
+    );
+}
+
+#[test]
+fn diagonal_rows_should_reject_zero_board_width() {
+    let deck = Deck::new(10, 10).unwrap();
+
+    assert_eq!(Err(DiagonalError::ZeroBoardWidth), diagonal_rows(&deck, 0));
+}
+
+#[test]
+fn calepine_diagonally_should_start_lines_at_row_start() {
+    let deck = Deck::new(3, 3).unwrap();
+    let rows = diagonal_rows(&deck, 1).unwrap();
+    let plank_heap = PlankHeap::new().add(30, 1);
+    let options = CalepinageOptions::default().with_min_junction_offset(0);
+
+    let calepinage = calepine_diagonally(plank_heap, &rows, options).unwrap();
+
+    let starts: Vec<usize> = calepinage.lines.iter().map(|line| line.start).collect();
+    let expected: Vec<usize> = rows.iter().map(|row| row.start).collect();
+    assert_eq!(expected, starts);
+}
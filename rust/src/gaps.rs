@@ -0,0 +1,128 @@
+#[cfg(test)]
+use crate::calepinage::CalepinageOptions;
+use crate::messages::{Language, Localize};
+
+// Expansion gaps recommended for a wood. A board swells across its width by its
+// dimensional change coefficient for every point of moisture content it gains, the gap
+// between rows taking the swelling from the driest to the wettest expected moisture.
+// Lengthwise wood moves about fifty times less, the gap between board ends taking that
+// movement for the longest board. Past its fiber saturation point, wood doesn't swell
+// anymore.
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Species {
+    Ipe,
+    Teak,
+    Cedar,
+    DouglasFir,
+    Pine,
+    Larch,
+    Oak,
+}
+
+impl Species {
+    /// Swelling across the width of a flat sawn board for a point of moisture content, as
+    /// a fraction of its width.
+    pub fn tangential_coefficient(&self) -> f64 {
+        match self {
+            Species::Ipe => 0.0031,
+            Species::Teak => 0.0019,
+            Species::Cedar => 0.0023,
+            Species::DouglasFir => 0.0027,
+            Species::Pine => 0.0026,
+            Species::Larch => 0.0029,
+            Species::Oak => 0.0037,
+        }
+    }
+
+    /// Swelling along the board for a point of moisture content, as a fraction of its
+    /// length.
+    pub fn longitudinal_coefficient(&self) -> f64 {
+        self.tangential_coefficient() / LONGITUDINAL_RATIO
+    }
+}
+
+const LONGITUDINAL_RATIO: f64 = 50.0;
+
+/// Moisture content, in percent, past which wood doesn't swell.
+const FIBER_SATURATION: f64 = 28.0;
+
+/// Range of moisture content, in percent, the boards are expected to go through once
+/// laid.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Moisture {
+    pub min: f64,
+    pub max: f64,
+}
+
+/// A moisture range going below 0 %, or from a wetter to a drier moisture.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InvalidMoistureRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl std::fmt::Display for InvalidMoistureRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for InvalidMoistureRange {}
+
+impl Moisture {
+    pub fn new(min: f64, max: f64) -> Result<Self, InvalidMoistureRange> {
+        if !(0.0 <= min && min <= max) {
+            return Err(InvalidMoistureRange { min, max });
+        }
+        Ok(Moisture { min, max })
+    }
+
+    /// Points of moisture content the wood swells for.
+    fn swelling_points(&self) -> f64 {
+        self.max.min(FIBER_SATURATION) - self.min.min(FIBER_SATURATION)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Gaps {
+    pub end_gap: usize,
+    pub side_gap: usize,
+}
+
+/// Gaps for boards of the species `board_width` wide, the longest being `board_length`
+/// long, rounded up to the unit of the dimensions.
+pub fn recommended_gaps(species: Species, moisture: Moisture, board_width: usize, board_length: usize) -> Gaps {
+    let points = moisture.swelling_points();
+    let swelling = |dimension: usize, coefficient: f64| (dimension as f64 * coefficient * points).ceil() as usize;
+    Gaps {
+        end_gap: swelling(board_length, species.longitudinal_coefficient()),
+        side_gap: swelling(board_width, species.tangential_coefficient()),
+    }
+}
+
+#[test]
+fn gaps_should_take_the_swelling_over_the_moisture_range() {
+    let moisture = Moisture::new(12.0, 20.0).unwrap();
+
+    let gaps = recommended_gaps(Species::Ipe, moisture, 1450, 24000);
+
+    assert_eq!(Gaps { end_gap: 12, side_gap: 36 }, gaps);
+    let options = CalepinageOptions::default().with_gaps(gaps);
+    assert_eq!((12, 36), (options.end_gap, options.side_gap));
+    assert!(recommended_gaps(Species::Teak, moisture, 1450, 24000).side_gap < gaps.side_gap);
+}
+
+#[test]
+fn gaps_should_ignore_moisture_past_the_fiber_saturation() {
+    let saturated = recommended_gaps(Species::Pine, Moisture::new(15.0, 28.0).unwrap(), 1450, 24000);
+
+    assert_eq!(saturated, recommended_gaps(Species::Pine, Moisture::new(15.0, 60.0).unwrap(), 1450, 24000));
+    assert_eq!(
+        Gaps { end_gap: 0, side_gap: 0 },
+        recommended_gaps(Species::Pine, Moisture::new(30.0, 60.0).unwrap(), 1450, 24000)
+    );
+    assert_eq!(Err(InvalidMoistureRange { min: 20.0, max: 12.0 }), Moisture::new(20.0, 12.0));
+    assert!(Moisture::new(f64::NAN, 12.0).is_err());
+}
@@ -0,0 +1,177 @@
+#[cfg(test)]
+use crate::calepinage::Plank;
+use crate::calepinage::{Calepinage, Line};
+#[cfg(test)]
+use crate::plank_line;
+use crate::substructure::Substructure;
+
+// Fasteners needed to screw the deck lines on the joists, border excluded, and where the
+// hidden clips of clip-fastened decks go.
+
+/// Screws or clips fastening a plank on every joist it lies on.
+pub const FASTENERS_PER_JOIST: usize = 2;
+/// Additional fasteners securing the plank ends meeting at a butt joint.
+pub const EXTRA_FASTENERS_PER_BUTT_JOINT: usize = 2;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Hardware {
+    pub fasteners: usize,
+    pub butt_joints: usize,
+}
+
+impl Hardware {
+    /// Number of boxes of `box_size` fasteners to buy.
+    pub fn boxes(&self, box_size: usize) -> usize {
+        self.fasteners.div_ceil(box_size)
+    }
+}
+
+pub fn hardware(calepinage: &Calepinage, substructure: &Substructure) -> Hardware {
+    let joists: usize = calepinage.lines.iter().map(|line| joists_under_planks(line, substructure)).sum();
+    let butt_joints: usize = calepinage.lines.iter().map(|line| line.compute_junction().len()).sum();
+    Hardware {
+        fasteners: joists * FASTENERS_PER_JOIST + butt_joints * EXTRA_FASTENERS_PER_BUTT_JOINT,
+        butt_joints,
+    }
+}
+
+/// Hidden fastener holding the boards along an edge on a joist, `edge` being the edge below
+/// the row of the same index: edge 0 is the deck side along the first row, fastened with
+/// starter clips, and the edge past the last row is the other side.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Clip {
+    pub edge: usize,
+    pub at: usize,
+    pub starter: bool,
+}
+
+impl std::fmt::Display for Clip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.starter { "starter clip" } else { "clip" };
+        write!(f, "{} at {} on edge {}", kind, self.at, self.edge)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ClipLayout {
+    /// Edge after edge, along the deck length.
+    pub clips: Vec<Clip>,
+}
+
+impl ClipLayout {
+    pub fn count(&self) -> usize {
+        self.clips.len()
+    }
+
+    pub fn starter_count(&self) -> usize {
+        self.clips.iter().filter(|clip| clip.starter).count()
+    }
+}
+
+/// One clip on every edge of every board for every joist the board lies on, a board
+/// sharing the clips of its lower edge with the boards below it.
+pub fn clip_layout(calepinage: &Calepinage, substructure: &Substructure) -> ClipLayout {
+    let placements = calepinage.placements();
+    let rows = placements.iter().map(|placement| placement.row + placement.plank.width).max().unwrap_or(0);
+    let mut clips = vec![];
+    for placement in placements.iter() {
+        let top = placement.row + placement.plank.width;
+        let edges = std::iter::once(placement.row).chain((top == rows).then_some(top));
+        for edge in edges {
+            clips.extend(joist_positions(placement.x_start, placement.x_end, substructure).map(|at| Clip {
+                edge,
+                at,
+                starter: edge == 0,
+            }));
+        }
+    }
+    clips.sort_by_key(|clip| (clip.edge, clip.at));
+    ClipLayout { clips }
+}
+
+/// Sum over the planks of the line of the joists each plank lies on, ends included.
+fn joists_under_planks(line: &Line, substructure: &Substructure) -> usize {
+    let mut start = line.start;
+    let mut joists = 0;
+    for plank in line.planks.iter() {
+        joists += joists_between(start, start + plank.length, substructure);
+        start += plank.length + line.gap;
+    }
+    joists
+}
+
+fn joists_between(start: usize, end: usize, substructure: &Substructure) -> usize {
+    joist_positions(start, end, substructure).len()
+}
+
+/// Positions of the joists from `start` to `end`, ends included.
+fn joist_positions(start: usize, end: usize, substructure: &Substructure) -> impl ExactSizeIterator<Item = usize> {
+    let Substructure {
+        joist_spacing,
+        first_joist_offset,
+    } = *substructure;
+    let joists = if end < first_joist_offset {
+        0..0
+    } else {
+        start.saturating_sub(first_joist_offset).div_ceil(joist_spacing)..(end - first_joist_offset) / joist_spacing + 1
+    };
+    joists.map(move |joist| first_joist_offset + joist * joist_spacing)
+}
+
+#[test]
+fn joists_between_should_include_ends() {
+    let substructure = Substructure::new(400, 100).unwrap();
+
+    assert_eq!(0, joists_between(0, 99, &substructure));
+    assert_eq!(1, joists_between(0, 100, &substructure));
+    assert_eq!(2, joists_between(100, 500, &substructure));
+    assert_eq!(0, joists_between(101, 499, &substructure));
+    assert_eq!(3, joists_between(50, 1000, &substructure));
+}
+
+#[test]
+fn hardware_should_count_fasteners_per_joist_and_butt_joint() {
+    let substructure = Substructure::new(400, 0).unwrap();
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![Plank::new(1200).unwrap()])
+        .with_line(plank_line![Plank::new(800).unwrap(), Plank::new(400).unwrap()]);
+
+    let hardware = hardware(&calepinage, &substructure);
+
+    // 4 joists under the first line, 3 + 2 under the planks of the second one
+    assert_eq!(Hardware { fasteners: 20, butt_joints: 1 }, hardware);
+    assert_eq!(2, hardware.boxes(10));
+    assert_eq!(1, hardware.boxes(100));
+}
+
+#[test]
+fn clip_layout_should_clip_every_board_edge_on_every_joist() {
+    let substructure = Substructure::new(400, 0).unwrap();
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![Plank::new(800).unwrap()])
+        .with_line(plank_line![Plank::new(400).unwrap(), Plank::new(400).unwrap()]);
+
+    let layout = clip_layout(&calepinage, &substructure);
+
+    let clip = |edge, at| Clip { edge, at, starter: edge == 0 };
+    assert_eq!(
+        vec![
+            clip(0, 0),
+            clip(0, 400),
+            clip(0, 800),
+            // the planks of the second line meet on the joist at 400, each having its clips
+            clip(1, 0),
+            clip(1, 400),
+            clip(1, 400),
+            clip(1, 800),
+            clip(2, 0),
+            clip(2, 400),
+            clip(2, 400),
+            clip(2, 800)
+        ],
+        layout.clips
+    );
+    assert_eq!(11, layout.count());
+    assert_eq!(3, layout.starter_count());
+    assert_eq!("starter clip at 400 on edge 0", layout.clips[1].to_string());
+}
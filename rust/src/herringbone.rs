@@ -0,0 +1,159 @@
+pub use crate::calepinage::Orientation;
+use crate::calepinage::{Deck, Plank};
+use crate::messages::{Language, Localize};
+
+// Herringbone: planks are laid at 90° to each other, the end of every plank laid along
+// the deck length touching the side of a plank laid along the deck width, making
+// staircases of chevrons.
+//
+// Coordinates are physical: a deck with width = 4 is 4 boards wide (4 * board_width).
+
+/// The part of a plank visible on the deck, from (x, y) to (x + length, y + width) when laid
+/// along the length, to (x + width, y + length) when laid along the width.
+#[derive(Debug, PartialEq, Clone)]
+pub struct HerringbonePlacement {
+    pub x: usize,
+    pub y: usize,
+    pub length: usize,
+    pub width: usize,
+    pub orientation: Orientation,
+    pub cut: bool,
+}
+
+impl HerringbonePlacement {
+    pub fn area(&self) -> usize {
+        self.length * self.width
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Herringbone {
+    pub placements: Vec<HerringbonePlacement>,
+}
+
+impl Herringbone {
+    /// Every placement requires its own plank, offcuts are not reused.
+    pub fn planks_needed(&self) -> usize {
+        self.placements.len()
+    }
+
+    pub fn cut_placements(&self) -> Vec<&HerringbonePlacement> {
+        self.placements.iter().filter(|placement| placement.cut).collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HerringboneError {
+    /// Planks no longer than wide, or of no width, make no chevrons.
+    PlankNotLongerThanWide,
+}
+
+impl std::fmt::Display for HerringboneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for HerringboneError {}
+
+pub fn herringbone(deck: &Deck, plank: &Plank, board_width: usize) -> Result<Herringbone, HerringboneError> {
+    if board_width == 0 || plank.length <= board_width {
+        return Err(HerringboneError::PlankNotLongerThanWide);
+    }
+
+    let deck_length = deck.length as i64;
+    let deck_width = (deck.width * board_width) as i64;
+    let length = plank.length as i64;
+    let width = board_width as i64;
+
+    // Planks come by pairs, a pair every (width, width) along a staircase, staircases
+    // repeating every (length, -length).
+    let staircases = (-(deck_width + 2 * length)) / (2 * length) - 1..=(deck_length + 2 * length) / (2 * length) + 1;
+    let steps = -(2 * length) / (2 * width) - 1..=(deck_length + deck_width + 2 * length) / (2 * width) + 1;
+
+    let mut placements = vec![];
+    for staircase in staircases {
+        for step in steps.clone() {
+            let x = step * width + staircase * length;
+            let y = step * width - staircase * length;
+            let pair = [
+                (x, y, length, width, Orientation::AlongLength),
+                (x, y + width, width, length, Orientation::AlongWidth),
+            ];
+            for (x, y, dx, dy, orientation) in pair {
+                let (x_start, x_end) = (x.max(0), (x + dx).min(deck_length));
+                let (y_start, y_end) = (y.max(0), (y + dy).min(deck_width));
+                if x_start >= x_end || y_start >= y_end {
+                    continue;
+                }
+                let (visible_length, visible_width) = match orientation {
+                    Orientation::AlongLength => (x_end - x_start, y_end - y_start),
+                    Orientation::AlongWidth => (y_end - y_start, x_end - x_start),
+                };
+                placements.push(HerringbonePlacement {
+                    x: x_start as usize,
+                    y: y_start as usize,
+                    length: visible_length as usize,
+                    width: visible_width as usize,
+                    orientation,
+                    cut: visible_length < length || visible_width < width,
+                });
+            }
+        }
+    }
+    placements.sort_by_key(|placement| (placement.y, placement.x));
+
+    Ok(Herringbone { placements })
+}
+
+#[test]
+fn herringbone_should_cover_the_whole_deck() {
+    for (plank_length, board_width) in [(2, 1), (3, 1), (5, 2), (7, 3)] {
+        let deck = Deck::new(17, 5).unwrap();
+
+        let layout = herringbone(&deck, &Plank::new(plank_length).unwrap(), board_width).unwrap();
+
+        let covered: usize = layout.placements.iter().map(HerringbonePlacement::area).sum();
+        assert_eq!(17 * 5 * board_width, covered);
+    }
+}
+
+#[test]
+fn herringbone_placements_should_not_overlap() {
+    let deck = Deck::new(12, 4).unwrap();
+
+    let layout = herringbone(&deck, &Plank::new(4).unwrap(), 1).unwrap();
+
+    let bounds = |placement: &HerringbonePlacement| match placement.orientation {
+        Orientation::AlongLength => (placement.x, placement.y, placement.x + placement.length, placement.y + placement.width),
+        Orientation::AlongWidth => (placement.x, placement.y, placement.x + placement.width, placement.y + placement.length),
+    };
+    for (i, lhs) in layout.placements.iter().enumerate() {
+        for rhs in layout.placements.iter().skip(i + 1) {
+            let (lx0, ly0, lx1, ly1) = bounds(lhs);
+            let (rx0, ry0, rx1, ry1) = bounds(rhs);
+            assert!(lx1 <= rx0 || rx1 <= lx0 || ly1 <= ry0 || ry1 <= ly0, "{:?} overlaps {:?}", lhs, rhs);
+        }
+    }
+}
+
+#[test]
+fn herringbone_should_alternate_orientations() {
+    let deck = Deck::new(12, 4).unwrap();
+
+    let layout = herringbone(&deck, &Plank::new(4).unwrap(), 1).unwrap();
+
+    let along_length = layout.placements.iter().filter(|placement| placement.orientation == Orientation::AlongLength).count();
+    let along_width = layout.placements.len() - along_length;
+    assert!(along_length > 0 && along_width > 0);
+    assert!(!layout.cut_placements().is_empty());
+}
+
+#[test]
+fn herringbone_should_reject_planks_not_longer_than_wide() {
+    let deck = Deck::new(12, 4).unwrap();
+
+    assert_eq!(Err(HerringboneError::PlankNotLongerThanWide), herringbone(&deck, &Plank::new(2).unwrap(), 2));
+    assert_eq!(Err(HerringboneError::PlankNotLongerThanWide), herringbone(&deck, &Plank::new(2).unwrap(), 0));
+}
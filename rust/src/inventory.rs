@@ -0,0 +1,153 @@
+use std::io::BufRead;
+
+use crate::calepinage::{LotId, Plank, PlankError, PlankHeap};
+use crate::messages::{Language, Localize};
+
+// Stock lists exported from spreadsheets, as CSV files. The first line names the columns,
+// in any order: `length` is required, `count`, `width`, `id` and `lot` are optional. A
+// stock list either counts the boards of every length, like `length,count`, or lists the
+// physical boards one per row, like `id,length,lot`, the id becoming the board label.
+// Lengths and widths are in the solver unit, like `PlankHeap::try_add`.
+
+const COLUMNS: [&str; 5] = ["length", "count", "width", "id", "lot"];
+
+/// Why a stock list can't be read, lines counting from 1.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InventoryError {
+    /// The stock list can't be read, as told by the reader.
+    Unreadable(String),
+    UnknownColumn { line: usize, column: String },
+    NoLengthColumn { line: usize },
+    FieldCount { line: usize, expected: usize, found: usize },
+    /// The field of a number column isn't a number.
+    NotANumber { line: usize, column: String, field: String },
+    NoLength { line: usize },
+    InvalidPlank { line: usize, error: PlankError },
+    /// A board with an id is a single board.
+    CountedBoard { line: usize, id: String, count: usize },
+}
+
+impl std::fmt::Display for InventoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for InventoryError {}
+
+pub fn read_csv(reader: impl BufRead) -> Result<PlankHeap, InventoryError> {
+    let unreadable = |error: std::io::Error| InventoryError::Unreadable(error.to_string());
+    let mut lines = reader.lines().enumerate().filter(|(_, line)| match line {
+        Ok(line) => !line.trim().is_empty(),
+        Err(_) => true,
+    });
+    let (header_index, header) = match lines.next() {
+        Some((index, line)) => (index, line.map_err(unreadable)?),
+        None => return Ok(PlankHeap::new()),
+    };
+    let columns: Vec<String> = fields(&header).map(|column| column.to_lowercase()).collect();
+    if let Some(unknown) = columns.iter().find(|column| !COLUMNS.contains(&column.as_str())) {
+        return Err(InventoryError::UnknownColumn {
+            line: header_index + 1,
+            column: unknown.clone(),
+        });
+    }
+    let index_of = |name: &str| columns.iter().position(|column| column == name);
+    if index_of("length").is_none() {
+        return Err(InventoryError::NoLengthColumn { line: header_index + 1 });
+    }
+
+    let mut plank_heap = PlankHeap::new();
+    for (index, line) in lines {
+        let line = line.map_err(unreadable)?;
+        let line_number = index + 1;
+        let row: Vec<&str> = fields(&line).collect();
+        if row.len() != columns.len() {
+            return Err(InventoryError::FieldCount {
+                line: line_number,
+                expected: columns.len(),
+                found: row.len(),
+            });
+        }
+        let field = |name: &str| index_of(name).map(|index| row[index]).filter(|field| !field.is_empty());
+        let not_a_number = |name: &str, field: &str| InventoryError::NotANumber {
+            line: line_number,
+            column: name.to_string(),
+            field: field.to_string(),
+        };
+        let number = |name: &str| -> Result<Option<usize>, InventoryError> {
+            field(name).map(|field| field.parse().map_err(|_| not_a_number(name, field))).transpose()
+        };
+        let invalid_plank = |error| InventoryError::InvalidPlank { line: line_number, error };
+
+        let length = number("length")?.ok_or(InventoryError::NoLength { line: line_number })?;
+        let mut plank = Plank::new(length).map_err(invalid_plank)?;
+        if let Some(width) = number("width")? {
+            plank = plank.with_width(width).map_err(invalid_plank)?;
+        }
+        if let Some(lot) = field("lot") {
+            let lot = lot.parse().map_err(|_| not_a_number("lot", lot))?;
+            plank = plank.with_lot(LotId(lot));
+        }
+        let count = number("count")?.unwrap_or(1);
+        if let Some(id) = field("id") {
+            if count != 1 {
+                return Err(InventoryError::CountedBoard {
+                    line: line_number,
+                    id: id.to_string(),
+                    count,
+                });
+            }
+            plank = plank.with_label(id);
+        }
+        plank_heap = plank_heap.try_add_planks(count, plank).map_err(invalid_plank)?;
+    }
+    Ok(plank_heap)
+}
+
+/// Fields of a CSV line, trimmed and unquoted. Quoted fields can't contain commas.
+fn fields(line: &str) -> impl Iterator<Item = &str> {
+    line.split(',').map(|field| field.trim().trim_matches('"').trim())
+}
+
+#[test]
+fn read_csv_should_count_boards_of_every_length() {
+    let csv = "length,count\n2400,3\n\n1200,2\n";
+
+    let plank_heap = read_csv(csv.as_bytes()).unwrap();
+
+    assert_eq!(PlankHeap::new().add(3, 2400).add(2, 1200), plank_heap);
+}
+
+#[test]
+fn read_csv_should_list_physical_boards() {
+    let csv = "\"ID\",\"Length\",\"Lot\",\"Width\"\nB1,2400,1,\nB2,2395,,2\n";
+
+    let plank_heap = read_csv(csv.as_bytes()).unwrap();
+
+    let expected = vec![
+        Plank::new(2400).unwrap().with_lot(LotId(1)).with_label("B1"),
+        Plank::new(2395).unwrap().with_width(2).unwrap().with_label("B2"),
+    ];
+    assert_eq!(Ok(plank_heap), PlankHeap::from_planks(expected));
+}
+
+#[test]
+fn read_csv_should_report_the_invalid_line() {
+    let read = |csv: &str| read_csv(csv.as_bytes()).map_err(|error| error.to_string());
+
+    assert_eq!(Err("line 1: unknown column color".to_string()), read("length,color\n"));
+    assert_eq!(Err("line 1: no length column".to_string()), read("count\n3\n"));
+    assert_eq!(Err("line 3: 2.4m isn't a length".to_string()), read("length,count\n2400,1\n2.4m,1\n"));
+    assert_eq!(Err("line 2: expected 2 fields, got 1".to_string()), read("length,count\n2400\n"));
+    assert_eq!(Err("line 2: board B1 can't be counted 2 times".to_string()), read("id,length,count\nB1,2400,2\n"));
+    assert_eq!(
+        Err(InventoryError::NotANumber {
+            line: 2,
+            column: "lot".to_string(),
+            field: "A".to_string()
+        }),
+        read_csv("length,lot\n2400,A\n".as_bytes())
+    );
+}
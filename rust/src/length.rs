@@ -0,0 +1,319 @@
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use crate::calepinage::{Cutout, Deck, DeckError, Dimension, Junction, Plank, PlankError};
+use crate::messages::{Language, Localize};
+
+// Lengths with a fractional part of a millimeter, like boards of 995.5 mm. The solver
+// counts lengths in units of no particular size, `Length` only converting measures to
+// units and back: a length is a fixed point number of millimeters with one decimal, and
+// decks and planks built from lengths measure their dimensions in tenths of a millimeter,
+// junctions and cuts coming out in the same unit. The limits of the solver stay in units,
+// a plank being at most `Plank::MAX_LENGTH` tenths of a millimeter long.
+//
+// Lengths are given in metric or imperial units, an inch being exactly 25.4 mm.
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Unit {
+    Millimeter,
+    Centimeter,
+    Meter,
+    Inch,
+    Foot,
+}
+
+impl Unit {
+    const ALL: [Unit; 5] = [Unit::Millimeter, Unit::Centimeter, Unit::Meter, Unit::Inch, Unit::Foot];
+
+    pub fn units(&self) -> usize {
+        match self {
+            Unit::Millimeter => Length::UNITS_PER_MM,
+            Unit::Centimeter => 10 * Length::UNITS_PER_MM,
+            Unit::Meter => 1000 * Length::UNITS_PER_MM,
+            Unit::Inch => 254,
+            Unit::Foot => 12 * 254,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Millimeter => "mm",
+            Unit::Centimeter => "cm",
+            Unit::Meter => "m",
+            Unit::Inch => "in",
+            Unit::Foot => "ft",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum LengthError {
+    /// The millimeters are negative or not a number.
+    NotALength(f64),
+    /// The text isn't a whole number of units followed by a unit symbol.
+    Unparsable(String),
+}
+
+impl std::fmt::Display for LengthError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for LengthError {}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+pub struct Length(usize);
+
+impl Length {
+    pub const UNITS_PER_MM: usize = 10;
+
+    pub fn from_units(units: usize) -> Self {
+        Length(units)
+    }
+
+    pub fn of(count: usize, unit: Unit) -> Self {
+        Length(count * unit.units())
+    }
+
+    pub fn mm(mm: usize) -> Self {
+        Length::of(mm, Unit::Millimeter)
+    }
+
+    pub fn cm(cm: usize) -> Self {
+        Length::of(cm, Unit::Centimeter)
+    }
+
+    pub fn m(m: usize) -> Self {
+        Length::of(m, Unit::Meter)
+    }
+
+    pub fn inches(inches: usize) -> Self {
+        Length::of(inches, Unit::Inch)
+    }
+
+    pub fn feet(feet: usize) -> Self {
+        Length::of(feet, Unit::Foot)
+    }
+
+    /// The length rounded to the nearest unit.
+    pub fn from_mm_f64(mm: f64) -> Result<Self, LengthError> {
+        if !mm.is_finite() || mm < 0.0 {
+            return Err(LengthError::NotALength(mm));
+        }
+        Ok(Length((mm * Self::UNITS_PER_MM as f64).round() as usize))
+    }
+
+    pub fn units(&self) -> usize {
+        self.0
+    }
+
+    pub fn to_mm_f64(&self) -> f64 {
+        self.in_unit(Unit::Millimeter)
+    }
+
+    pub fn in_unit(&self, unit: Unit) -> f64 {
+        self.0 as f64 / unit.units() as f64
+    }
+}
+
+impl FromStr for Length {
+    type Err = LengthError;
+
+    /// Parses a decimal number followed by the unit symbol, like "2.4m" or "8ft",
+    /// millimeters when there's no symbol. The length must be a whole number of units.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || LengthError::Unparsable(s.to_string());
+        let s = s.trim();
+        let number_end = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+        let (number, symbol) = s.split_at(number_end);
+        let unit = match symbol.trim() {
+            "" => Unit::Millimeter,
+            symbol => *Unit::ALL.iter().find(|unit| unit.symbol() == symbol).ok_or_else(invalid)?,
+        };
+        let (whole, decimals) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() || decimals.contains('.') {
+            return Err(invalid());
+        }
+        let digits: usize = format!("{}{}", whole, decimals).parse().map_err(|_| invalid())?;
+        let scale = 10usize.checked_pow(decimals.len() as u32).ok_or_else(invalid)?;
+        let units = digits.checked_mul(unit.units()).ok_or_else(invalid)?;
+        if units % scale != 0 {
+            return Err(invalid());
+        }
+        Ok(Length(units / scale))
+    }
+}
+
+impl std::fmt::Display for Length {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (mm, tenths) = (self.0 / Self::UNITS_PER_MM, self.0 % Self::UNITS_PER_MM);
+        if tenths == 0 {
+            write!(f, "{} mm", mm)
+        } else {
+            write!(f, "{}.{} mm", mm, tenths)
+        }
+    }
+}
+
+impl Plank {
+    pub fn of_length(length: Length) -> Result<Self, PlankError> {
+        Plank::new(length.units())
+    }
+
+    pub fn measured_length(&self) -> Length {
+        Length::from_units(self.length)
+    }
+}
+
+impl Deck {
+    /// A physical deck whose dimensions are lengths, see `Deck::physical`.
+    pub fn of_lengths(length: Length, width: Length, board_width: Length) -> Result<Self, DeckError> {
+        Deck::physical(length.units(), width.units(), board_width.units())
+    }
+
+    pub fn builder() -> DeckBuilder {
+        DeckBuilder::default()
+    }
+}
+
+/// Collects the dimensions of a deck, checked together by `build`. The width counts rows,
+/// like `Deck::new`, unless the boards width is given.
+#[derive(Debug, Clone, Default)]
+pub struct DeckBuilder {
+    length: Option<Length>,
+    width: Option<Length>,
+    board_width: Option<Length>,
+    gap: Length,
+    cutouts: Vec<Cutout>,
+}
+
+impl DeckBuilder {
+    pub fn length(mut self, length: Length) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn board_width(mut self, board_width: Length) -> Self {
+        self.board_width = Some(board_width);
+        self
+    }
+
+    /// Expansion gap left along the walls, at both ends of the length and, for physical
+    /// decks, of the width. None by default.
+    pub fn gap(mut self, gap: Length) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    pub fn cutout(mut self, cutout: Cutout) -> Self {
+        self.cutouts.push(cutout);
+        self
+    }
+
+    pub fn build(self) -> Result<Deck, DeckError> {
+        let length = self.length.ok_or(DeckError::MissingDimension(Dimension::Length))?;
+        let width = self.width.ok_or(DeckError::MissingDimension(Dimension::Width))?;
+        let gap = self.gap.units();
+        let without_gaps = |length: Length, dimension| match length.units().checked_sub(2 * gap) {
+            Some(laid) if laid > 0 || gap == 0 => Ok(laid),
+            _ => Err(DeckError::GapTooWide { gap, dimension }),
+        };
+        let laid_length = without_gaps(length, Dimension::Length)?;
+        let deck = match self.board_width {
+            Some(board_width) => Deck::physical(laid_length, without_gaps(width, Dimension::Width)?, board_width.units()),
+            None => Deck::new(laid_length, width.units()),
+        }?;
+        Ok(self.cutouts.into_iter().fold(deck, Deck::with_cutout))
+    }
+}
+
+impl Junction {
+    pub fn position(&self) -> Length {
+        Length::from_units(self.coordinate())
+    }
+}
+
+#[test]
+fn length_should_parse_tenths_of_millimeters() {
+    assert_eq!(Ok(Length::from_units(23955)), "2395.5".parse::<Length>());
+    assert_eq!(Ok(Length::mm(2400)), "2400".parse::<Length>());
+    assert!("2395.55".parse::<Length>().is_err());
+    assert!(".5".parse::<Length>().is_err());
+    assert!("-1".parse::<Length>().is_err());
+}
+
+#[test]
+fn length_should_parse_units() {
+    assert_eq!(Ok(Length::mm(2400)), "2.4m".parse::<Length>());
+    assert_eq!(Ok(Length::mm(2400)), "240 cm".parse::<Length>());
+    assert_eq!(Ok(Length::mm(2400)), "2400mm".parse::<Length>());
+    assert_eq!(Ok(Length::inches(96)), "8ft".parse::<Length>());
+    assert_eq!(Ok(Length::from_units(1397)), "5.5in".parse::<Length>());
+    assert_eq!(Err(LengthError::Unparsable("8yd".to_string())), "8yd".parse::<Length>());
+    assert!("1.1.1m".parse::<Length>().is_err());
+}
+
+#[test]
+fn length_should_convert_units() {
+    assert_eq!(Length::feet(8), Length::inches(96));
+    assert_eq!(2438.4, Length::feet(8).to_mm_f64());
+    assert_eq!(2.4, Length::cm(240).in_unit(Unit::Meter));
+}
+
+#[test]
+fn length_should_display_its_decimal_only_when_needed() {
+    assert_eq!("2395.5 mm", Length::from_units(23955).to_string());
+    assert_eq!("2400 mm", Length::mm(2400).to_string());
+}
+
+#[test]
+fn length_should_round_millimeters_to_the_nearest_unit() {
+    assert_eq!(Ok(Length::from_units(23955)), Length::from_mm_f64(2395.54));
+    assert_eq!(2395.5, Length::from_units(23955).to_mm_f64());
+    assert_eq!(Err(LengthError::NotALength(-1.0)), Length::from_mm_f64(-1.0));
+}
+
+#[test]
+fn deck_builder_should_leave_the_gaps_along_the_walls() {
+    let deck = Deck::builder()
+        .length(Length::m(6))
+        .width(Length::m(4))
+        .board_width(Length::mm(145))
+        .gap(Length::mm(5))
+        .build()
+        .unwrap();
+
+    assert_eq!(Length::mm(5990).units(), deck.length);
+    assert_eq!(Length::mm(3990).units(), deck.width);
+    assert_eq!(Some(Length::mm(145).units()), deck.board_width);
+}
+
+#[test]
+fn deck_builder_should_check_every_dimension() {
+    let builder = Deck::builder().length(Length::m(6)).width(Length::m(4));
+
+    assert_eq!(Err(DeckError::MissingDimension(Dimension::Width)), Deck::builder().length(Length::m(6)).build().map(|_| ()));
+    assert_eq!(
+        Err(DeckError::GapTooWide { gap: Length::m(2).units(), dimension: Dimension::Width }),
+        builder.clone().board_width(Length::mm(145)).gap(Length::m(2)).build().map(|_| ())
+    );
+    assert_eq!(
+        Err(DeckError::ZeroDimension(Dimension::BoardWidth)),
+        builder.board_width(Length::mm(0)).build().map(|_| ())
+    );
+}
+
+#[test]
+fn plank_of_length_should_keep_the_fractional_part() {
+    let plank = Plank::of_length("995.5".parse().unwrap()).unwrap();
+
+    assert_eq!(9955, plank.length);
+    assert_eq!("995.5 mm", plank.measured_length().to_string());
+    assert!(Plank::of_length(Length::m(2)).is_err());
+}
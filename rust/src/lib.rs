@@ -1 +1,25 @@
+pub mod bom;
 pub mod calepinage;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod cut_list;
+pub mod diagonal;
+pub mod gaps;
+pub mod hardware;
+pub mod herringbone;
+pub mod inventory;
+pub mod length;
+pub mod messages;
+pub mod orientation;
+pub mod patterns;
+pub mod project;
+pub mod purchase;
+pub mod render;
+pub mod report;
+pub mod scenario;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod stairs;
+pub mod stats;
+pub mod substructure;
+pub mod validation;
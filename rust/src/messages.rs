@@ -0,0 +1,635 @@
+use std::fmt::{Display, Formatter, Result};
+
+use crate::calepinage::{CalepinageError, DeckError, Dimension, OutlineError, ParseHeapError, PlankError, SlopeRule};
+#[cfg(feature = "config")]
+use crate::config::ConfigError;
+use crate::cut_list::{CutGroup, MiterGroup};
+#[cfg(test)]
+use crate::calepinage::Orientation;
+use crate::diagonal::DiagonalError;
+use crate::gaps::InvalidMoistureRange;
+use crate::herringbone::HerringboneError;
+use crate::inventory::InventoryError;
+use crate::length::LengthError;
+use crate::orientation::OrientationError;
+use crate::patterns::{PatternError, Preset};
+use crate::project::ZoneError;
+#[cfg(feature = "image")]
+use crate::render::RenderError;
+use crate::substructure::FramingError;
+
+// Message catalog: the errors and the report phrases in every language. Errors display in
+// English, `in_language` displays them in another language.
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Language {
+    #[default]
+    English,
+    French,
+}
+
+impl std::str::FromStr for Language {
+    type Err = String;
+
+    /// Parses a language code like "en" or "fr".
+    fn from_str(code: &str) -> std::result::Result<Self, Self::Err> {
+        match code.to_lowercase().as_str() {
+            "en" | "english" => Ok(Language::English),
+            "fr" | "french" | "français" => Ok(Language::French),
+            _ => Err(format!("unknown language {}", code)),
+        }
+    }
+}
+
+/// A message written in any language of the catalog.
+pub trait Localize {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result;
+
+    /// The message, displayed in the language.
+    fn in_language(&self, language: Language) -> Localized<'_, Self> {
+        Localized { message: self, language }
+    }
+}
+
+pub struct Localized<'a, T: ?Sized> {
+    message: &'a T,
+    language: Language,
+}
+
+impl<T: Localize + ?Sized> Display for Localized<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.message.write_in(f, self.language)
+    }
+}
+
+impl Localize for Dimension {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        let name = match (language, self) {
+            (Language::English, Dimension::Length) => "length",
+            (Language::English, Dimension::Width) => "width",
+            (Language::English, Dimension::BoardWidth) => "board width",
+            (Language::English, Dimension::Thickness) => "thickness",
+            (Language::French, Dimension::Length) => "longueur",
+            (Language::French, Dimension::Width) => "largeur",
+            (Language::French, Dimension::BoardWidth) => "largeur de lame",
+            (Language::French, Dimension::Thickness) => "épaisseur",
+        };
+        f.write_str(name)
+    }
+}
+
+impl Localize for LengthError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (Language::English, LengthError::NotALength(mm)) => write!(f, "{} mm isn't a length", mm),
+            (Language::English, LengthError::Unparsable(text)) => {
+                write!(f, "{} isn't a length in tenths of millimeters", text)
+            }
+            (Language::French, LengthError::NotALength(mm)) => write!(f, "{} mm n'est pas une longueur", mm),
+            (Language::French, LengthError::Unparsable(text)) => {
+                write!(f, "{} n'est pas une longueur en dixièmes de millimètre", text)
+            }
+        }
+    }
+}
+
+impl Localize for OutlineError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (Language::English, OutlineError::VertexCount(count)) => {
+                write!(f, "an outline needs an even number of vertices, at least 4, not {}", count)
+            }
+            (Language::English, OutlineError::SlantedEdge { from, to }) => {
+                write!(f, "outline edge from {:?} to {:?} must be either horizontal or vertical", from, to)
+            }
+            (Language::English, OutlineError::NonAlternatingEdges) => {
+                write!(f, "outline edges must alternate horizontal and vertical")
+            }
+            (Language::French, OutlineError::VertexCount(count)) => {
+                write!(f, "un contour a un nombre pair de sommets, au moins 4, et non {}", count)
+            }
+            (Language::French, OutlineError::SlantedEdge { from, to }) => {
+                write!(f, "le côté du contour de {:?} à {:?} doit être horizontal ou vertical", from, to)
+            }
+            (Language::French, OutlineError::NonAlternatingEdges) => {
+                write!(f, "les côtés du contour doivent être tour à tour horizontaux et verticaux")
+            }
+        }
+    }
+}
+
+impl Localize for DeckError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (_, DeckError::Outline(error)) => error.write_in(f, language),
+            (Language::English, DeckError::ZeroDimension(dimension)) => {
+                write!(f, "a deck can't have a zero {}", dimension.in_language(language))
+            }
+            (Language::English, DeckError::MissingDimension(dimension)) => {
+                write!(f, "the deck {} is missing", dimension.in_language(language))
+            }
+            (Language::English, DeckError::GapTooWide { gap, dimension }) => {
+                write!(f, "a gap of {} at both ends leaves no deck {}", gap, dimension.in_language(language))
+            }
+            (Language::English, DeckError::TooLong { length, max_length }) => {
+                write!(f, "a deck of length {} is longer than the max length {}", length, max_length)
+            }
+            (Language::French, DeckError::ZeroDimension(dimension)) => {
+                write!(f, "une terrasse ne peut pas avoir une {} nulle", dimension.in_language(language))
+            }
+            (Language::French, DeckError::MissingDimension(dimension)) => {
+                write!(f, "la {} de la terrasse manque", dimension.in_language(language))
+            }
+            (Language::French, DeckError::GapTooWide { gap, dimension }) => write!(
+                f,
+                "un jeu de {} à chaque extrémité ne laisse aucune {} de terrasse",
+                gap,
+                dimension.in_language(language)
+            ),
+            (Language::French, DeckError::TooLong { length, max_length }) => {
+                write!(f, "une terrasse de longueur {} dépasse la longueur maximale {}", length, max_length)
+            }
+        }
+    }
+}
+
+impl Localize for PlankError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (Language::English, PlankError::ZeroDimension(dimension)) => {
+                write!(f, "a plank can't have a zero {}", dimension.in_language(language))
+            }
+            (Language::English, PlankError::TooLong { length, max_length }) => {
+                write!(f, "a plank of length {} is longer than the max length {}", length, max_length)
+            }
+            (Language::English, PlankError::DefectOffPlank { defect, length }) => {
+                write!(f, "a defect at {} is off a plank of length {}", defect, length)
+            }
+            (Language::English, PlankError::MinLengthOverLength { min_length, length }) => {
+                write!(f, "a plank of length {} can't be trimmed to {}", length, min_length)
+            }
+            (Language::English, PlankError::TooManyPlanks { count, length }) => {
+                write!(f, "{} planks of length {} are too many to count", count, length)
+            }
+            (Language::French, PlankError::ZeroDimension(dimension)) => {
+                write!(f, "une lame ne peut pas avoir une {} nulle", dimension.in_language(language))
+            }
+            (Language::French, PlankError::TooLong { length, max_length }) => {
+                write!(f, "une lame de longueur {} dépasse la longueur maximale {}", length, max_length)
+            }
+            (Language::French, PlankError::DefectOffPlank { defect, length }) => {
+                write!(f, "un défaut à {} est hors d'une lame de longueur {}", defect, length)
+            }
+            (Language::French, PlankError::MinLengthOverLength { min_length, length }) => {
+                write!(f, "une lame de longueur {} ne peut pas être recoupée à {}", length, min_length)
+            }
+            (Language::French, PlankError::TooManyPlanks { count, length }) => {
+                write!(f, "{} lames de longueur {} sont trop nombreuses pour être comptées", count, length)
+            }
+        }
+    }
+}
+
+impl Localize for ParseHeapError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (_, ParseHeapError::InvalidPlank(error)) => error.write_in(f, language),
+            (Language::English, ParseHeapError::BadCount(term)) => {
+                write!(f, "{} has no count of planks, like 6x2400", term)
+            }
+            (Language::English, ParseHeapError::BadLength(term)) => {
+                write!(f, "{} has no length of planks, like 6x2400", term)
+            }
+            (Language::English, ParseHeapError::Overflow(term)) => {
+                write!(f, "{} counts more planks than a heap can", term)
+            }
+            (Language::French, ParseHeapError::BadCount(term)) => {
+                write!(f, "{} n'a pas de nombre de lames, comme 6x2400", term)
+            }
+            (Language::French, ParseHeapError::BadLength(term)) => {
+                write!(f, "{} n'a pas de longueur de lames, comme 6x2400", term)
+            }
+            (Language::French, ParseHeapError::Overflow(term)) => {
+                write!(f, "{} compte plus de lames qu'un tas ne peut en compter", term)
+            }
+        }
+    }
+}
+
+impl Localize for CalepinageError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (Language::English, CalepinageError::NotEnoughPlanks) => write!(f, "not enough planks to lay the deck"),
+            (
+                Language::English,
+                CalepinageError::OnlyUnusablePlanksRemaining {
+                    row,
+                    needed_length,
+                    remaining_lengths,
+                },
+            ) => write!(
+                f,
+                "row {} still needs {} but the remaining planks {:?} can't be laid",
+                row, needed_length, remaining_lengths
+            ),
+            (Language::English, CalepinageError::DeckTooSmallForBorder) => {
+                write!(f, "the deck is too small for its border")
+            }
+            (Language::English, CalepinageError::UnknownBoardWidth) => {
+                write!(f, "only decks of known board width can have a border")
+            }
+            (Language::English, CalepinageError::UncoveredDeckWidth(uncovered)) => {
+                write!(f, "{} of the deck width is left uncovered", uncovered)
+            }
+            (Language::English, CalepinageError::MixedThicknesses(thicknesses)) => {
+                write!(f, "planks of different thicknesses {:?} can't be laid together", thicknesses)
+            }
+            (Language::English, CalepinageError::AgainstSlope { rule, .. }) => {
+                let way = if *rule == SlopeRule::Parallel { "across" } else { "along" };
+                write!(f, "boards along the deck length would run {} its slope", way)
+            }
+            (Language::English, CalepinageError::OutOfBudget) => {
+                write!(f, "the search ran out of time or was cancelled before laying the deck")
+            }
+            (Language::French, CalepinageError::NotEnoughPlanks) => {
+                write!(f, "pas assez de lames pour poser la terrasse")
+            }
+            (
+                Language::French,
+                CalepinageError::OnlyUnusablePlanksRemaining {
+                    row,
+                    needed_length,
+                    remaining_lengths,
+                },
+            ) => write!(
+                f,
+                "il manque encore {} à la rangée {} mais les lames restantes {:?} ne peuvent pas être posées",
+                needed_length, row, remaining_lengths
+            ),
+            (Language::French, CalepinageError::DeckTooSmallForBorder) => {
+                write!(f, "la terrasse est trop petite pour sa bordure")
+            }
+            (Language::French, CalepinageError::UnknownBoardWidth) => {
+                write!(f, "seules les terrasses dont la largeur des lames est connue peuvent avoir une bordure")
+            }
+            (Language::French, CalepinageError::UncoveredDeckWidth(uncovered)) => {
+                write!(f, "{} de la largeur de la terrasse reste à couvrir", uncovered)
+            }
+            (Language::French, CalepinageError::MixedThicknesses(thicknesses)) => {
+                write!(f, "des lames d'épaisseurs différentes {:?} ne peuvent pas être posées ensemble", thicknesses)
+            }
+            (Language::French, CalepinageError::AgainstSlope { rule, .. }) => {
+                let way = if *rule == SlopeRule::Parallel { "en travers de" } else { "dans le sens de" };
+                write!(f, "des lames dans la longueur de la terrasse seraient posées {} sa pente", way)
+            }
+            (Language::French, CalepinageError::OutOfBudget) => {
+                write!(f, "la recherche a manqué de temps ou a été annulée avant de poser la terrasse")
+            }
+        }
+    }
+}
+
+impl Localize for ZoneError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        let error = self.error.in_language(language);
+        match language {
+            Language::English => write!(f, "zone {} can't be laid: {}", self.zone, error),
+            Language::French => write!(f, "la zone {} ne peut pas être posée : {}", self.zone, error),
+        }
+    }
+}
+
+impl Localize for FramingError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (Language::English, FramingError::ZeroJoistSpacing) => write!(f, "joists can't have a zero spacing"),
+            (Language::English, FramingError::ZeroJoistSpan) => write!(f, "joists can't have a zero span"),
+            (Language::English, FramingError::UnknownBoardWidth) => {
+                write!(f, "only decks of known board width can be framed")
+            }
+            (Language::French, FramingError::ZeroJoistSpacing) => {
+                write!(f, "les solives ne peuvent pas avoir un entraxe nul")
+            }
+            (Language::French, FramingError::ZeroJoistSpan) => write!(f, "les solives ne peuvent pas avoir une portée nulle"),
+            (Language::French, FramingError::UnknownBoardWidth) => {
+                write!(f, "seules les terrasses dont la largeur des lames est connue peuvent être charpentées")
+            }
+        }
+    }
+}
+
+impl Localize for InvalidMoistureRange {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        let (min, max) = (self.min, self.max);
+        match language {
+            Language::English => write!(f, "the moisture range {} to {} isn't a range of percentages", min, max),
+            Language::French => write!(f, "l'humidité de {} à {} n'est pas une plage de pourcentages", min, max),
+        }
+    }
+}
+
+impl Localize for HerringboneError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (Language::English, HerringboneError::PlankNotLongerThanWide) => {
+                write!(f, "a herringbone plank must be longer than wide")
+            }
+            (Language::French, HerringboneError::PlankNotLongerThanWide) => {
+                write!(f, "une lame en chevrons doit être plus longue que large")
+            }
+        }
+    }
+}
+
+impl Localize for DiagonalError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (Language::English, DiagonalError::ZeroBoardWidth) => {
+                write!(f, "a diagonal layout needs a non zero board width")
+            }
+            (Language::French, DiagonalError::ZeroBoardWidth) => {
+                write!(f, "une pose en diagonale a besoin d'une largeur de lame non nulle")
+            }
+        }
+    }
+}
+
+impl Localize for OrientationError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (_, OrientationError::Deck(error)) => error.write_in(f, language),
+            (Language::English, OrientationError::NotTurnable) => {
+                write!(f, "only rectangular decks of known board width can be turned")
+            }
+            (Language::French, OrientationError::NotTurnable) => write!(
+                f,
+                "seules les terrasses rectangulaires dont la largeur des lames est connue peuvent être tournées"
+            ),
+        }
+    }
+}
+
+impl Localize for PatternError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        let names: Vec<&str> = Preset::ALL.iter().map(Preset::name).collect();
+        match (language, self) {
+            (Language::English, PatternError::UnknownPreset(name)) => {
+                write!(f, "unknown pattern {}, expected one of {}", name, names.join(", "))
+            }
+            (Language::French, PatternError::UnknownPreset(name)) => {
+                write!(f, "motif {} inconnu, parmi {}", name, names.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+impl Localize for RenderError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (_, RenderError::Encoding(message)) => f.write_str(message),
+            (Language::English, RenderError::ImageSize { width, height }) => {
+                write!(f, "can't draw an image of {}x{}", width, height)
+            }
+            (Language::French, RenderError::ImageSize { width, height }) => {
+                write!(f, "impossible de dessiner une image de {}x{}", width, height)
+            }
+        }
+    }
+}
+
+impl Localize for InventoryError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (_, InventoryError::Unreadable(message)) => f.write_str(message),
+            (Language::English, InventoryError::UnknownColumn { line, column }) => {
+                write!(f, "line {}: unknown column {}", line, column)
+            }
+            (Language::English, InventoryError::NoLengthColumn { line }) => {
+                write!(f, "line {}: no length column", line)
+            }
+            (Language::English, InventoryError::FieldCount { line, expected, found }) => {
+                write!(f, "line {}: expected {} fields, got {}", line, expected, found)
+            }
+            (Language::English, InventoryError::NotANumber { line, column, field }) => {
+                write!(f, "line {}: {} isn't a {}", line, field, column)
+            }
+            (Language::English, InventoryError::NoLength { line }) => write!(f, "line {}: no length", line),
+            (Language::English, InventoryError::InvalidPlank { line, error }) => {
+                write!(f, "line {}: {}", line, error.in_language(language))
+            }
+            (Language::English, InventoryError::CountedBoard { line, id, count }) => {
+                write!(f, "line {}: board {} can't be counted {} times", line, id, count)
+            }
+            (Language::French, InventoryError::UnknownColumn { line, column }) => {
+                write!(f, "ligne {} : colonne {} inconnue", line, column)
+            }
+            (Language::French, InventoryError::NoLengthColumn { line }) => {
+                write!(f, "ligne {} : pas de colonne length", line)
+            }
+            (Language::French, InventoryError::FieldCount { line, expected, found }) => {
+                write!(f, "ligne {} : {} champs attendus, {} trouvés", line, expected, found)
+            }
+            (Language::French, InventoryError::NotANumber { line, column, field }) => {
+                write!(f, "ligne {} : {} n'est pas un nombre de la colonne {}", line, field, column)
+            }
+            (Language::French, InventoryError::NoLength { line }) => write!(f, "ligne {} : pas de longueur", line),
+            (Language::French, InventoryError::InvalidPlank { line, error }) => {
+                write!(f, "ligne {} : {}", line, error.in_language(language))
+            }
+            (Language::French, InventoryError::CountedBoard { line, id, count }) => {
+                write!(f, "ligne {} : la lame {} ne peut pas être comptée {} fois", line, id, count)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl Localize for ConfigError {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        match (language, self) {
+            (_, ConfigError::Unreadable(message)) | (_, ConfigError::Syntax(message)) => f.write_str(message),
+            (_, ConfigError::Deck(error)) => error.write_in(f, language),
+            (_, ConfigError::Plank(error)) => error.write_in(f, language),
+            (_, ConfigError::Moisture(error)) => error.write_in(f, language),
+            (_, ConfigError::Substructure(error)) => error.write_in(f, language),
+            (_, ConfigError::Pattern(error)) => error.write_in(f, language),
+            (_, ConfigError::Inventory(error)) => error.write_in(f, language),
+            (_, ConfigError::InFile { path, error }) => {
+                write!(f, "{}: {}", path.display(), error.in_language(language))
+            }
+            (Language::English, ConfigError::OutlineWithDimensions) => {
+                write!(f, "a deck with an outline has neither length, width nor board_width")
+            }
+            (Language::English, ConfigError::MissingDimensions) => {
+                write!(f, "a deck needs a length and a width, or an outline")
+            }
+            (Language::French, ConfigError::OutlineWithDimensions) => {
+                write!(f, "une terrasse à contour n'a ni length, ni width, ni board_width")
+            }
+            (Language::French, ConfigError::MissingDimensions) => {
+                write!(f, "une terrasse a besoin d'une longueur et d'une largeur, ou d'un contour")
+            }
+        }
+    }
+}
+
+impl Localize for CutGroup {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        let (count, length, at) = (self.count, self.plank_length, self.at);
+        match language {
+            Language::English => {
+                let boards = if count > 1 { "boards" } else { "board" };
+                write!(f, "cut {} {} of {} at {}", count, boards, length, at)
+            }
+            Language::French => {
+                let boards = if count > 1 { "lames" } else { "lame" };
+                write!(f, "couper {} {} de {} à {}", count, boards, length, at)
+            }
+        }
+    }
+}
+
+impl Localize for MiterGroup {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        let (count, length) = (self.count, self.length);
+        match language {
+            Language::English => {
+                let boards = if count > 1 { "boards" } else { "board" };
+                let ends = if self.ends > 1 { "both ends" } else { "one end" };
+                write!(f, "miter {} {} of {} at {}, long point to long point", count, boards, length, ends)
+            }
+            Language::French => {
+                let boards = if count > 1 { "lames" } else { "lame" };
+                let ends = if self.ends > 1 { "aux deux bouts" } else { "à un bout" };
+                write!(f, "couper d'onglet {} {} de {} {}, de pointe à pointe", count, boards, length, ends)
+            }
+        }
+    }
+}
+
+/// Phrases of the plan report, see `report`.
+pub(crate) enum ReportPhrase<'a> {
+    DeckSection,
+    RowsSection,
+    CutsSection,
+    WasteSection,
+    LeftoversSection,
+    DeckRows { length: usize, rows: usize },
+    DeckSize { length: usize, width: usize, board_width: usize },
+    Cutouts(usize),
+    Rip(usize),
+    Row { row: usize, boards: &'a str },
+    Border { side: usize, boards: &'a str },
+    Breaker { at: usize, boards: &'a str },
+    /// Boards of a line starting away from the deck edge.
+    From { boards: &'a str, start: usize },
+    NoCuts,
+    BoardsUsed { boards: usize, waste_percentage: f64 },
+    Coverage(f64),
+    Junctions(usize),
+    StaggeredJunctions { junctions: usize, stagger: f64 },
+    BoardsLeftOver(usize),
+    Offcuts(&'a str),
+}
+
+impl Localize for ReportPhrase<'_> {
+    fn write_in(&self, f: &mut Formatter<'_>, language: Language) -> Result {
+        use ReportPhrase::*;
+
+        match language {
+            Language::English => match self {
+                DeckSection => write!(f, "Deck"),
+                RowsSection => write!(f, "Rows"),
+                CutsSection => write!(f, "Cuts"),
+                WasteSection => write!(f, "Waste"),
+                LeftoversSection => write!(f, "Leftovers"),
+                DeckRows { length, rows } => write!(f, "{} long, {} rows", length, rows),
+                DeckSize { length, width, board_width } => {
+                    write!(f, "{} long, {} wide, boards {} wide", length, width, board_width)
+                }
+                Cutouts(count) => write!(f, "{} cutouts", count),
+                Rip(width) => write!(f, "last row ripped to {}", width),
+                Row { row, boards } => write!(f, "row {}: {}", row, boards),
+                Border { side, boards } => {
+                    let sides = ["bottom", "right", "top", "left"];
+                    write!(f, "{} border: {}", sides[side % 4], boards)
+                }
+                Breaker { at, boards } => write!(f, "breaker at {}: {}", at, boards),
+                From { boards, start } => write!(f, "{} from {}", boards, start),
+                NoCuts => write!(f, "no cuts"),
+                BoardsUsed { boards, waste_percentage } => {
+                    write!(f, "{} boards used, {:.1}% wasted", boards, waste_percentage)
+                }
+                Coverage(coverage) => write!(f, "{:.1}% of the deck covered", coverage * 100.0),
+                Junctions(junctions) => write!(f, "{} junctions", junctions),
+                StaggeredJunctions { junctions, stagger } => {
+                    write!(f, "{} junctions, {:.0} apart on average", junctions, stagger)
+                }
+                BoardsLeftOver(boards) => write!(f, "{} boards left over", boards),
+                Offcuts(offcuts) => write!(f, "offcuts: {}", offcuts),
+            },
+            Language::French => match self {
+                DeckSection => write!(f, "Terrasse"),
+                RowsSection => write!(f, "Rangées"),
+                CutsSection => write!(f, "Coupes"),
+                WasteSection => write!(f, "Pertes"),
+                LeftoversSection => write!(f, "Restes"),
+                DeckRows { length, rows } => write!(f, "{} de long, {} rangées", length, rows),
+                DeckSize { length, width, board_width } => {
+                    write!(f, "{} de long, {} de large, lames de {} de large", length, width, board_width)
+                }
+                Cutouts(count) => write!(f, "{} découpes", count),
+                Rip(width) => write!(f, "dernière rangée délignée à {}", width),
+                Row { row, boards } => write!(f, "rangée {} : {}", row, boards),
+                Border { side, boards } => {
+                    let sides = ["bas", "droite", "haut", "gauche"];
+                    write!(f, "bordure {} : {}", sides[side % 4], boards)
+                }
+                Breaker { at, boards } => write!(f, "lame de rupture à {} : {}", at, boards),
+                From { boards, start } => write!(f, "{} à partir de {}", boards, start),
+                NoCuts => write!(f, "aucune coupe"),
+                BoardsUsed { boards, waste_percentage } => {
+                    write!(f, "{} lames utilisées, {:.1} % de pertes", boards, waste_percentage)
+                }
+                Coverage(coverage) => write!(f, "{:.1} % de la terrasse couverte", coverage * 100.0),
+                Junctions(junctions) => write!(f, "{} joints", junctions),
+                StaggeredJunctions { junctions, stagger } => {
+                    write!(f, "{} joints, espacés de {:.0} en moyenne", junctions, stagger)
+                }
+                BoardsLeftOver(boards) => write!(f, "{} lames restantes", boards),
+                Offcuts(offcuts) => write!(f, "chutes : {}", offcuts),
+            },
+        }
+    }
+}
+
+#[test]
+fn errors_should_display_in_every_language() {
+    let error = ZoneError {
+        zone: 1,
+        error: CalepinageError::NotEnoughPlanks,
+    };
+
+    assert_eq!("zone 1 can't be laid: not enough planks to lay the deck", error.to_string());
+    assert_eq!(
+        "la zone 1 ne peut pas être posée : pas assez de lames pour poser la terrasse",
+        error.in_language(Language::French).to_string()
+    );
+    assert_eq!(
+        "une lame ne peut pas avoir une épaisseur nulle",
+        PlankError::ZeroDimension(Dimension::Thickness).in_language(Language::French).to_string()
+    );
+    let against_slope = CalepinageError::AgainstSlope {
+        slope: Orientation::AlongWidth,
+        rule: SlopeRule::Parallel,
+    };
+    assert_eq!("boards along the deck length would run across its slope", against_slope.to_string());
+    let counted = InventoryError::CountedBoard {
+        line: 2,
+        id: "B1".to_string(),
+        count: 3,
+    };
+    assert_eq!("ligne 2 : la lame B1 ne peut pas être comptée 3 fois", counted.in_language(Language::French).to_string());
+    assert_eq!(Ok(Language::French), "FR".parse());
+}
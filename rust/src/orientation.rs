@@ -0,0 +1,166 @@
+use crate::calepinage::{
+    calepine_with_options, Calepinage, CalepinageError, CalepinageOptions, Deck, DeckError, Orientation, PlankHeap,
+    Post, PostEdge,
+};
+use crate::messages::{Language, Localize};
+#[cfg(test)]
+use crate::calepinage::SlopeRule;
+#[cfg(test)]
+use crate::cut_list::notch_schedule;
+
+// Boards can run along the deck length, or along its width on the same deck turned by 90°.
+
+#[derive(Debug)]
+pub struct OrientationComparison {
+    pub along_length: Result<Calepinage, CalepinageError>,
+    /// Calepinage of the turned deck, its lines running along the deck width.
+    pub along_width: Result<Calepinage, CalepinageError>,
+    /// The deck turned by 90°, its slope and its posts turned with it.
+    pub turned: Deck,
+    /// Orientation wasting less, then cutting less, none when both fail.
+    pub best: Option<Orientation>,
+}
+
+impl OrientationComparison {
+    pub fn best_calepinage(&self) -> Option<&Calepinage> {
+        match self.best {
+            Some(Orientation::AlongLength) => self.along_length.as_ref().ok(),
+            Some(Orientation::AlongWidth) => self.along_width.as_ref().ok(),
+            None => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OrientationError {
+    /// Decks of unknown board width, with cutouts or with an outline can't be turned.
+    NotTurnable,
+    /// The turned deck can't be built, its new length being too long.
+    Deck(DeckError),
+}
+
+impl std::fmt::Display for OrientationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for OrientationError {}
+
+/// Lays the deck in both orientations. Only rectangular decks of known board width can be
+/// turned.
+pub fn calepine_best_orientation(
+    plank_heap: PlankHeap,
+    deck: Deck,
+    options: CalepinageOptions,
+) -> Result<OrientationComparison, OrientationError> {
+    let board_width = match deck.board_width {
+        Some(board_width) if deck.cutouts.is_empty() && deck.outline.is_none() => board_width,
+        _ => return Err(OrientationError::NotTurnable),
+    };
+    let mut turned = Deck::physical(deck.width, deck.length, board_width).map_err(OrientationError::Deck)?;
+    // the slope turns with the deck, boards breaking the slope rule being rejected
+    turned.slope = deck.slope.map(|slope| match slope {
+        Orientation::AlongLength => Orientation::AlongWidth,
+        Orientation::AlongWidth => Orientation::AlongLength,
+    });
+    // the first row edge becomes the start of the rows, positions along the edges staying
+    turned.posts = deck
+        .posts
+        .iter()
+        .map(|post| Post {
+            edge: match post.edge {
+                PostEdge::First => PostEdge::Start,
+                PostEdge::Last => PostEdge::End,
+                PostEdge::Start => PostEdge::First,
+                PostEdge::End => PostEdge::Last,
+            },
+            ..post.clone()
+        })
+        .collect();
+
+    let along_length = calepine_with_options(plank_heap.clone(), deck, options.clone());
+    let along_width = calepine_with_options(plank_heap, turned.clone(), options.clone());
+    let score = |calepinage: &Calepinage| (calepinage.waste(&options), calepinage.cuts.len());
+    let best = match (&along_length, &along_width) {
+        (Ok(lhs), Ok(rhs)) if score(rhs) < score(lhs) => Some(Orientation::AlongWidth),
+        (Ok(_), _) => Some(Orientation::AlongLength),
+        (Err(_), Ok(_)) => Some(Orientation::AlongWidth),
+        (Err(_), Err(_)) => None,
+    };
+
+    Ok(OrientationComparison {
+        along_length,
+        along_width,
+        turned,
+        best,
+    })
+}
+
+#[test]
+fn should_lay_boards_along_the_width_when_they_fit_better() {
+    let deck = Deck::physical(600, 400, 100).unwrap();
+    let plank_heap = PlankHeap::new().add(6, 400);
+
+    let comparison = calepine_best_orientation(plank_heap, deck, CalepinageOptions::default()).unwrap();
+
+    assert!(comparison.along_length.is_err());
+    assert_eq!(Some(Orientation::AlongWidth), comparison.best);
+    assert_eq!(6, comparison.best_calepinage().unwrap().lines.len());
+}
+
+#[test]
+fn should_prefer_the_orientation_with_less_waste() {
+    let deck = Deck::physical(400, 600, 100).unwrap();
+    let plank_heap = PlankHeap::new().add(10, 600);
+    let options = CalepinageOptions::default().with_allow_cutting(true);
+
+    let comparison = calepine_best_orientation(plank_heap, deck, options).unwrap();
+
+    assert!(comparison.along_length.is_ok());
+    assert_eq!(Some(Orientation::AlongWidth), comparison.best);
+    assert_eq!(0, comparison.best_calepinage().unwrap().cuts.len());
+}
+
+#[test]
+fn should_lay_boards_the_way_the_slope_rule_wants() {
+    let deck = Deck::physical(400, 600, 100).unwrap().with_slope(Orientation::AlongWidth);
+    let plank_heap = PlankHeap::new().add(10, 600);
+    let options = CalepinageOptions::default()
+        .with_allow_cutting(true)
+        .with_slope_rule(SlopeRule::Perpendicular);
+
+    let comparison = calepine_best_orientation(plank_heap, deck, options).unwrap();
+
+    assert!(comparison.along_width.is_err());
+    assert_eq!(Some(Orientation::AlongLength), comparison.best);
+}
+
+#[test]
+fn posts_should_turn_with_the_deck() {
+    let post = Post {
+        edge: PostEdge::First,
+        at: 300,
+        width: 100,
+        depth: 60,
+    };
+    let deck = Deck::physical(600, 400, 100).unwrap().with_post(post.clone());
+    let plank_heap = PlankHeap::new().add(6, 400);
+
+    let comparison = calepine_best_orientation(plank_heap, deck, CalepinageOptions::default()).unwrap();
+
+    assert_eq!(vec![Post { edge: PostEdge::Start, ..post }], comparison.turned.posts);
+    let along_width = comparison.along_width.unwrap();
+    let notches = notch_schedule(&along_width, &comparison.turned, &CalepinageOptions::default());
+    assert_eq!(vec![2, 3], notches.iter().map(|notch| notch.row).collect::<Vec<_>>());
+}
+
+#[test]
+fn should_only_turn_rectangular_decks_of_known_board_width() {
+    let plank_heap = PlankHeap::new().add(10, 600);
+
+    let comparison = calepine_best_orientation(plank_heap, Deck::new(400, 6).unwrap(), CalepinageOptions::default());
+
+    assert!(matches!(comparison, Err(OrientationError::NotTurnable)));
+}
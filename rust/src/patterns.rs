@@ -0,0 +1,115 @@
+use std::str::FromStr;
+
+use crate::calepinage::Pattern;
+use crate::messages::{Language, Localize};
+#[cfg(test)]
+use crate::calepinage::{calepine_with_options, CalepinageOptions, Deck, PlankHeap};
+
+// Named stagger patterns, the usual ways of shifting the junctions of a row from the
+// previous row ones. A preset gives the `Pattern` for planks of a given length, the
+// project files and the command line selecting presets by name.
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Preset {
+    Random,
+    /// Junctions shifted by half a plank every row.
+    HalfBond,
+    /// Junctions shifted by a third of a plank every row.
+    ThirdBond,
+    /// Junctions shifted by a quarter of a plank every row.
+    QuarterStagger,
+    /// Junctions shifted by a third of a plank, going back and forth.
+    Symmetric,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 5] = [
+        Preset::Random,
+        Preset::HalfBond,
+        Preset::ThirdBond,
+        Preset::QuarterStagger,
+        Preset::Symmetric,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Random => "random",
+            Preset::HalfBond => "half_bond",
+            Preset::ThirdBond => "third_bond",
+            Preset::QuarterStagger => "quarter_stagger",
+            Preset::Symmetric => "symmetric",
+        }
+    }
+
+    /// The pattern for planks `plank_length` long, the random preset shuffling the planks
+    /// with the seed.
+    pub fn pattern(&self, plank_length: usize, seed: u64) -> Pattern {
+        match self {
+            Preset::Random => Pattern::Random { seed },
+            Preset::HalfBond => Pattern::half_bond(plank_length),
+            Preset::ThirdBond => Pattern::third_bond(plank_length),
+            Preset::QuarterStagger => Pattern::RunningBond {
+                offset: plank_length / 4,
+            },
+            Preset::Symmetric => Pattern::Symmetric {
+                offset: plank_length / 3,
+            },
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PatternError {
+    /// No preset has the name, underscores standing for dashes.
+    UnknownPreset(String),
+}
+
+impl std::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// Reads a preset name, with underscores or dashes between words.
+impl FromStr for Preset {
+    type Err = PatternError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let name = name.replace('-', "_");
+        Preset::ALL
+            .iter()
+            .copied()
+            .find(|preset| preset.name() == name)
+            .ok_or(PatternError::UnknownPreset(name))
+    }
+}
+
+#[test]
+fn presets_should_be_read_by_name() {
+    assert_eq!(Ok(Preset::QuarterStagger), "quarter-stagger".parse());
+    assert_eq!(Ok(Preset::HalfBond), "half_bond".parse());
+    assert_eq!(Err(PatternError::UnknownPreset("herringbone".to_string())), "herringbone".parse::<Preset>());
+    assert_eq!(
+        "unknown pattern herringbone, expected one of random, half_bond, third_bond, quarter_stagger, symmetric",
+        PatternError::UnknownPreset("herringbone".to_string()).to_string()
+    );
+    assert_eq!(Pattern::RunningBond { offset: 600 }, Preset::QuarterStagger.pattern(2400, 0));
+    assert_eq!(Pattern::Random { seed: 7 }, Preset::Random.pattern(2400, 7));
+}
+
+#[test]
+fn symmetric_preset_should_shift_junctions_back_and_forth() {
+    let deck = Deck::new(10, 5).unwrap();
+    let plank_heap = PlankHeap::default().add(15, 6);
+    let options = CalepinageOptions::default()
+        .with_allow_cutting(true)
+        .with_pattern(Preset::Symmetric.pattern(6, 0));
+
+    let calepinage = calepine_with_options(plank_heap, deck, options).unwrap();
+
+    let first_pieces: Vec<usize> = calepinage.lines.iter().map(|line| line.planks[0].length).collect();
+    assert_eq!(vec![6, 4, 2, 4, 6], first_pieces);
+}
@@ -0,0 +1,180 @@
+use crate::calepinage::{calepine_deck, Calepinage, CalepinageError, CalepinageOptions, Deck, PlankHeap};
+use crate::messages::{Language, Localize};
+
+// A project is made of several decks, the zones, laid with planks of the same heap. Zones
+// are laid one after the other, the order deciding which zone gets which planks.
+
+/// Projects with more zones than this are only tried in the given order and from the
+/// largest zone to the smallest, instead of trying every order.
+const MAX_PERMUTED_ZONES: usize = 6;
+
+/// The decks of a project with the planks and the options they are laid with, see
+/// `config` to load a project from a file.
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub decks: Vec<Deck>,
+    pub plank_heap: PlankHeap,
+    pub options: CalepinageOptions,
+}
+
+impl Project {
+    pub fn calepine(&self) -> Result<Vec<Calepinage>, ZoneError> {
+        calepine_zones(self.plank_heap.clone(), &self.decks, self.options.clone())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZoneError {
+    /// Index of the failing zone in the given decks.
+    pub zone: usize,
+    pub error: CalepinageError,
+}
+
+impl std::fmt::Display for ZoneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for ZoneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Calepines every deck, returned in the same order as the decks. When no order lets every
+/// zone be laid, reports the failing zone of the order laying the most zones.
+pub fn calepine_zones(
+    plank_heap: PlankHeap,
+    decks: &[Deck],
+    options: CalepinageOptions,
+) -> Result<Vec<Calepinage>, ZoneError> {
+    let mut furthest_failure: Option<(usize, ZoneError)> = None;
+    for laid in calepine_in_every_order(&plank_heap, decks, &options) {
+        match laid {
+            Ok(calepinages) => return Ok(calepinages),
+            Err((laid, error)) => {
+                if furthest_failure.as_ref().is_none_or(|(furthest, _)| laid > *furthest) {
+                    furthest_failure = Some((laid, error));
+                }
+            }
+        }
+    }
+    match furthest_failure {
+        Some((_, error)) => Err(error),
+        None => Ok(vec![]),
+    }
+}
+
+type OrderResult = Result<Vec<Calepinage>, (usize, ZoneError)>;
+
+#[cfg(not(feature = "rayon"))]
+fn calepine_in_every_order<'a>(
+    plank_heap: &'a PlankHeap,
+    decks: &'a [Deck],
+    options: &'a CalepinageOptions,
+) -> impl Iterator<Item = OrderResult> + 'a {
+    zone_orders(decks)
+        .into_iter()
+        .map(move |order| calepine_in_order(plank_heap.clone(), decks, &order, options))
+}
+
+/// Lays the zones in every order on several threads, results keeping the orders order.
+#[cfg(feature = "rayon")]
+fn calepine_in_every_order<'a>(
+    plank_heap: &'a PlankHeap,
+    decks: &'a [Deck],
+    options: &'a CalepinageOptions,
+) -> impl Iterator<Item = OrderResult> + 'a {
+    use rayon::prelude::*;
+
+    let laid: Vec<OrderResult> = zone_orders(decks)
+        .into_par_iter()
+        .map(|order| calepine_in_order(plank_heap.clone(), decks, &order, options))
+        .collect();
+    laid.into_iter()
+}
+
+/// Lays the zones in the given order, returning the number of zones laid on failure.
+fn calepine_in_order(
+    plank_heap: PlankHeap,
+    decks: &[Deck],
+    order: &[usize],
+    options: &CalepinageOptions,
+) -> OrderResult {
+    let mut the_plank_heap = plank_heap;
+    let mut calepinages: Vec<Option<Calepinage>> = vec![None; decks.len()];
+    for (laid, &zone) in order.iter().enumerate() {
+        let (calepinage, remaining) = calepine_deck(the_plank_heap, &decks[zone], options)
+            .map_err(|error| (laid, ZoneError { zone, error }))?;
+        calepinages[zone] = Some(calepinage);
+        the_plank_heap = remaining;
+    }
+    Ok(calepinages.into_iter().flatten().collect())
+}
+
+fn zone_orders(decks: &[Deck]) -> Vec<Vec<usize>> {
+    let given: Vec<usize> = (0..decks.len()).collect();
+    if decks.len() <= MAX_PERMUTED_ZONES {
+        return permutations(&given);
+    }
+    let mut largest_first = given.clone();
+    largest_first.sort_by_key(|&zone| std::cmp::Reverse(decks[zone].length * decks[zone].width));
+    vec![given, largest_first]
+}
+
+/// Every order of the items, starting with the given order.
+fn permutations(items: &[usize]) -> Vec<Vec<usize>> {
+    if items.is_empty() {
+        return vec![vec![]];
+    }
+    (0..items.len())
+        .flat_map(|index| {
+            let mut rest = items.to_vec();
+            let first = rest.remove(index);
+            permutations(&rest).into_iter().map(move |mut order| {
+                order.insert(0, first);
+                order
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn permutations_should_start_with_given_order() {
+    let orders = permutations(&[0, 1, 2]);
+
+    assert_eq!(6, orders.len());
+    assert_eq!(vec![0, 1, 2], orders[0]);
+}
+
+#[test]
+fn zones_should_share_planks_in_any_order_that_fits() {
+    let decks = vec![Deck::new(6, 1).unwrap(), Deck::new(5, 1).unwrap()];
+    let plank_heap = PlankHeap::new().add(2, 3).add(1, 5).add(1, 1);
+
+    let calepinages = calepine_zones(plank_heap, &decks, CalepinageOptions::default()).unwrap();
+
+    let lengths: Vec<Vec<usize>> = calepinages
+        .iter()
+        .map(|calepinage| calepinage.lines[0].planks.iter().map(|plank| plank.length).collect())
+        .collect();
+    assert_eq!(vec![vec![3, 3], vec![5]], lengths);
+}
+
+#[test]
+fn zones_should_report_the_failing_zone() {
+    let decks = vec![Deck::new(10, 1).unwrap(), Deck::new(10, 1).unwrap()];
+    let plank_heap = PlankHeap::new().add(1, 10);
+
+    let result = calepine_zones(plank_heap, &decks, CalepinageOptions::default());
+
+    assert_eq!(
+        Err(ZoneError {
+            zone: 1,
+            error: CalepinageError::NotEnoughPlanks
+        }),
+        result.map(|_| ())
+    );
+}
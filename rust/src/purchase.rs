@@ -0,0 +1,153 @@
+use crate::calepinage::{calepine_deck, count_rows, Calepinage, CalepinageError, CalepinageOptions, Deck, Plank, PlankHeap};
+
+// Purchase planning: the boards to buy among the lengths sold to lay a deck, and the
+// calepinage of these boards. Boards are always cut to the deck.
+//
+// The planner first buys the fewest boards of a single length laying the deck, for every
+// length, keeping the cheapest heap. It then trades boards of a length for cheaper boards
+// of another length as long as the deck can still be laid and the heap gets cheaper.
+
+/// A board length sold, at a price in the smallest currency unit.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StockLength {
+    pub length: usize,
+    pub price: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Purchase {
+    /// How many boards of every length of the catalog to buy, in the catalog order.
+    pub counts: Vec<(StockLength, usize)>,
+    pub cost: usize,
+    pub calepinage: Calepinage,
+}
+
+pub fn plan_purchase(
+    deck: &Deck,
+    catalog: &[StockLength],
+    options: CalepinageOptions,
+) -> Result<Purchase, CalepinageError> {
+    if catalog.iter().any(|stock| stock.length == 0 || stock.length > Plank::MAX_LENGTH) {
+        return Err(CalepinageError::NotEnoughPlanks);
+    }
+    let options = options.with_allow_cutting(true);
+    let (row_count, _) = count_rows(deck, &options)?;
+    let needed_length = row_count * deck.length;
+
+    let mut best: Option<Order> = None;
+    let mut last_error = CalepinageError::NotEnoughPlanks;
+    for (index, stock) in catalog.iter().enumerate() {
+        let fewest = needed_length.div_ceil(stock.length);
+        // every row may waste up to a board
+        for count in fewest..=fewest + row_count {
+            let mut counts = vec![0; catalog.len()];
+            counts[index] = count;
+            match Order::lay(catalog, counts, deck, &options) {
+                Ok(order) => {
+                    if best.as_ref().is_none_or(|best| order.is_cheaper_than(best)) {
+                        best = Some(order);
+                    }
+                    break;
+                }
+                Err(error) => last_error = error,
+            }
+        }
+    }
+    let mut best = best.ok_or(last_error)?;
+
+    while let Some(cheaper) = best.trade(catalog, deck, &options, row_count) {
+        best = cheaper;
+    }
+    Ok(Purchase {
+        counts: catalog.iter().copied().zip(best.counts).collect(),
+        cost: best.cost,
+        calepinage: best.calepinage,
+    })
+}
+
+/// Boards bought, counted for every length of the catalog, and their calepinage.
+struct Order {
+    counts: Vec<usize>,
+    cost: usize,
+    length: usize,
+    calepinage: Calepinage,
+}
+
+impl Order {
+    fn lay(
+        catalog: &[StockLength],
+        counts: Vec<usize>,
+        deck: &Deck,
+        options: &CalepinageOptions,
+    ) -> Result<Self, CalepinageError> {
+        let planks = catalog
+            .iter()
+            .zip(counts.iter())
+            .flat_map(|(stock, &count)| {
+                (0..count).map(move |_| Plank::new(stock.length).unwrap().with_price(stock.price))
+            })
+            .collect();
+        let plank_heap = PlankHeap::from_planks(planks).map_err(|_| CalepinageError::NotEnoughPlanks)?;
+        let (calepinage, _) = calepine_deck(plank_heap, deck, options)?;
+        let cost = catalog.iter().zip(counts.iter()).map(|(stock, count)| stock.price * count).sum();
+        let length = catalog.iter().zip(counts.iter()).map(|(stock, count)| stock.length * count).sum();
+        Ok(Order {
+            counts,
+            cost,
+            length,
+            calepinage,
+        })
+    }
+
+    /// Cheaper, or as cheap with less wood.
+    fn is_cheaper_than(&self, other: &Order) -> bool {
+        (self.cost, self.length) < (other.cost, other.length)
+    }
+
+    /// The first cheaper order swapping a board for the fewest boards of another length
+    /// laying the deck, if any.
+    fn trade(&self, catalog: &[StockLength], deck: &Deck, options: &CalepinageOptions, row_count: usize) -> Option<Order> {
+        for (sold, &count) in self.counts.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            for bought in (0..catalog.len()).filter(|&bought| bought != sold) {
+                let fewest = catalog[sold].length.div_ceil(catalog[bought].length);
+                for extra in 0..=fewest + row_count {
+                    let mut counts = self.counts.clone();
+                    counts[sold] -= 1;
+                    counts[bought] += extra;
+                    if catalog[sold].price <= catalog[bought].price * extra {
+                        break;
+                    }
+                    if let Ok(order) = Order::lay(catalog, counts, deck, options) {
+                        return Some(order);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[test]
+fn purchase_should_mix_lengths_when_cheaper() {
+    let deck = Deck::new(10, 2).unwrap();
+    let catalog = [StockLength { length: 4, price: 3 }, StockLength { length: 10, price: 12 }];
+
+    let purchase = plan_purchase(&deck, &catalog, CalepinageOptions::default()).unwrap();
+
+    assert_eq!(vec![(catalog[0], 3), (catalog[1], 1)], purchase.counts);
+    assert_eq!(21, purchase.cost);
+    assert_eq!(2, purchase.calepinage.lines.len());
+}
+
+#[test]
+fn purchase_should_fail_without_catalog() {
+    let deck = Deck::new(10, 2).unwrap();
+
+    assert_eq!(
+        Err(CalepinageError::NotEnoughPlanks),
+        plan_purchase(&deck, &[], CalepinageOptions::default()).map(|purchase| purchase.cost)
+    );
+}
@@ -0,0 +1,512 @@
+use std::fmt::Write;
+
+use crate::calepinage::{Calepinage, Deck};
+#[cfg(feature = "image")]
+use crate::messages::{Language, Localize};
+#[cfg(test)]
+use crate::calepinage::{Line, Plank};
+#[cfg(test)]
+use crate::plank_line;
+
+// Drawings of a calepinage seen from above, the deck length going right and the rows
+// stacking down from the first one.
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SvgOptions {
+    /// Pixels per unit of length.
+    pub scale: f64,
+    /// Width of a row for decks whose boards width isn't known, 100 by default.
+    pub row_width: usize,
+    pub show_lengths: bool,
+    pub show_junctions: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            scale: 1.0,
+            row_width: 100,
+            show_lengths: true,
+            show_junctions: true,
+        }
+    }
+}
+
+impl SvgOptions {
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_row_width(mut self, row_width: usize) -> Self {
+        self.row_width = row_width;
+        self
+    }
+
+    pub fn with_lengths(mut self, show_lengths: bool) -> Self {
+        self.show_lengths = show_lengths;
+        self
+    }
+
+    pub fn with_junctions(mut self, show_junctions: bool) -> Self {
+        self.show_junctions = show_junctions;
+        self
+    }
+}
+
+/// Width and number of the rows of the deck, a ripped last row counting as a row.
+fn rows(deck: &Deck, default_row_width: usize) -> (usize, usize) {
+    match deck.board_width {
+        Some(board_width) => (board_width, deck.width.div_ceil(board_width)),
+        None => (default_row_width, deck.width),
+    }
+}
+
+/// Draws the outline of the deck, the boards of the field with their length and a marker
+/// on every junction.
+pub fn svg(calepinage: &Calepinage, deck: &Deck, options: &SvgOptions) -> String {
+    let (row_width, rows) = rows(deck, options.row_width);
+    let scaled = |length: usize| length as f64 * options.scale;
+    let (width, height) = (scaled(deck.length), scaled(rows * row_width));
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+        width, height, width, height
+    )
+    .unwrap();
+    writeln!(svg, r#"<rect x="0" y="0" width="{}" height="{}" fill="none" stroke="black"/>"#, width, height).unwrap();
+    for placement in calepinage.placements() {
+        let (x, y) = (scaled(placement.x_start), scaled(placement.row * row_width));
+        let (board_length, board_width) = (scaled(placement.x_end - placement.x_start), scaled(placement.plank.width * row_width));
+        writeln!(
+            svg,
+            r##"<rect x="{}" y="{}" width="{}" height="{}" fill="#deb887" stroke="black"/>"##,
+            x, y, board_length, board_width
+        )
+        .unwrap();
+        if options.show_lengths {
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}" text-anchor="middle" dominant-baseline="middle">{}</text>"#,
+                x + board_length / 2.0,
+                y + board_width / 2.0,
+                placement.plank.length
+            )
+            .unwrap();
+        }
+    }
+    if options.show_junctions {
+        for (row, junction) in calepinage.junctions() {
+            let x = scaled(junction.coordinate());
+            writeln!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="red" stroke-width="2"/>"#,
+                x,
+                scaled(row * row_width),
+                x,
+                scaled((row + 1) * row_width)
+            )
+            .unwrap();
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(feature = "image")]
+#[derive(Debug, PartialEq, Clone)]
+pub struct PngOptions {
+    /// Pixels per unit of length.
+    pub scale: f64,
+    /// Width of a row for decks whose boards width isn't known, 100 by default.
+    pub row_width: usize,
+    pub background: [u8; 3],
+    pub board: [u8; 3],
+    pub outline: [u8; 3],
+    /// Color of the junction markers, none drawn when None.
+    pub junction: Option<[u8; 3]>,
+}
+
+#[cfg(feature = "image")]
+impl Default for PngOptions {
+    fn default() -> Self {
+        PngOptions {
+            scale: 1.0,
+            row_width: 100,
+            background: [255, 255, 255],
+            board: [222, 184, 135],
+            outline: [0, 0, 0],
+            junction: Some([255, 0, 0]),
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+impl PngOptions {
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_row_width(mut self, row_width: usize) -> Self {
+        self.row_width = row_width;
+        self
+    }
+
+    pub fn with_colors(mut self, background: [u8; 3], board: [u8; 3], outline: [u8; 3]) -> Self {
+        self.background = background;
+        self.board = board;
+        self.outline = outline;
+        self
+    }
+
+    pub fn with_junction_color(mut self, junction: Option<[u8; 3]>) -> Self {
+        self.junction = junction;
+        self
+    }
+}
+
+#[cfg(feature = "image")]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RenderError {
+    /// The image has no pixel, or more than can be drawn, at the scale of the options.
+    ImageSize { width: u32, height: u32 },
+    /// The image can't be encoded, as told by the encoder.
+    Encoding(String),
+}
+
+#[cfg(feature = "image")]
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+#[cfg(feature = "image")]
+impl std::error::Error for RenderError {}
+
+/// Draws the deck like `svg`, board lengths aside, as the bytes of a PNG image.
+#[cfg(feature = "image")]
+pub fn png(calepinage: &Calepinage, deck: &Deck, options: &PngOptions) -> Result<Vec<u8>, RenderError> {
+    use tiny_skia::{Color, Paint, PathBuilder, Pixmap, Rect, Stroke, Transform};
+
+    let (row_width, rows) = rows(deck, options.row_width);
+    let scaled = |length: usize| (length as f64 * options.scale) as f32;
+    let (width, height) = (scaled(deck.length).ceil() as u32, scaled(rows * row_width).ceil() as u32);
+    let mut pixmap = Pixmap::new(width, height).ok_or(RenderError::ImageSize { width, height })?;
+    let paint = |[red, green, blue]: [u8; 3]| {
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(red, green, blue, 255);
+        paint
+    };
+    let [red, green, blue] = options.background;
+    pixmap.fill(Color::from_rgba8(red, green, blue, 255));
+
+    let (board, outline) = (paint(options.board), paint(options.outline));
+    for placement in calepinage.placements() {
+        let rect = Rect::from_xywh(
+            scaled(placement.x_start),
+            scaled(placement.row * row_width),
+            scaled(placement.x_end - placement.x_start),
+            scaled(placement.plank.width * row_width),
+        );
+        if let Some(rect) = rect {
+            pixmap.fill_rect(rect, &board, Transform::identity(), None);
+            let path = PathBuilder::from_rect(rect);
+            pixmap.stroke_path(&path, &outline, &Stroke::default(), Transform::identity(), None);
+        }
+    }
+    if let Some(color) = options.junction {
+        let junction = paint(color);
+        let stroke = Stroke {
+            width: 2.0,
+            ..Stroke::default()
+        };
+        for (row, at) in calepinage.junctions() {
+            let mut path = PathBuilder::new();
+            path.move_to(scaled(at.coordinate()), scaled(row * row_width));
+            path.line_to(scaled(at.coordinate()), scaled((row + 1) * row_width));
+            if let Some(path) = path.finish() {
+                pixmap.stroke_path(&path, &junction, &stroke, Transform::identity(), None);
+            }
+        }
+    }
+    pixmap.encode_png().map_err(|error| RenderError::Encoding(error.to_string()))
+}
+
+/// Writes the calepinage as an ASCII DXF drawing, on a `DECK` layer for the deck outline,
+/// `BOARDS` for the board outlines and `JOINTS` for the junctions. The first row lies on the
+/// x axis, the rows stacking up, `row_width` wide for decks whose boards width isn't known.
+pub fn dxf(calepinage: &Calepinage, deck: &Deck, row_width: usize) -> String {
+    let (row_width, rows) = rows(deck, row_width);
+    let mut dxf = String::new();
+    dxf.push_str("0\nSECTION\n2\nTABLES\n0\nTABLE\n2\nLAYER\n70\n3\n");
+    for (layer, color) in [("DECK", 7), ("BOARDS", 3), ("JOINTS", 1)].iter() {
+        write!(dxf, "0\nLAYER\n2\n{}\n70\n0\n62\n{}\n6\nCONTINUOUS\n", layer, color).unwrap();
+    }
+    dxf.push_str("0\nENDTAB\n0\nENDSEC\n0\nSECTION\n2\nENTITIES\n");
+
+    let outline: Vec<(usize, usize)> = match &deck.outline {
+        Some(outline) => outline.vertices().iter().map(|&(x, y)| (x, y * row_width)).collect(),
+        None => vec![(0, 0), (deck.length, 0), (deck.length, rows * row_width), (0, rows * row_width)],
+    };
+    write_polyline(&mut dxf, "DECK", &outline);
+    for placement in calepinage.placements() {
+        let (bottom, top) = (placement.row * row_width, (placement.row + placement.plank.width) * row_width);
+        let corners = [(placement.x_start, bottom), (placement.x_end, bottom), (placement.x_end, top), (placement.x_start, top)];
+        write_polyline(&mut dxf, "BOARDS", &corners);
+    }
+    for (row, junction) in calepinage.junctions() {
+        let x = junction.coordinate();
+        write!(
+            dxf,
+            "0\nLINE\n8\nJOINTS\n10\n{}\n20\n{}\n11\n{}\n21\n{}\n",
+            x,
+            row * row_width,
+            x,
+            (row + 1) * row_width
+        )
+        .unwrap();
+    }
+    dxf.push_str("0\nENDSEC\n0\nEOF\n");
+    dxf
+}
+
+/// Writes a closed polyline through the points.
+fn write_polyline(dxf: &mut String, layer: &str, points: &[(usize, usize)]) {
+    write!(dxf, "0\nPOLYLINE\n8\n{}\n66\n1\n70\n1\n", layer).unwrap();
+    for (x, y) in points {
+        write!(dxf, "0\nVERTEX\n8\n{}\n10\n{}\n20\n{}\n", layer, x, y).unwrap();
+    }
+    write!(dxf, "0\nSEQEND\n8\n{}\n", layer).unwrap();
+}
+
+/// Size of the PDF plan sheet, A4 landscape, in points.
+#[cfg(feature = "pdf")]
+const PDF_PAGE: (f64, f64) = (842.0, 595.0);
+#[cfg(feature = "pdf")]
+const PDF_MARGIN: f64 = 40.0;
+/// Width of the cut table on the right of the plan.
+#[cfg(feature = "pdf")]
+const PDF_CUT_TABLE_WIDTH: f64 = 180.0;
+
+/// Writes a printable plan sheet: the deck outline with its dimensions, the boards numbered
+/// in the order of the placements and the cut schedule on the side. Rows are `row_width`
+/// wide for decks whose boards width isn't known.
+#[cfg(feature = "pdf")]
+pub fn pdf(calepinage: &Calepinage, deck: &Deck, row_width: usize) -> Vec<u8> {
+    use crate::cut_list::cut_schedule;
+
+    let (row_width, rows) = rows(deck, row_width);
+    let (page_width, page_height) = PDF_PAGE;
+    let plan_width = page_width - 3.0 * PDF_MARGIN - PDF_CUT_TABLE_WIDTH;
+    let plan_height = page_height - 3.0 * PDF_MARGIN;
+    let deck_width = rows * row_width;
+    let scale = (plan_width / deck.length as f64).min(plan_height / deck_width as f64);
+    let left = PDF_MARGIN;
+    let top = page_height - 2.0 * PDF_MARGIN;
+    // rows go down the sheet from the top of the plan
+    let point = |x: usize, y: usize| (left + x as f64 * scale, top - y as f64 * scale);
+
+    let mut content = String::new();
+    let (x0, y0) = point(0, 0);
+    let (x1, y1) = point(deck.length, deck_width);
+    writeln!(content, "1 w {:.2} {:.2} {:.2} {:.2} re S", x0, y1, x1 - x0, y0 - y1).unwrap();
+    writeln!(content, "0.5 w {:.2} {:.2} m {:.2} {:.2} l S", x0, y0 + 10.0, x1, y0 + 10.0).unwrap();
+    write_pdf_text(&mut content, 10.0, (x0 + x1) / 2.0, y0 + 14.0, &deck.length.to_string());
+    writeln!(content, "{:.2} {:.2} m {:.2} {:.2} l S", x0 - 10.0, y0, x0 - 10.0, y1).unwrap();
+    write_pdf_text(&mut content, 10.0, x0 - 34.0, (y0 + y1) / 2.0, &deck_width.to_string());
+
+    for (number, placement) in calepinage.placements().iter().enumerate() {
+        let (x0, y0) = point(placement.x_start, placement.row * row_width);
+        let (x1, y1) = point(placement.x_end, (placement.row + placement.plank.width) * row_width);
+        writeln!(content, "0.5 w {:.2} {:.2} {:.2} {:.2} re S", x0, y1, x1 - x0, y0 - y1).unwrap();
+        write_pdf_text(&mut content, 8.0, (x0 + x1) / 2.0 - 4.0, (y0 + y1) / 2.0 - 3.0, &(number + 1).to_string());
+    }
+
+    let table_left = page_width - PDF_MARGIN - PDF_CUT_TABLE_WIDTH;
+    write_pdf_text(&mut content, 12.0, table_left, top, "Cuts");
+    for (index, group) in cut_schedule(calepinage).iter().enumerate() {
+        write_pdf_text(&mut content, 9.0, table_left, top - 18.0 - 12.0 * index as f64, &group.to_string());
+    }
+
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 4 0 R >> >> /Contents 5 0 R >>",
+            page_width, page_height
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        format!("<< /Length {} >>\nstream\n{}endstream", content.len(), content),
+    ];
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = vec![];
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        write!(pdf, "{} 0 obj\n{}\nendobj\n", index + 1, object).unwrap();
+    }
+    let xref = pdf.len();
+    write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).unwrap();
+    for offset in offsets {
+        writeln!(pdf, "{:010} 00000 n ", offset).unwrap();
+    }
+    write!(pdf, "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref).unwrap();
+    pdf.into_bytes()
+}
+
+#[cfg(feature = "pdf")]
+fn write_pdf_text(content: &mut String, size: f64, x: f64, y: f64, text: &str) {
+    let escaped = text.replace('\\', "\\\\").replace('(', "\\(").replace(')', "\\)");
+    writeln!(content, "BT /F1 {} Tf {:.2} {:.2} Td ({}) Tj ET", size, x, y, escaped).unwrap();
+}
+
+/// Decks longer than this many characters are drawn scaled down to fit.
+pub const MAX_ASCII_WIDTH: usize = 80;
+
+/// Draws every row on a text line, a `|` starting every board followed by its length, and
+/// `#` where the deck isn't covered.
+pub fn ascii(calepinage: &Calepinage, deck: &Deck) -> String {
+    let (_, rows) = rows(deck, 1);
+    let units_per_char = deck.length.div_ceil(MAX_ASCII_WIDTH).max(1);
+    let columns = deck.length.div_ceil(units_per_char);
+    let mut grid = vec![vec!['#'; columns]; rows];
+    for placement in calepinage.placements() {
+        let start = placement.x_start / units_per_char;
+        let end = (placement.x_end / units_per_char).clamp(start + 1, columns);
+        for line in grid.iter_mut().skip(placement.row).take(placement.plank.width) {
+            for cell in line[start..end].iter_mut() {
+                *cell = ' ';
+            }
+            if start > 0 {
+                line[start] = '|';
+            }
+        }
+        if let Some(line) = grid.get_mut(placement.row) {
+            let label_start = if start > 0 { start + 1 } else { start };
+            let label = placement.plank.length.to_string();
+            if label_start + label.len() <= end {
+                for (cell, digit) in line[label_start..].iter_mut().zip(label.chars()) {
+                    *cell = digit;
+                }
+            }
+        }
+    }
+
+    let mut ascii = format!("/{}\\\n", "=".repeat(columns));
+    for line in grid {
+        ascii.push('|');
+        ascii.extend(line);
+        ascii.push_str("|\n");
+    }
+    ascii.push_str(&format!("\\{}/\n", "=".repeat(columns)));
+    ascii
+}
+
+#[test]
+fn ascii_should_draw_every_row() {
+    let deck = Deck::new(10, 3).unwrap();
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![Plank::new(10).unwrap()])
+        .with_line(plank_line![Plank::new(6).unwrap(), Plank::new(4).unwrap()]);
+
+    let expected = "\
+/==========\\
+|10        |
+|6     |4  |
+|##########|
+\\==========/
+";
+    assert_eq!(expected, ascii(&calepinage, &deck));
+}
+
+#[test]
+fn ascii_should_scale_long_decks_down() {
+    let deck = Deck::new(1600, 1).unwrap();
+    let calepinage = Calepinage::default().with_line(plank_line![Plank::new(800).unwrap(), Plank::new(800).unwrap()]);
+
+    let drawing = ascii(&calepinage, &deck);
+
+    let lines: Vec<&str> = drawing.lines().collect();
+    assert_eq!(MAX_ASCII_WIDTH + 2, lines[1].len());
+    assert!(lines[1].starts_with("|800 "));
+    assert_eq!(Some(40), lines[1][1..].find("|800"));
+}
+
+#[test]
+fn dxf_should_draw_every_board_and_joint_on_its_layer() {
+    let deck = Deck::physical(10, 20, 10).unwrap();
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![Plank::new(10).unwrap()])
+        .with_line(plank_line![Plank::new(6).unwrap(), Plank::new(4).unwrap()]);
+
+    let dxf = dxf(&calepinage, &deck, 100);
+
+    assert!(dxf.starts_with("0\nSECTION\n2\nTABLES\n"));
+    assert!(dxf.ends_with("0\nENDSEC\n0\nEOF\n"));
+    assert_eq!(1, dxf.matches("0\nPOLYLINE\n8\nDECK\n").count());
+    assert_eq!(3, dxf.matches("0\nPOLYLINE\n8\nBOARDS\n").count());
+    assert!(dxf.contains("0\nLINE\n8\nJOINTS\n10\n6\n20\n10\n11\n6\n21\n20\n"));
+    assert!(dxf.contains("0\nVERTEX\n8\nDECK\n10\n10\n20\n20\n"));
+}
+
+#[test]
+fn svg_should_draw_boards_and_junctions() {
+    let deck = Deck::new(10, 2).unwrap();
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![Plank::new(10).unwrap()])
+        .with_line(plank_line![Plank::new(6).unwrap(), Plank::new(4).unwrap()]);
+
+    let svg = svg(&calepinage, &deck, &SvgOptions::default().with_scale(2.0).with_row_width(5));
+
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="20""#));
+    assert_eq!(3, svg.matches(r##"fill="#deb887""##).count());
+    assert!(svg.contains(r##"<rect x="12" y="10" width="8" height="10" fill="#deb887" stroke="black"/>"##));
+    assert!(svg.contains(r#">6</text>"#));
+    assert!(svg.contains(r#"<line x1="12" y1="10" x2="12" y2="20" stroke="red" stroke-width="2"/>"#));
+    assert!(svg.ends_with("</svg>\n"));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn png_should_draw_boards_in_their_color() {
+    let deck = Deck::new(10, 2).unwrap();
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![Plank::new(10).unwrap()])
+        .with_line(plank_line![Plank::new(6).unwrap(), Plank::new(4).unwrap()]);
+    let options = PngOptions::default().with_scale(4.0).with_row_width(5).with_junction_color(None);
+
+    let png = png(&calepinage, &deck, &options).unwrap();
+
+    let pixmap = tiny_skia::Pixmap::decode_png(&png).unwrap();
+    assert_eq!((40, 40), (pixmap.width(), pixmap.height()));
+    let pixel = pixmap.pixel(10, 30).unwrap();
+    assert_eq!([222, 184, 135], [pixel.red(), pixel.green(), pixel.blue()]);
+}
+
+#[cfg(feature = "pdf")]
+#[test]
+fn pdf_should_number_the_boards_and_list_the_cuts() {
+    use crate::calepinage::Cut;
+
+    let deck = Deck::new(10, 2).unwrap();
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![Plank::new(10).unwrap()])
+        .with_line(plank_line![Plank::new(6).unwrap(), Plank::new(4).unwrap()])
+        .with_cut(Cut {
+            plank: Plank::new(8).unwrap(),
+            at: 4,
+        });
+
+    let pdf = String::from_utf8(pdf(&calepinage, &deck, 5)).unwrap();
+
+    assert!(pdf.starts_with("%PDF-1.4\n"));
+    assert!(pdf.ends_with("%%EOF\n"));
+    assert!(pdf.contains("(3) Tj"));
+    assert!(!pdf.contains("(4) Tj"));
+    assert!(pdf.contains("(cut 1 board of 8 at 4) Tj"));
+    let xref = pdf.find("xref\n").unwrap();
+    assert!(pdf.ends_with(&format!("startxref\n{}\n%%EOF\n", xref)));
+}
@@ -0,0 +1,184 @@
+use std::fmt::Write;
+
+use crate::calepinage::{Calepinage, Deck, Line};
+#[cfg(test)]
+use crate::calepinage::{Cut, Plank};
+use crate::cut_list::{cut_schedule, miter_schedule};
+use crate::messages::{Language, Localize, ReportPhrase};
+#[cfg(test)]
+use crate::plank_line;
+use crate::stats::Stats;
+
+// Plan report: a plain text summary of a calepinage for the customer, the deck, the boards
+// of every row, the cuts, the waste and what is left over, one section after the other.
+
+pub fn text(calepinage: &Calepinage, deck: &Deck, stats: &Stats) -> String {
+    text_in(calepinage, deck, stats, Language::English)
+}
+
+/// The report written in the language.
+pub fn text_in(calepinage: &Calepinage, deck: &Deck, stats: &Stats, language: Language) -> String {
+    let mut report = String::new();
+    let out = &mut report;
+    let say = |out: &mut String, phrase: ReportPhrase| writeln!(out, "{}", phrase.in_language(language)).unwrap();
+    section(out, ReportPhrase::DeckSection, language);
+    match deck.board_width {
+        Some(board_width) => say(
+            out,
+            ReportPhrase::DeckSize {
+                length: deck.length,
+                width: deck.width,
+                board_width,
+            },
+        ),
+        None => say(
+            out,
+            ReportPhrase::DeckRows {
+                length: deck.length,
+                rows: deck.width,
+            },
+        ),
+    }
+    if !deck.cutouts.is_empty() {
+        say(out, ReportPhrase::Cutouts(deck.cutouts.len()));
+    }
+    if let Some(rip) = calepinage.rip {
+        say(out, ReportPhrase::Rip(rip));
+    }
+
+    section(out, ReportPhrase::RowsSection, language);
+    let mut row = 1;
+    for line in calepinage.lines.iter() {
+        say(out, ReportPhrase::Row { row, boards: &boards(line, language) });
+        row += line.width();
+    }
+    for (side, line) in calepinage.border.iter().enumerate() {
+        say(out, ReportPhrase::Border { side, boards: &boards(line, language) });
+    }
+    for breaker in calepinage.breakers.iter() {
+        let boards = boards(&breaker.line, language);
+        say(out, ReportPhrase::Breaker { at: breaker.at, boards: &boards });
+    }
+
+    section(out, ReportPhrase::CutsSection, language);
+    let schedule = cut_schedule(calepinage);
+    let miters = miter_schedule(calepinage);
+    if schedule.is_empty() && miters.is_empty() {
+        say(out, ReportPhrase::NoCuts);
+    }
+    for group in schedule {
+        writeln!(out, "{}", group.in_language(language)).unwrap();
+    }
+    for group in miters {
+        writeln!(out, "{}", group.in_language(language)).unwrap();
+    }
+
+    section(out, ReportPhrase::WasteSection, language);
+    say(
+        out,
+        ReportPhrase::BoardsUsed {
+            boards: stats.boards_used,
+            waste_percentage: stats.waste_percentage,
+        },
+    );
+    say(out, ReportPhrase::Coverage(stats.coverage));
+    match stats.average_stagger {
+        Some(stagger) => say(
+            out,
+            ReportPhrase::StaggeredJunctions {
+                junctions: stats.junctions,
+                stagger,
+            },
+        ),
+        None => say(out, ReportPhrase::Junctions(stats.junctions)),
+    }
+
+    section(out, ReportPhrase::LeftoversSection, language);
+    say(out, ReportPhrase::BoardsLeftOver(stats.boards_left_over));
+    let offcuts: Vec<String> = calepinage
+        .offcuts()
+        .iter()
+        .filter(|offcut| offcut.length > 0)
+        .map(|offcut| offcut.length.to_string())
+        .collect();
+    if !offcuts.is_empty() {
+        say(out, ReportPhrase::Offcuts(&offcuts.join(", ")));
+    }
+    report
+}
+
+/// Title of a section, a blank line separating it from the previous one.
+fn section(out: &mut String, title: ReportPhrase, language: Language) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    let title = title.in_language(language).to_string();
+    writeln!(out, "{}\n{}", title, "-".repeat(title.chars().count())).unwrap();
+}
+
+/// Lengths of the boards of the line, with where the line starts when it doesn't start at
+/// the deck edge.
+fn boards(line: &Line, language: Language) -> String {
+    let lengths: Vec<String> = line.planks.iter().map(|plank| plank.length.to_string()).collect();
+    let boards = lengths.join(" + ");
+    match line.start {
+        0 => boards,
+        start => ReportPhrase::From { boards: &boards, start }.in_language(language).to_string(),
+    }
+}
+
+#[test]
+fn text_should_summarize_the_plan() {
+    let deck = Deck::new(10, 2).unwrap();
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![Plank::new(6).unwrap(), Plank::new(4).unwrap()])
+        .with_line(plank_line![Plank::new(4).unwrap(), Plank::new(6).unwrap()])
+        .with_cut(Cut {
+            plank: Plank::new(10).unwrap(),
+            at: 4,
+        });
+    let stats = Stats {
+        waste_percentage: 0.0,
+        boards_used: 3,
+        boards_left_over: 1,
+        junctions: 2,
+        average_stagger: Some(2.0),
+        coverage: 1.0,
+    };
+
+    let report = text(&calepinage, &deck, &stats);
+
+    assert_eq!(
+        "Deck\n----\n10 long, 2 rows\n\
+         \nRows\n----\nrow 1: 6 + 4\nrow 2: 4 + 6\n\
+         \nCuts\n----\ncut 1 board of 10 at 4\n\
+         \nWaste\n-----\n3 boards used, 0.0% wasted\n100.0% of the deck covered\n2 junctions, 2 apart on average\n\
+         \nLeftovers\n---------\n1 boards left over\noffcuts: 6\n",
+        report
+    );
+}
+
+#[test]
+fn text_in_should_write_the_report_in_french() {
+    let deck = Deck::new(10, 1).unwrap();
+    let calepinage = Calepinage::default().with_line(plank_line![Plank::new(10).unwrap()]);
+    let stats = Stats {
+        waste_percentage: 0.0,
+        boards_used: 1,
+        boards_left_over: 0,
+        junctions: 0,
+        average_stagger: None,
+        coverage: 1.0,
+    };
+
+    let report = text_in(&calepinage, &deck, &stats, Language::French);
+
+    assert_eq!(
+        "Terrasse\n--------\n10 de long, 1 rangées\n\
+         \nRangées\n-------\nrangée 1 : 10\n\
+         \nCoupes\n------\naucune coupe\n\
+         \nPertes\n------\n1 lames utilisées, 0.0 % de pertes\n100.0 % de la terrasse couverte\n0 joints\n\
+         \nRestes\n------\n0 lames restantes\n",
+        report
+    );
+}
@@ -0,0 +1,73 @@
+use crate::calepinage::{calepine_with_options, Calepinage, CalepinageError, CalepinageOptions, Deck, PlankHeap};
+use crate::stats::Stats;
+
+// Scenarios are alternatives laid with the same heap, like two deck sizes or orientations,
+// every scenario getting the whole heap. Their stats tell which one wastes less.
+
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub deck: Deck,
+    pub options: CalepinageOptions,
+}
+
+impl Scenario {
+    pub fn new(deck: Deck, options: CalepinageOptions) -> Self {
+        Scenario { deck, options }
+    }
+}
+
+/// Calepinage of a scenario with its stats, to compare it with the other scenarios.
+#[derive(Debug, PartialEq, Clone)]
+pub struct LaidScenario {
+    pub calepinage: Calepinage,
+    pub stats: Stats,
+}
+
+/// Lays every scenario with the heap, results keeping the scenarios order.
+#[cfg(not(feature = "rayon"))]
+pub fn calepine_scenarios(plank_heap: &PlankHeap, scenarios: &[Scenario]) -> Vec<Result<LaidScenario, CalepinageError>> {
+    scenarios.iter().map(|scenario| calepine_scenario(plank_heap, scenario)).collect()
+}
+
+/// Lays every scenario with the heap on several threads, results keeping the scenarios
+/// order.
+#[cfg(feature = "rayon")]
+pub fn calepine_scenarios(plank_heap: &PlankHeap, scenarios: &[Scenario]) -> Vec<Result<LaidScenario, CalepinageError>> {
+    use rayon::prelude::*;
+
+    scenarios.par_iter().map(|scenario| calepine_scenario(plank_heap, scenario)).collect()
+}
+
+fn calepine_scenario(plank_heap: &PlankHeap, scenario: &Scenario) -> Result<LaidScenario, CalepinageError> {
+    let calepinage = calepine_with_options(plank_heap.clone(), scenario.deck.clone(), scenario.options.clone())?;
+    let stats = calepinage.stats(plank_heap, &scenario.deck);
+    Ok(LaidScenario { calepinage, stats })
+}
+
+/// Index of the scenario laid wasting the least, the first one among equals.
+pub fn least_wasteful(laid: &[Result<LaidScenario, CalepinageError>]) -> Option<usize> {
+    laid.iter()
+        .enumerate()
+        .filter_map(|(index, laid)| laid.as_ref().ok().map(|laid| (index, laid.stats.waste_percentage)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index)
+}
+
+#[test]
+fn scenarios_should_each_get_the_whole_heap() {
+    let plank_heap = PlankHeap::new().add(2, 10).add(1, 6);
+    let options = CalepinageOptions::default().with_allow_cutting(true);
+    let scenarios = vec![
+        Scenario::new(Deck::new(10, 2).unwrap(), options.clone()),
+        Scenario::new(Deck::new(8, 2).unwrap(), options.clone()),
+        Scenario::new(Deck::new(10, 3).unwrap(), options),
+    ];
+
+    let laid = calepine_scenarios(&plank_heap, &scenarios);
+
+    assert_eq!(3, laid.len());
+    assert_eq!(Some(0.0), laid[0].as_ref().ok().map(|laid| laid.stats.waste_percentage));
+    assert!(laid[1].as_ref().is_ok_and(|laid| laid.stats.waste_percentage > 0.0));
+    assert_eq!(Some(&CalepinageError::NotEnoughPlanks), laid[2].as_ref().err());
+    assert_eq!(Some(0), least_wasteful(&laid));
+}
@@ -0,0 +1,393 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::calepinage::{
+    calepine_with_budget, calepine_with_options, Budget, Calepinage, CalepinageError, DeckError, PlankError, PlankHeap,
+    Solver,
+};
+use crate::config::{ConfigError, DeckFile, OptionsFile, PlankFile};
+
+// HTTP service laying a deck: `POST /plan` with a JSON body
+//
+//     {"deck": {"length": 4000, "width": 2}, "options": {"allow_cutting": true},
+//      "planks": [{"length": 2400, "count": 4}]}
+//
+// the deck, options and planks being written like in the project files, see `config`. The
+// response is the calepinage, `{"calepinage": ...}`, or `{"error": ...}` with a 400 status
+// for invalid requests and 422 when the deck can't be laid, the error being the error enum
+// of the constructor or solver that failed, like `{"error": {"unlayable": "NotEnoughPlanks"}}`. Every connection is served on
+// its own thread, HTTP/1.1 without keep-alive. Requests are bounded so that no client can
+// hold the server: their size, the time to send them, the planks they count and the time
+// every solver may take.
+
+/// Requests with a larger body are rejected.
+const MAX_BODY_LENGTH: usize = 1 << 20;
+/// Longest request line or header line, its line break included.
+const MAX_LINE_LENGTH: usize = 8 << 10;
+const MAX_HEADERS: usize = 100;
+/// Time a client has to send the whole request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Connections served at the same time, the others being answered as unavailable.
+const MAX_CONNECTIONS: usize = 64;
+/// Requests planning a larger heap are rejected.
+const MAX_PLANKS: usize = 100_000;
+/// Budget of the solvers, see `calepine_with_budget`.
+const SEARCH_TIME: Duration = Duration::from_secs(5);
+const SEARCH_NODES: usize = 1_000_000;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PlanRequest {
+    deck: DeckFile,
+    #[serde(default)]
+    options: OptionsFile,
+    planks: Vec<PlankFile>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum PlanResponse {
+    Calepinage(Calepinage),
+    Error(RequestError),
+}
+
+/// Why a request failed.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum RequestError {
+    /// The HTTP request can't be served.
+    Http(String),
+    /// The JSON body isn't a request, or its values don't go together.
+    Invalid(ConfigError),
+    TooManyPlanks { max_planks: usize },
+    Deck(DeckError),
+    Plank(PlankError),
+    Unlayable(CalepinageError),
+}
+
+impl From<ConfigError> for RequestError {
+    fn from(error: ConfigError) -> Self {
+        match error {
+            ConfigError::Deck(error) => RequestError::Deck(error),
+            ConfigError::Plank(error) => RequestError::Plank(error),
+            error => RequestError::Invalid(error),
+        }
+    }
+}
+
+/// Serves the requests of the listener connections until accepting a connection fails.
+pub fn serve(listener: &TcpListener) -> std::io::Result<()> {
+    let connections = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let stream = stream?;
+        if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            connections.fetch_sub(1, Ordering::SeqCst);
+            let _ = write_response(stream, 503, &error_body(RequestError::Http("too many connections".to_string())));
+            continue;
+        }
+        let connections = Arc::clone(&connections);
+        std::thread::spawn(move || {
+            // a client going away only ends its own connection
+            let _ = handle_connection(stream);
+            connections.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream) -> std::io::Result<()> {
+    let deadline = Instant::now() + REQUEST_TIMEOUT;
+    let (status, body) = match read_request(Deadline { stream: &stream, deadline })? {
+        Ok((method, path, body)) => respond(&method, &path, &body),
+        Err((status, error)) => (status, error_body(RequestError::Http(error))),
+    };
+    write_response(stream, status, &body)
+}
+
+fn write_response(mut stream: TcpStream, status: u16, body: &str) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Unprocessable Entity",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// A connection read until the deadline, reads failing with a timeout past it.
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(std::io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+type Request = Result<(String, String, Vec<u8>), (u16, String)>;
+
+/// The method, path and body of the request, or the status and message of the error.
+fn read_request(stream: impl Read) -> std::io::Result<Request> {
+    let mut reader = BufReader::new(stream);
+    let request_line = match read_line(&mut reader)? {
+        Some(line) => line,
+        None => return Ok(Err((431, "request line too long".to_string()))),
+    };
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Ok(Err((400, "malformed request line".to_string()))),
+    };
+    let mut content_length = None;
+    let mut headers = 0;
+    loop {
+        let header = match read_line(&mut reader)? {
+            Some(header) => header,
+            None => return Ok(Err((431, "header line too long".to_string()))),
+        };
+        if header.trim().is_empty() {
+            break;
+        }
+        headers += 1;
+        if headers > MAX_HEADERS {
+            return Ok(Err((431, "too many headers".to_string())));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    if method != "POST" {
+        return Ok(Ok((method, path, vec![])));
+    }
+    let content_length = match content_length {
+        Some(length) if length > MAX_BODY_LENGTH => return Ok(Err((413, "request body too large".to_string()))),
+        Some(length) => length,
+        None => return Ok(Err((411, "missing content length".to_string()))),
+    };
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Ok((method, path, body)))
+}
+
+/// The next line, empty at the end of the stream, none when it is longer than
+/// `MAX_LINE_LENGTH`.
+fn read_line(reader: &mut impl BufRead) -> std::io::Result<Option<String>> {
+    let mut line = String::new();
+    reader.take(MAX_LINE_LENGTH as u64).read_line(&mut line)?;
+    if line.len() == MAX_LINE_LENGTH && !line.ends_with('\n') {
+        return Ok(None);
+    }
+    Ok(Some(line))
+}
+
+fn respond(method: &str, path: &str, body: &[u8]) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/plan") => plan(body),
+        (_, "/plan") => (405, error_body(RequestError::Http("only POST is allowed".to_string()))),
+        _ => (404, error_body(RequestError::Http(format!("no endpoint at {}", path)))),
+    }
+}
+
+/// Lays the deck of the request, returning the status and the JSON body of the response.
+pub fn plan(body: &[u8]) -> (u16, String) {
+    plan_within(body, Budget::default().with_time(SEARCH_TIME).with_nodes(SEARCH_NODES))
+}
+
+fn plan_within(body: &[u8], budget: Budget) -> (u16, String) {
+    let request = match serde_json::from_slice::<PlanRequest>(body) {
+        Ok(request) => request,
+        Err(error) => return (400, error_body(RequestError::Invalid(ConfigError::Syntax(error.to_string())))),
+    };
+    let PlanRequest { deck, options, planks } = request;
+    let laid = deck.deck().map_err(RequestError::from).and_then(|deck| {
+        let options = options.options()?;
+        let counts = PlankFile::counts(planks).map_err(RequestError::Plank)?;
+        let count = counts.iter().try_fold(0, |total: usize, (_, count)| total.checked_add(*count));
+        if count.is_none_or(|count| count > MAX_PLANKS) {
+            return Err(RequestError::TooManyPlanks { max_planks: MAX_PLANKS });
+        }
+        let plank_heap = PlankHeap::from_counts(counts).map_err(RequestError::Plank)?;
+        Ok((deck, options, plank_heap))
+    });
+    let (deck, options, plank_heap) = match laid {
+        Ok(laid) => laid,
+        Err(error) => return (400, error_body(error)),
+    };
+    let laid = match options.solver {
+        // the greedy solver lays a single calepinage, within the time of the budget
+        Solver::Greedy => {
+            let options = match budget.time {
+                Some(time) => options.with_deadline(Instant::now() + time),
+                None => options,
+            };
+            calepine_with_options(plank_heap, deck, options)
+        }
+        Solver::Exhaustive | Solver::BranchAndBound => {
+            calepine_with_budget(plank_heap, deck, options, budget).map(|budgeted| budgeted.calepinage)
+        }
+    };
+    match laid {
+        Ok(calepinage) => (200, to_json(&PlanResponse::Calepinage(calepinage))),
+        Err(error) => (422, error_body(RequestError::Unlayable(error))),
+    }
+}
+
+fn error_body(error: RequestError) -> String {
+    to_json(&PlanResponse::Error(error))
+}
+
+fn to_json(response: &PlanResponse) -> String {
+    serde_json::to_string(response).expect("responses are always serializable")
+}
+
+#[test]
+fn plan_should_return_the_calepinage() {
+    let body = br#"{"deck": {"length": 10, "width": 1}, "planks": [{"length": 5, "count": 2}]}"#;
+
+    let (status, response) = plan(body);
+
+    assert_eq!(200, status);
+    let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+    let planks = &response["calepinage"]["lines"][0]["planks"];
+    assert_eq!(2, planks.as_array().unwrap().len());
+    assert_eq!(5, planks[0]["length"]);
+}
+
+#[test]
+fn plan_should_tell_invalid_requests_from_unlayable_decks() {
+    assert_eq!(400, plan(b"{\"deck\": {}").0);
+    assert_eq!(
+        (400, r#"{"error":{"deck":{"ZeroDimension":"Length"}}}"#.to_string()),
+        plan(br#"{"deck": {"length": 0, "width": 1}, "planks": []}"#)
+    );
+    assert_eq!(
+        (422, r#"{"error":{"unlayable":"NotEnoughPlanks"}}"#.to_string()),
+        plan(br#"{"deck": {"length": 10, "width": 1}, "planks": [{"length": 5}]}"#)
+    );
+}
+
+#[test]
+fn serve_should_answer_over_http() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || serve(&listener));
+
+    let body = r#"{"deck": {"length": 10, "width": 1}, "planks": [{"length": 10}]}"#;
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "POST /plan HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    let body: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(10, body["calepinage"]["lines"][0]["planks"][0]["length"]);
+}
+
+#[test]
+fn plan_should_reject_heaps_too_large_to_lay() {
+    let body = br#"{"deck": {"length": 10, "width": 1}, "planks": [{"length": 5, "count": 10000000000}]}"#;
+
+    assert_eq!((400, r#"{"error":{"too_many_planks":{"max_planks":100000}}}"#.to_string()), plan(body));
+}
+
+#[test]
+fn read_request_should_reject_oversized_headers() {
+    let long_header = format!("POST /plan HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(MAX_LINE_LENGTH));
+    let many_headers = format!("POST /plan HTTP/1.1\r\n{}\r\n", "X-Padding: a\r\n".repeat(MAX_HEADERS + 1));
+
+    assert_eq!(Some(431), read_request(long_header.as_bytes()).unwrap().err().map(|(status, _)| status));
+    assert_eq!(Some(431), read_request(many_headers.as_bytes()).unwrap().err().map(|(status, _)| status));
+}
+
+#[test]
+fn serve_should_not_wait_for_slow_clients() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || serve(&listener));
+
+    let mut slow = TcpStream::connect(address).unwrap();
+    write!(slow, "POST /plan HTTP/1.1\r\n").unwrap();
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "GET /plan HTTP/1.1\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+}
+
+#[test]
+fn plan_should_budget_the_exhaustive_solver() {
+    let body = br#"{"deck": {"length": 10, "width": 2}, "options": {"solver": "exhaustive"},
+        "planks": [{"length": 6, "count": 2}, {"length": 4, "count": 2}]}"#;
+
+    let (status, response) = plan(body);
+
+    assert_eq!(200, status);
+    let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(2, response["calepinage"]["lines"].as_array().unwrap().len());
+}
+
+#[test]
+fn plan_should_return_structured_errors() {
+    let body = br#"{"deck": {"length": 10, "width": 1}, "planks": [{"length": 4}, {"length": 7}]}"#;
+
+    let (status, response) = plan(body);
+
+    assert_eq!(422, status);
+    let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+    let expected = serde_json::json!({"error": {"unlayable": {
+        "OnlyUnusablePlanksRemaining": {"row": 0, "needed_length": 3, "remaining_lengths": [4]}
+    }}});
+    assert_eq!(expected, response);
+    let (status, response) = plan(br#"{"deck": {"width": 2}, "planks": []}"#);
+    assert_eq!(
+        (400, r#"{"error":{"invalid":"MissingDimensions"}}"#),
+        (status, response.as_str())
+    );
+    let body = br#"{"deck": {"length": 10, "width": 1}, "options": {"substructure": {"joist_spacing": 0,
+        "first_joist_offset": 0, "tolerance": 0}}, "planks": []}"#;
+    assert_eq!((400, r#"{"error":{"invalid":{"Substructure":"ZeroJoistSpacing"}}}"#.to_string()), plan(body));
+}
+
+#[test]
+fn plan_should_budget_the_greedy_solver() {
+    let planks: Vec<String> = (1..=10).map(|length| format!(r#"{{"length": {}, "count": 30}}"#, length)).collect();
+    let body = format!(
+        r#"{{"deck": {{"length": 60, "width": 2}}, "options": {{"min_junction_offset": 60}}, "planks": [{}]}}"#,
+        planks.join(", ")
+    );
+    let start = Instant::now();
+
+    let laid = plan_within(body.as_bytes(), Budget::default().with_time(Duration::from_millis(100)));
+
+    assert_eq!((422, r#"{"error":{"unlayable":"OutOfBudget"}}"#.to_string()), laid);
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
@@ -0,0 +1,92 @@
+use crate::calepinage::{calepine_deck, Calepinage, CalepinageOptions, Deck, DeckError, PlankHeap};
+use crate::project::ZoneError;
+
+// Stairs going down from the deck: every tread is a small deck as long as the stairs are
+// wide, laid with the planks the deck surface leaves, tread after tread.
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Stairs {
+    pub treads: usize,
+    /// Width of the stairs, the length of the tread boards.
+    pub width: usize,
+    /// Depth of a tread, measured like the deck width.
+    pub depth: usize,
+}
+
+impl Stairs {
+    pub fn new(treads: usize, width: usize, depth: usize) -> Result<Self, DeckError> {
+        Deck::new(width, depth)?;
+        Ok(Stairs { treads, width, depth })
+    }
+
+    /// A tread, as physical as the deck it goes down from.
+    pub fn tread(&self, deck: &Deck) -> Deck {
+        match deck.board_width {
+            Some(board_width) => Deck::physical(self.width, self.depth, board_width),
+            None => Deck::new(self.width, self.depth),
+        }
+        .expect("the stairs dimensions and the deck board width are checked already")
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct StairsPlan {
+    pub deck: Calepinage,
+    /// Top tread first.
+    pub treads: Vec<Calepinage>,
+    pub remaining: PlankHeap,
+}
+
+/// Lays the deck then every tread with the planks left. On failure, the zone is 0 for the
+/// deck and the tread number, from 1 at the top, for a tread.
+pub fn calepine_with_stairs(
+    plank_heap: PlankHeap,
+    deck: &Deck,
+    stairs: &Stairs,
+    options: &CalepinageOptions,
+) -> Result<StairsPlan, ZoneError> {
+    let (surface, mut the_plank_heap) =
+        calepine_deck(plank_heap, deck, options).map_err(|error| ZoneError { zone: 0, error })?;
+    let tread = stairs.tread(deck);
+    let mut treads = vec![];
+    for zone in 1..=stairs.treads {
+        let (calepinage, remaining) =
+            calepine_deck(the_plank_heap, &tread, options).map_err(|error| ZoneError { zone, error })?;
+        treads.push(calepinage);
+        the_plank_heap = remaining;
+    }
+    Ok(StairsPlan {
+        deck: surface,
+        treads,
+        remaining: the_plank_heap,
+    })
+}
+
+#[test]
+fn treads_should_be_laid_with_the_planks_the_deck_leaves() {
+    let deck = Deck::physical(2000, 280, 140).unwrap();
+    let stairs = Stairs::new(3, 900, 280).unwrap();
+    let plank_heap = PlankHeap::new().add(6, 1000).add(2, 2000);
+    let options = CalepinageOptions::default().with_allow_cutting(true);
+
+    let plan = calepine_with_stairs(plank_heap, &deck, &stairs, &options).unwrap();
+
+    assert_eq!(2, plan.deck.lines.len());
+    assert!(plan.deck.cuts.is_empty());
+    assert_eq!(3, plan.treads.len());
+    assert!(plan.treads.iter().all(|tread| tread.lines.len() == 2 && tread.lines.iter().all(|line| line.length() == 900)));
+    assert_eq!(6, plan.treads.iter().map(|tread| tread.cuts.len()).sum::<usize>());
+    assert!(plan.remaining.is_empty());
+}
+
+#[test]
+fn failing_tread_should_be_reported_with_its_number() {
+    let deck = Deck::new(10, 1).unwrap();
+    let stairs = Stairs::new(2, 5, 1).unwrap();
+    let plank_heap = PlankHeap::new().add(1, 10).add(1, 5);
+
+    let error = calepine_with_stairs(plank_heap, &deck, &stairs, &CalepinageOptions::default()).unwrap_err();
+
+    assert_eq!(2, error.zone);
+    assert!(Stairs::new(2, 0, 1).is_err());
+}
@@ -0,0 +1,137 @@
+use crate::calepinage::{Calepinage, Deck, Plank, PlankHeap};
+
+// Figures about a calepinage laid with a heap, for reports. Boards of the heap are told
+// apart from the offcuts put back in the heap by matching them with the heap planks.
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Stats {
+    /// Part of the length of the boards used that isn't laid, offcuts and saw kerf.
+    pub waste_percentage: f64,
+    /// Boards of the heap laid whole or cut.
+    pub boards_used: usize,
+    pub boards_left_over: usize,
+    pub junctions: usize,
+    /// Mean distance between a junction and the nearest junction of the line before, if any.
+    pub average_stagger: Option<f64>,
+    /// Part of the deck area covered by the boards laid, ignoring the gaps.
+    pub coverage: f64,
+}
+
+impl std::fmt::Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} boards used, {} left over, {:.1}% wasted, {:.1}% covered, {} junctions",
+            self.boards_used,
+            self.boards_left_over,
+            self.waste_percentage,
+            self.coverage * 100.0,
+            self.junctions
+        )?;
+        if let Some(stagger) = self.average_stagger {
+            write!(f, " {:.0} apart on average", stagger)?;
+        }
+        Ok(())
+    }
+}
+
+impl Calepinage {
+    pub fn stats(&self, plank_heap: &PlankHeap, deck: &Deck) -> Stats {
+        let laid: Vec<&Plank> = self
+            .lines
+            .iter()
+            .chain(self.border.iter())
+            .chain(self.breakers.iter().map(|breaker| &breaker.line))
+            .flat_map(|line| line.planks.iter())
+            .collect();
+
+        let mut left_over: Vec<&Plank> = plank_heap.planks().collect();
+        let mut pieces: Vec<Plank> = vec![];
+        for cut in self.cuts.iter() {
+            take(&mut left_over, &cut.plank);
+            pieces.push(cut.piece());
+        }
+        for plank in laid.iter() {
+            match pieces.iter().position(|piece| piece == *plank) {
+                Some(index) => {
+                    pieces.remove(index);
+                }
+                None => take(&mut left_over, plank),
+            }
+        }
+
+        let used_length = area(plank_heap.planks()) - area(left_over.iter().copied());
+        let laid_length = area(laid.iter().copied());
+        let deck_width = deck.board_width.map_or(deck.width, |board_width| deck.width / board_width);
+        Stats {
+            waste_percentage: percentage(used_length.saturating_sub(laid_length), used_length),
+            boards_used: plank_heap.len() - left_over.len(),
+            boards_left_over: left_over.len(),
+            junctions: self.lines.iter().map(|line| line.compute_junction().len()).sum(),
+            average_stagger: self.average_stagger(),
+            coverage: percentage(laid_length, deck.length * deck_width) / 100.0,
+        }
+    }
+
+    fn average_stagger(&self) -> Option<f64> {
+        let staggers: Vec<usize> = self
+            .lines
+            .windows(2)
+            .flat_map(|lines| {
+                let previous = lines[0].compute_junction();
+                lines[1]
+                    .compute_junction()
+                    .into_iter()
+                    .filter_map(move |junction| previous.iter().map(|other| other.distance(&junction)).min())
+            })
+            .collect();
+        if staggers.is_empty() {
+            return None;
+        }
+        Some(staggers.iter().sum::<usize>() as f64 / staggers.len() as f64)
+    }
+}
+
+/// Removes a plank from the left over ones, if it is one of them.
+fn take(left_over: &mut Vec<&Plank>, plank: &Plank) {
+    if let Some(index) = left_over.iter().position(|candidate| *candidate == plank) {
+        left_over.remove(index);
+    }
+}
+
+/// Length of the planks, a plank as wide as several rows counting once for every row.
+fn area<'a>(planks: impl Iterator<Item = &'a Plank>) -> usize {
+    planks.map(|plank| plank.length * plank.width).sum()
+}
+
+fn percentage(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+#[test]
+fn stats_should_count_boards_used_and_waste() {
+    use crate::calepinage::{calepine_with_options, CalepinageOptions};
+
+    let deck = Deck::new(10, 2).unwrap();
+    let plank_heap = PlankHeap::new().add(2, 6).add(2, 5);
+    let options = CalepinageOptions::default().with_allow_cutting(true);
+    let calepinage = calepine_with_options(plank_heap.clone(), deck.clone(), options).unwrap();
+
+    let stats = calepinage.stats(&plank_heap, &deck);
+
+    assert_eq!(
+        Stats {
+            waste_percentage: 100.0 * 2.0 / 22.0,
+            boards_used: 4,
+            boards_left_over: 0,
+            junctions: 2,
+            average_stagger: Some(1.0),
+            coverage: 1.0,
+        },
+        stats
+    );
+}
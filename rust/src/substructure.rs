@@ -0,0 +1,252 @@
+use crate::calepinage::{Calepinage, Deck, Orientation};
+use crate::messages::{Language, Localize};
+#[cfg(test)]
+use crate::calepinage::{Line, Plank};
+#[cfg(test)]
+use crate::plank_line;
+
+// Joists supporting the deck, perpendicular to the rows and evenly spaced along the deck
+// length. Coordinates are along the deck length, like junctions. The framing of a deck, its
+// joists and the beams under them, can be generated from the spacing rules.
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Substructure {
+    pub joist_spacing: usize,
+    /// Distance from the start of the deck to the center of the first joist.
+    pub first_joist_offset: usize,
+}
+
+impl std::fmt::Display for Substructure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "joists every {} from {}", self.joist_spacing, self.first_joist_offset)
+    }
+}
+
+impl Substructure {
+    pub fn new(joist_spacing: usize, first_joist_offset: usize) -> Result<Self, FramingError> {
+        if joist_spacing == 0 {
+            Err(FramingError::ZeroJoistSpacing)
+        } else {
+            Ok(Substructure {
+                joist_spacing,
+                first_joist_offset,
+            })
+        }
+    }
+
+    /// Distance from the coordinate to the center of the nearest joist.
+    pub fn distance_to_joist(&self, coordinate: usize) -> usize {
+        if coordinate <= self.first_joist_offset {
+            return self.first_joist_offset - coordinate;
+        }
+        let past_joist = (coordinate - self.first_joist_offset) % self.joist_spacing;
+        past_joist.min(self.joist_spacing - past_joist)
+    }
+
+    pub fn is_on_joist(&self, coordinate: usize, tolerance: usize) -> bool {
+        self.distance_to_joist(coordinate) <= tolerance
+    }
+}
+
+/// How far apart joists and beams may be, for the boards and the joists not to sag.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FramingRules {
+    pub max_joist_spacing: usize,
+    /// Longest part of a joist between two beams, or past the last beam.
+    pub max_joist_span: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FramingError {
+    ZeroJoistSpacing,
+    ZeroJoistSpan,
+    /// The deck is only known in rows, the boards having no width.
+    UnknownBoardWidth,
+}
+
+impl std::fmt::Display for FramingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_in(f, Language::English)
+    }
+}
+
+impl std::error::Error for FramingError {}
+
+impl FramingRules {
+    pub fn new(max_joist_spacing: usize, max_joist_span: usize) -> Result<Self, FramingError> {
+        if max_joist_spacing == 0 {
+            Err(FramingError::ZeroJoistSpacing)
+        } else if max_joist_span == 0 {
+            Err(FramingError::ZeroJoistSpan)
+        } else {
+            Ok(FramingRules {
+                max_joist_spacing,
+                max_joist_span,
+            })
+        }
+    }
+}
+
+/// A joist or a beam, `at` being the distance from the deck side to its center.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Member {
+    pub at: usize,
+    pub length: usize,
+}
+
+/// Joists across the boards, at both ends of the deck and evenly spaced in between, lying
+/// on beams along the boards, at both sides of the deck and evenly spaced in between.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Framing {
+    pub joists: Vec<Member>,
+    pub beams: Vec<Member>,
+    /// The joists as laid for the options of the calepinage, the joist at the deck end
+    /// being off the spacing.
+    pub substructure: Substructure,
+}
+
+impl Framing {
+    pub fn joist_count(&self) -> usize {
+        self.joists.len()
+    }
+
+    pub fn beam_count(&self) -> usize {
+        self.beams.len()
+    }
+
+    /// Total length of the joists and beams to buy.
+    pub fn lumber_length(&self) -> usize {
+        self.joists.iter().chain(self.beams.iter()).map(|member| member.length).sum()
+    }
+}
+
+/// Framing of a physical deck whose boards run in the orientation, the joists being
+/// spaced along the boards.
+pub fn framing(deck: &Deck, orientation: Orientation, rules: &FramingRules) -> Result<Framing, FramingError> {
+    if deck.board_width.is_none() {
+        return Err(FramingError::UnknownBoardWidth);
+    }
+    let (along_boards, across_boards) = match orientation {
+        Orientation::AlongLength => (deck.length, deck.width),
+        Orientation::AlongWidth => (deck.width, deck.length),
+    };
+    let (joist_spacing, joists) = evenly_spaced(along_boards, rules.max_joist_spacing);
+    let (_, beams) = evenly_spaced(across_boards, rules.max_joist_span);
+    Ok(Framing {
+        joists: joists.into_iter().map(|at| Member { at, length: across_boards }).collect(),
+        beams: beams.into_iter().map(|at| Member { at, length: along_boards }).collect(),
+        substructure: Substructure {
+            joist_spacing,
+            first_joist_offset: 0,
+        },
+    })
+}
+
+/// The spacing and the positions from 0 to `length`, ends included, no further apart than
+/// `max_spacing`.
+fn evenly_spaced(length: usize, max_spacing: usize) -> (usize, Vec<usize>) {
+    let intervals = length.div_ceil(max_spacing).max(1);
+    let spacing = length.div_ceil(intervals).max(1);
+    let mut positions: Vec<usize> = (0..intervals).map(|interval| interval * spacing).collect();
+    positions.push(length);
+    (spacing, positions)
+}
+
+/// A butt joint off the joists, needing a doubled joist or blocking under it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Blocking {
+    /// Index of the line in the calepinage.
+    pub line: usize,
+    /// Center of the butt joint.
+    pub at: usize,
+}
+
+impl std::fmt::Display for Blocking {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: blocking at {}", self.line, self.at)
+    }
+}
+
+/// Lists the butt joints of the calepinage lines off the joists. Setting the substructure
+/// in the options instead makes the solver keep every butt joint on a joist.
+pub fn blocking_required(calepinage: &Calepinage, substructure: &Substructure, tolerance: usize) -> Vec<Blocking> {
+    calepinage
+        .lines
+        .iter()
+        .enumerate()
+        .flat_map(|(index, line)| {
+            line.compute_junction().into_iter().map(move |junction| Blocking {
+                line: index,
+                at: junction.coordinate() + line.gap / 2,
+            })
+        })
+        .filter(|blocking| !substructure.is_on_joist(blocking.at, tolerance))
+        .collect()
+}
+
+#[test]
+fn blocking_should_be_required_under_junctions_off_joists() {
+    let substructure = Substructure::new(400, 0).unwrap();
+    let calepinage = Calepinage::default()
+        .with_line(plank_line![Plank::new(800).unwrap(), Plank::new(400).unwrap()])
+        .with_line(plank_line![Plank::new(600).unwrap(), Plank::new(600).unwrap()])
+        .with_line(plank_line![Plank::new(398).unwrap(), Plank::new(802).unwrap()].with_gap(4));
+
+    let blockings = blocking_required(&calepinage, &substructure, 0);
+
+    assert_eq!(vec![Blocking { line: 1, at: 600 }], blockings);
+}
+
+#[test]
+fn distance_to_joist_should_be_to_the_nearest_joist() {
+    let substructure = Substructure::new(400, 50).unwrap();
+
+    assert_eq!(50, substructure.distance_to_joist(0));
+    assert_eq!(0, substructure.distance_to_joist(450));
+    assert_eq!(100, substructure.distance_to_joist(750));
+    assert_eq!(180, substructure.distance_to_joist(670));
+}
+
+#[test]
+fn is_on_joist_should_accept_the_tolerance() {
+    let substructure = Substructure::new(400, 0).unwrap();
+
+    assert!(substructure.is_on_joist(805, 5));
+    assert!(!substructure.is_on_joist(806, 5));
+    assert_eq!(Err(FramingError::ZeroJoistSpacing), Substructure::new(0, 0));
+}
+
+#[test]
+fn framing_should_space_joists_along_the_boards() {
+    let deck = Deck::physical(4000, 1500, 140).unwrap();
+    let rules = FramingRules::new(450, 1000).unwrap();
+
+    let framing = framing(&deck, Orientation::AlongLength, &rules).unwrap();
+
+    let joists: Vec<usize> = framing.joists.iter().map(|joist| joist.at).collect();
+    assert_eq!(vec![0, 445, 890, 1335, 1780, 2225, 2670, 3115, 3560, 4000], joists);
+    assert!(framing.joists.iter().all(|joist| joist.length == 1500));
+    let beams: Vec<usize> = framing.beams.iter().map(|beam| beam.at).collect();
+    assert_eq!(vec![0, 750, 1500], beams);
+    assert_eq!(Member { at: 750, length: 4000 }, framing.beams[1]);
+    assert_eq!(Substructure::new(445, 0).unwrap(), framing.substructure);
+    assert_eq!(10 * 1500 + 3 * 4000, framing.lumber_length());
+}
+
+#[test]
+fn framing_should_turn_with_the_boards() {
+    let deck = Deck::physical(4000, 1500, 140).unwrap();
+    let rules = FramingRules::new(500, 5000).unwrap();
+    assert_eq!(Err(FramingError::ZeroJoistSpan), FramingRules::new(500, 0));
+    assert_eq!(
+        Err(FramingError::UnknownBoardWidth),
+        framing(&Deck::new(10, 2).unwrap(), Orientation::AlongLength, &rules)
+    );
+
+    let framing = framing(&deck, Orientation::AlongWidth, &rules).unwrap();
+
+    assert_eq!(4, framing.joist_count());
+    assert!(framing.joists.iter().all(|joist| joist.length == 4000));
+    assert_eq!(2, framing.beam_count());
+}
@@ -0,0 +1,162 @@
+use crate::calepinage::{
+    check_slope, count_rows, joint_figures, line_goal, previous_lines, BalancedRip, Calepinage, CalepinageError,
+    CalepinageOptions, Deck, JointFigure, LastRow, Line, RowGoal, UncoveredRow,
+};
+
+// Checks a calepinage, computed or built by hand, against the rules the solver follows:
+// the options are the rules. Only the lines of the field are checked, against the rows of
+// the deck as the solver splits them, without border nor breakers.
+
+/// A rule the calepinage breaks, lines being given by their index in the calepinage.
+#[derive(Debug, PartialEq)]
+pub enum Violation {
+    /// The deck itself can't be laid with these options.
+    Unlayable(CalepinageError),
+    /// The line doesn't cover its part of the deck, from `start` to `start + length`.
+    WrongCoverage { line: usize, start: usize, length: usize },
+    /// The line covers no part of the deck, every row being already covered.
+    ExtraLine { line: usize },
+    Uncovered(UncoveredRow),
+    /// The junction is closer than `min_junction_offset` to a junction of the previous lines.
+    AlignedJunction { line: usize, at: usize },
+    /// The butt joint, centered on `at`, misses the joists.
+    OffJoist { line: usize, at: usize },
+    /// The first or last piece of the line is shorter than `min_end_piece`.
+    ShortEndPiece { line: usize, length: usize },
+    /// The boards of the last row are ripped narrower than `min_rip_width`, with the
+    /// balanced rips of both sides suggested instead.
+    NarrowRip {
+        width: usize,
+        min_rip_width: usize,
+        balanced: Option<BalancedRip>,
+    },
+    /// The junction ends a stair-step of the `joint_figures` of the options.
+    StairStep { line: usize, at: usize },
+    /// The junction is aligned with a junction two rows before, drawing an H.
+    HJoint { line: usize, at: usize },
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::Unlayable(error) => write!(f, "unlayable deck: {}", error),
+            Violation::WrongCoverage { line, start, length } => {
+                write!(f, "line {}: should cover from {} to {}", line, start, start + length)
+            }
+            Violation::ExtraLine { line } => write!(f, "line {}: covers no part of the deck", line),
+            Violation::Uncovered(row) => write!(f, "{}", row),
+            Violation::AlignedJunction { line, at } => write!(f, "line {}: junction at {} too close to the previous lines", line, at),
+            Violation::OffJoist { line, at } => write!(f, "line {}: butt joint at {} off the joists", line, at),
+            Violation::ShortEndPiece { line, length } => write!(f, "line {}: end piece of {} too short", line, length),
+            Violation::NarrowRip { width, min_rip_width, balanced } => {
+                write!(f, "last row ripped to {}, narrower than {}", width, min_rip_width)?;
+                match balanced {
+                    Some(balanced) => write!(f, ", {}", balanced),
+                    None => Ok(()),
+                }
+            }
+            Violation::StairStep { line, at } => write!(f, "line {}: junction at {} steps diagonally", line, at),
+            Violation::HJoint { line, at } => {
+                write!(f, "line {}: junction at {} aligned with a junction two rows before", line, at)
+            }
+        }
+    }
+}
+
+impl Calepinage {
+    pub fn validate(&self, deck: &Deck, options: &CalepinageOptions) -> Vec<Violation> {
+        let row_count = match check_slope(deck, options).and_then(|_| count_rows(deck, options)) {
+            Ok((row_count, _)) => row_count,
+            Err(error) => return vec![Violation::Unlayable(error)],
+        };
+        let goals: Vec<RowGoal> = (0..row_count)
+            .flat_map(|row| deck.row_segments(row, 0, deck.length))
+            .collect();
+
+        let mut violations = vec![];
+        if let Some(rip) = deck.rip(options.side_gap) {
+            if options.last_row == LastRow::Rip && rip.width < options.min_rip_width {
+                violations.push(Violation::NarrowRip {
+                    width: rip.width,
+                    min_rip_width: options.min_rip_width,
+                    balanced: rip.balanced,
+                });
+            }
+        }
+        let mut covered = 0;
+        let mut lines_last_row = vec![];
+        for (index, line) in self.lines.iter().enumerate() {
+            let width = line.width().max(1);
+            let goal = match line_goal(&goals[covered..], width) {
+                Some(goal) => goal,
+                None => {
+                    violations.push(Violation::ExtraLine { line: index });
+                    continue;
+                }
+            };
+            if line.start != goal.start || line.length() != goal.length {
+                violations.push(Violation::WrongCoverage {
+                    line: index,
+                    start: goal.start,
+                    length: goal.length,
+                });
+            }
+            let previous_lines = previous_lines(self, &lines_last_row, goal.row, options);
+            for junction in line.compute_junction() {
+                let at = junction.coordinate();
+                if previous_lines
+                    .junctions
+                    .iter()
+                    .any(|previous| previous.distance(&junction) < options.min_junction_offset)
+                {
+                    violations.push(Violation::AlignedJunction { line: index, at });
+                }
+                let center = at + line.gap / 2;
+                if options
+                    .substructure
+                    .is_some_and(|substructure| !substructure.is_on_joist(center, options.joist_tolerance))
+                {
+                    violations.push(Violation::OffJoist { line: index, at: center });
+                }
+            }
+            if let Some(figures) = options.joint_figures {
+                violations.extend(joint_figures(self, &lines_last_row, line, goal.row, &figures).into_iter().map(
+                    |(figure, junction)| match figure {
+                        JointFigure::StairStep => Violation::StairStep {
+                            line: index,
+                            at: junction.coordinate(),
+                        },
+                        JointFigure::H => Violation::HJoint {
+                            line: index,
+                            at: junction.coordinate(),
+                        },
+                    },
+                ));
+            }
+            violations.extend(short_end_pieces(line, options.min_end_piece).map(|length| Violation::ShortEndPiece {
+                line: index,
+                length,
+            }));
+            lines_last_row.push(goal.row + width - 1);
+            covered += width;
+        }
+        violations.extend(goals.iter().skip(covered).map(|goal| {
+            Violation::Uncovered(UncoveredRow {
+                row: goal.row,
+                start: goal.start,
+                length: goal.length,
+            })
+        }));
+        violations
+    }
+}
+
+/// Lengths of the first and last pieces shorter than `min_end_piece`, a line of a single
+/// piece having none.
+fn short_end_pieces(line: &Line, min_end_piece: usize) -> impl Iterator<Item = usize> {
+    let ends = match line.planks.as_slice() {
+        [first, .., last] => vec![first.length, last.length],
+        _ => vec![],
+    };
+    ends.into_iter().filter(move |&length| length < min_end_piece)
+}